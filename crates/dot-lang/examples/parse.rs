@@ -0,0 +1,24 @@
+//! Parses a DOT file and prints its syntax tree and any errors.
+//!
+//! # Example
+//!
+//! ```sh
+//! cargo run --example parse --package dot-lang -- packages/web/assets/dot/sequence.dot
+//! ```
+use std::env;
+use std::fs;
+
+fn main() {
+    let path = env::args().nth(1).expect("usage: parse <file.dot>");
+    let src = fs::read_to_string(&path).expect("failed to read input file");
+
+    let parse = dot_lang::parse(&src);
+    println!("{:#?}", parse.document);
+
+    for err in &parse.errors {
+        eprintln!(
+            "{path}:{}:{}: {}",
+            err.span.start.line, err.span.start.column, err.message
+        );
+    }
+}
@@ -0,0 +1,298 @@
+//! Typed syntax tree for DOT documents.
+//!
+//! The tree mirrors the grammar from the Graphviz documentation
+//! (<https://graphviz.org/doc/info/lang.html>). Every node carries the [Span]
+//! it was parsed from so tooling can map back to the source.
+use crate::span::Span;
+
+/// A parsed `.dot` file. A file may contain more than one graph.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Document {
+    pub graphs: Vec<Graph>,
+    /// All comments in source order, kept aside from the tree.
+    pub comments: Vec<Comment>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GraphKind {
+    /// `graph`, edges are written `--`
+    Graph,
+    /// `digraph`, edges are written `->`
+    Digraph,
+}
+
+impl GraphKind {
+    /// The edge operator that is valid in this kind of graph.
+    pub fn edge_op(self) -> EdgeOp {
+        match self {
+            GraphKind::Graph => EdgeOp::Undirected,
+            GraphKind::Digraph => EdgeOp::Directed,
+        }
+    }
+
+    pub fn keyword(self) -> &'static str {
+        match self {
+            GraphKind::Graph => "graph",
+            GraphKind::Digraph => "digraph",
+        }
+    }
+}
+
+/// `[strict] (graph | digraph) [ID] { stmt_list }`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Graph {
+    pub strict: bool,
+    pub kind: GraphKind,
+    pub id: Option<Id>,
+    pub stmts: Vec<Stmt>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stmt {
+    Node(NodeStmt),
+    Edge(EdgeStmt),
+    Attr(AttrStmt),
+    /// A bare `ID = ID` statement, which sets a graph attribute.
+    Assign(Attribute),
+    Subgraph(Subgraph),
+}
+
+impl Stmt {
+    pub fn span(&self) -> Span {
+        match self {
+            Stmt::Node(s) => s.span,
+            Stmt::Edge(s) => s.span,
+            Stmt::Attr(s) => s.span,
+            Stmt::Assign(s) => s.span,
+            Stmt::Subgraph(s) => s.span,
+        }
+    }
+}
+
+/// `node_id [attr_list]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeStmt {
+    pub node: NodeId,
+    pub attrs: Vec<AttrList>,
+    pub span: Span,
+}
+
+/// `ID [port]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeId {
+    pub id: Id,
+    pub port: Option<Port>,
+    pub span: Span,
+}
+
+/// `: ID [: compass_pt]` or `: compass_pt`
+///
+/// Whether the single-ID form is a port name or a compass point can only be
+/// decided against the node's shape, so the parser keeps it as `name`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Port {
+    pub name: Id,
+    pub compass: Option<Id>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EdgeOp {
+    /// `->`
+    Directed,
+    /// `--`
+    Undirected,
+}
+
+impl EdgeOp {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EdgeOp::Directed => "->",
+            EdgeOp::Undirected => "--",
+        }
+    }
+}
+
+/// `(node_id | subgraph) edgeRHS [attr_list]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct EdgeStmt {
+    pub head: EdgeTarget,
+    /// One entry per `edgeop (node_id | subgraph)` pair; never empty.
+    pub rest: Vec<EdgeRhs>,
+    pub attrs: Vec<AttrList>,
+    pub span: Span,
+}
+
+impl EdgeStmt {
+    /// All endpoints of the edge chain in order.
+    pub fn targets(&self) -> impl Iterator<Item = &EdgeTarget> {
+        std::iter::once(&self.head).chain(self.rest.iter().map(|r| &r.target))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EdgeRhs {
+    pub op: EdgeOp,
+    pub op_span: Span,
+    pub target: EdgeTarget,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EdgeTarget {
+    Node(NodeId),
+    Subgraph(Subgraph),
+}
+
+impl EdgeTarget {
+    pub fn span(&self) -> Span {
+        match self {
+            EdgeTarget::Node(n) => n.span,
+            EdgeTarget::Subgraph(s) => s.span,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AttrTarget {
+    Graph,
+    Node,
+    Edge,
+}
+
+impl AttrTarget {
+    pub fn keyword(self) -> &'static str {
+        match self {
+            AttrTarget::Graph => "graph",
+            AttrTarget::Node => "node",
+            AttrTarget::Edge => "edge",
+        }
+    }
+}
+
+/// `(graph | node | edge) attr_list`
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttrStmt {
+    pub target: AttrTarget,
+    pub attrs: Vec<AttrList>,
+    pub span: Span,
+}
+
+/// `[subgraph [ID]] { stmt_list }`, or `subgraph ID` referring to a subgraph
+/// defined elsewhere.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Subgraph {
+    /// Whether the `subgraph` keyword was written (as opposed to a bare `{ }`).
+    pub keyword: bool,
+    pub id: Option<Id>,
+    /// `false` for the bodiless `subgraph ID` reference form.
+    pub has_body: bool,
+    pub stmts: Vec<Stmt>,
+    pub span: Span,
+}
+
+impl Subgraph {
+    /// Subgraphs whose name starts with `cluster` are drawn as boxes.
+    pub fn is_cluster(&self) -> bool {
+        self.id
+            .as_ref()
+            .is_some_and(|id| id.value.starts_with("cluster"))
+    }
+}
+
+/// One bracketed `[ ... ]` group. A statement may have several.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttrList {
+    pub attrs: Vec<Attribute>,
+    pub span: Span,
+}
+
+/// `ID = ID`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attribute {
+    pub key: Id,
+    pub value: Id,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IdKind {
+    /// Alphanumeric identifier, e.g. `node_1`
+    Ident,
+    /// Numeral, e.g. `-1.5`
+    Numeral,
+    /// Double-quoted string; possibly several joined with `+`
+    Quoted,
+    /// HTML string `<...>`
+    Html,
+}
+
+/// A DOT `ID`.
+///
+/// `value` is the semantic value: quotes and the outer `<` `>` are removed,
+/// `\"` is unescaped and line continuations are joined. Other backslash escapes
+/// (`\n`, `\l`, `\N`, ...) are left in place since they mean something to
+/// Graphviz rather than to the DOT grammar.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Id {
+    pub kind: IdKind,
+    pub value: String,
+    pub span: Span,
+}
+
+impl Id {
+    pub fn as_str(&self) -> &str {
+        &self.value
+    }
+
+    pub fn is_html(&self) -> bool {
+        self.kind == IdKind::Html
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CommentKind {
+    /// `// ...`
+    Line,
+    /// `/* ... */`
+    Block,
+    /// `# ...` at the start of a line
+    Preprocessor,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comment {
+    pub kind: CommentKind,
+    /// The full comment text including its delimiters.
+    pub text: String,
+    pub span: Span,
+}
+
+// ------------------------- Traversal -------------------------
+
+/// Call `f` for every statement in `stmts`, depth first, including those nested
+/// in subgraphs and in subgraphs used as edge endpoints.
+pub fn walk_stmts<'a>(stmts: &'a [Stmt], f: &mut impl FnMut(&'a Stmt)) {
+    for stmt in stmts {
+        f(stmt);
+        match stmt {
+            Stmt::Subgraph(sub) => walk_stmts(&sub.stmts, f),
+            Stmt::Edge(edge) => {
+                for target in edge.targets() {
+                    if let EdgeTarget::Subgraph(sub) = target {
+                        walk_stmts(&sub.stmts, f);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Graph {
+    /// Every statement in the graph, including nested ones. See [walk_stmts].
+    pub fn walk(&self, mut f: impl FnMut(&Stmt)) {
+        walk_stmts(&self.stmts, &mut f);
+    }
+}
@@ -0,0 +1,567 @@
+//! Built-in table of Graphviz attributes.
+//!
+//! Transcribed from <https://graphviz.org/doc/info/attrs.html>. Only the
+//! attributes that matter for the `dot` layout engine and SVG output are
+//! listed; engine-specific ones for `neato`, `fdp` etc. are included where they
+//! are commonly seen in the wild so they don't get flagged as unknown.
+
+/// Where in a graph an attribute can be set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Context {
+    Graph,
+    Subgraph,
+    Cluster,
+    Node,
+    Edge,
+}
+
+impl Context {
+    fn bit(self) -> u8 {
+        match self {
+            Context::Graph => G,
+            Context::Subgraph => S,
+            Context::Cluster => C,
+            Context::Node => N,
+            Context::Edge => E,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Context::Graph => "graphs",
+            Context::Subgraph => "subgraphs",
+            Context::Cluster => "clusters",
+            Context::Node => "nodes",
+            Context::Edge => "edges",
+        }
+    }
+}
+
+const G: u8 = 1;
+const S: u8 = 1 << 1;
+const C: u8 = 1 << 2;
+const N: u8 = 1 << 3;
+const E: u8 = 1 << 4;
+
+/// The type of value an attribute accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueKind {
+    Bool,
+    Int,
+    Double,
+    Color,
+    /// `red:blue;0.3`, a weighted list of colors
+    ColorList,
+    /// One of a fixed set of keywords
+    Enum(&'static [&'static str]),
+    /// `normal`, `onormal`, `lteeoldiamond`, ...
+    ArrowType,
+    /// Comma-separated style keywords, e.g. `filled,rounded`
+    Style,
+    /// `x,y` with an optional trailing `!`
+    Point,
+    /// Plain or HTML label; may contain escape sequences
+    Label,
+    /// Free text, not checked
+    Text,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AttrInfo {
+    pub name: &'static str,
+    used_by: u8,
+    pub kind: ValueKind,
+    /// One-line summary from the Graphviz documentation.
+    pub doc: &'static str,
+}
+
+impl AttrInfo {
+    /// Whether the attribute has any effect when set in `ctx`.
+    pub fn applies_to(&self, ctx: Context) -> bool {
+        self.used_by & ctx.bit() != 0
+    }
+
+    /// The contexts this attribute can be set in.
+    pub fn contexts(&self) -> Vec<Context> {
+        [
+            Context::Graph,
+            Context::Subgraph,
+            Context::Cluster,
+            Context::Node,
+            Context::Edge,
+        ]
+        .into_iter()
+        .filter(|ctx| self.applies_to(*ctx))
+        .collect()
+    }
+
+    /// Link to the attribute's section of the Graphviz documentation.
+    pub fn doc_url(&self) -> String {
+        format!("https://graphviz.org/docs/attrs/{}/", self.name)
+    }
+}
+
+pub const SHAPES: &[&str] = &[
+    "box",
+    "polygon",
+    "ellipse",
+    "oval",
+    "circle",
+    "point",
+    "egg",
+    "triangle",
+    "plaintext",
+    "plain",
+    "diamond",
+    "trapezium",
+    "parallelogram",
+    "house",
+    "pentagon",
+    "hexagon",
+    "septagon",
+    "octagon",
+    "doublecircle",
+    "doubleoctagon",
+    "tripleoctagon",
+    "invtriangle",
+    "invtrapezium",
+    "invhouse",
+    "Mdiamond",
+    "Msquare",
+    "Mcircle",
+    "rect",
+    "rectangle",
+    "square",
+    "star",
+    "none",
+    "underline",
+    "cylinder",
+    "note",
+    "tab",
+    "folder",
+    "box3d",
+    "component",
+    "promoter",
+    "cds",
+    "terminator",
+    "utr",
+    "primersite",
+    "restrictionsite",
+    "fivepoverhang",
+    "threepoverhang",
+    "noverhang",
+    "assembly",
+    "signature",
+    "insulator",
+    "ribosite",
+    "rnastab",
+    "proteasesite",
+    "proteinstab",
+    "rpromoter",
+    "rarrow",
+    "larrow",
+    "lpromoter",
+    "record",
+    "Mrecord",
+];
+
+pub const ARROW_SHAPES: &[&str] = &[
+    "normal", "inv", "dot", "invdot", "odot", "invodot", "none", "tee", "empty", "invempty",
+    "diamond", "odiamond", "ediamond", "crow", "box", "obox", "open", "halfopen", "vee", "curve",
+    "icurve", "onormal", "oinv", "lnormal", "rnormal",
+];
+
+/// Primitive arrow shapes that can be combined with `o`/`l`/`r` modifiers.
+const ARROW_PRIMITIVES: &[&str] = &[
+    "box", "crow", "curve", "icurve", "diamond", "dot", "inv", "none", "normal", "tee", "vee",
+];
+
+/// Pre-1.x arrow names that are still accepted as a whole.
+const ARROW_LEGACY: &[&str] = &["ediamond", "open", "halfopen", "empty", "invempty"];
+
+pub const STYLES: &[&str] = &[
+    "solid",
+    "dashed",
+    "dotted",
+    "bold",
+    "invis",
+    "filled",
+    "striped",
+    "wedged",
+    "diagonals",
+    "rounded",
+    "radial",
+    "tapered",
+];
+
+const BOOLS: &[&str] = &["true", "false"];
+const RANKDIR: &[&str] = &["TB", "LR", "BT", "RL"];
+const RANK: &[&str] = &["same", "min", "source", "max", "sink"];
+const SPLINES: &[&str] = &[
+    "none", "line", "polyline", "curved", "ortho", "spline", "true", "false",
+];
+const DIR: &[&str] = &["forward", "back", "both", "none"];
+// Graphviz only looks at the first letter, so the long forms work too.
+const LABELLOC: &[&str] = &["t", "c", "b", "top", "center", "bottom"];
+const LABELJUST: &[&str] = &["l", "c", "r", "left", "center", "right"];
+const ORDERING: &[&str] = &["in", "out"];
+const CLUSTERRANK: &[&str] = &["local", "global", "none"];
+const OUTPUTORDER: &[&str] = &["breadthfirst", "nodesfirst", "edgesfirst"];
+const RATIO: &[&str] = &["fill", "compress", "expand", "auto"];
+const CHARSET: &[&str] = &["UTF-8", "utf-8", "utf8", "latin1", "ISO-8859-1", "big-5"];
+const OVERLAP: &[&str] = &[
+    "true", "false", "scale", "prism", "voronoi", "scalexy", "compress", "vpsc", "ortho",
+];
+const TARGETS: &[&str] = &["_blank", "_self", "_parent", "_top"];
+const PACKMODE: &[&str] = &["node", "clust", "graph", "array"];
+const PAGEDIR: &[&str] = &["BL", "BR", "TL", "TR", "RB", "RT", "LB", "LT"];
+
+macro_rules! attr {
+    ($name:literal, $used_by:expr, $kind:expr, $doc:literal) => {
+        AttrInfo {
+            name: $name,
+            used_by: $used_by,
+            kind: $kind,
+            doc: $doc,
+        }
+    };
+}
+
+use ValueKind::*;
+
+/// Every known attribute, sorted by name.
+pub static ATTRIBUTES: &[AttrInfo] = &[
+    attr!("_background", G, Text, "A string in the xdot format specifying an arbitrary background."),
+    attr!("area", N | C, Double, "Indicates the preferred area for a node or empty cluster (patchwork only)."),
+    attr!("arrowhead", E, ArrowType, "Style of arrowhead on the head node of an edge."),
+    attr!("arrowsize", E, Double, "Multiplicative scale factor for arrowheads."),
+    attr!("arrowtail", E, ArrowType, "Style of arrowhead on the tail node of an edge."),
+    attr!("bb", G | C, Text, "Bounding box of drawing in points (set by the layout)."),
+    attr!("beautify", G, Bool, "Whether to draw leaf nodes uniformly in a circle around the root node in sfdp."),
+    attr!("bgcolor", G | C, ColorList, "Canvas background color."),
+    attr!("center", G, Bool, "Whether to center the drawing in the output canvas."),
+    attr!("charset", G, Enum(CHARSET), "Character encoding used when interpreting string input as a text label."),
+    attr!("class", G | C | N | E, Text, "Classnames to attach to the node, edge, graph, or cluster's SVG element."),
+    attr!("cluster", C | S, Bool, "Whether the subgraph is a cluster, regardless of its name."),
+    attr!("clusterrank", G, Enum(CLUSTERRANK), "Mode used for handling clusters."),
+    attr!("color", C | N | E, ColorList, "Basic drawing color for graphics, not text."),
+    attr!("colorscheme", G | C | N | E, Text, "A color scheme namespace: the context for interpreting color names."),
+    attr!("comment", G | N | E, Text, "Comments are inserted into output."),
+    attr!("compound", G, Bool, "If true, allow edges between clusters (see lhead and ltail)."),
+    attr!("concentrate", G, Bool, "If true, use edge concentrators to merge multiedges."),
+    attr!("constraint", E, Bool, "If false, the edge is not used in ranking the nodes."),
+    attr!("Damping", G, Double, "Factor damping force motions (neato only)."),
+    attr!("decorate", E, Bool, "Whether to connect the edge label to the edge with a line."),
+    attr!("defaultdist", G, Double, "The distance between nodes in separate connected components (neato only)."),
+    attr!("dim", G, Int, "Set the number of dimensions used for the layout."),
+    attr!("dimen", G, Int, "Set the number of dimensions used for rendering."),
+    attr!("dir", E, Enum(DIR), "Edge type for drawing arrowheads."),
+    attr!("diredgeconstraints", G, Text, "Whether to constrain most edges to point downwards (neato only)."),
+    attr!("distortion", N, Double, "Distortion factor for shape=polygon."),
+    attr!("dpi", G, Double, "Specifies the expected number of pixels per inch on a display device."),
+    attr!("edgehref", E, Text, "Synonym for edgeURL."),
+    attr!("edgetarget", E, Text, "Browser window to use for the edgeURL link."),
+    attr!("edgetooltip", E, Label, "Tooltip annotation attached to the non-label part of an edge."),
+    attr!("edgeURL", E, Label, "The link for the non-label parts of an edge."),
+    attr!("epsilon", G, Double, "Terminating condition (neato only)."),
+    attr!("esep", G, Text, "Margin used around polygons for purposes of spline edge routing."),
+    attr!("fillcolor", N | E | C, ColorList, "Color used to fill the background of a node or cluster."),
+    attr!("fixedsize", N, Enum(&["true", "false", "shape"]), "Whether to use the specified width and height to determine the size of the node."),
+    attr!("fontcolor", E | N | G | C, Color, "Color used for text."),
+    attr!("fontname", E | N | G | C, Text, "Font used for text."),
+    attr!("fontnames", G, Text, "Allows user control of how basic fontnames are represented in SVG output."),
+    attr!("fontpath", G, Text, "Directory list used by libgd to search for bitmap fonts."),
+    attr!("fontsize", E | N | G | C, Double, "Font size, in points, used for text."),
+    attr!("forcelabels", G, Bool, "Whether to force placement of all xlabels, even if overlapping."),
+    attr!("gradientangle", N | C | G, Int, "If a gradient fill is being used, this determines the angle of the fill."),
+    attr!("group", N, Text, "Name for a group of nodes, for bundling edges avoiding crossings."),
+    attr!("head_lp", E, Point, "Center position of an edge's head label (set by the layout)."),
+    attr!("headclip", E, Bool, "If true, the head of an edge is clipped to the boundary of the head node."),
+    attr!("headhref", E, Text, "Synonym for headURL."),
+    attr!("headlabel", E, Label, "Text label to be placed near head of edge."),
+    attr!("headport", E, Text, "Indicates where on the head node to attach the head of the edge."),
+    attr!("headtarget", E, Text, "Browser window to use for the headURL link."),
+    attr!("headtooltip", E, Label, "Tooltip annotation attached to the head of an edge."),
+    attr!("headURL", E, Label, "If defined, headURL is output as part of the head label of the edge."),
+    attr!("height", N, Double, "Height of node, in inches."),
+    attr!("href", G | C | N | E, Label, "Synonym for URL."),
+    attr!("id", G | C | N | E, Label, "Identifier for graph objects; becomes the SVG element id."),
+    attr!("image", N, Text, "Gives the name of a file containing an image to be displayed inside a node."),
+    attr!("imagepath", G, Text, "A list of directories in which to look for image files."),
+    attr!("imagepos", N, Enum(&["tl", "tc", "tr", "ml", "mc", "mr", "bl", "bc", "br"]), "Controls how an image is positioned within its containing node."),
+    attr!("imagescale", N, Enum(&["false", "true", "width", "height", "both"]), "Controls how an image fills its containing node."),
+    attr!("inputscale", G, Double, "Scales the input positions to convert between length units (neato, fdp)."),
+    attr!("K", G | C, Double, "Spring constant used in virtual physical model (fdp, sfdp)."),
+    attr!("label", E | N | G | C, Label, "Text label attached to objects."),
+    attr!("label_scheme", G, Int, "Whether to treat a node whose name has the form |edgelabel|* as a special node (sfdp)."),
+    attr!("labelangle", E, Double, "The angle in degrees that the head or tail label is rotated off the edge."),
+    attr!("labeldistance", E, Double, "Scaling factor for the distance of headlabel/taillabel from the head/tail node."),
+    attr!("labelfloat", E, Bool, "If true, allows edge labels to be less constrained in position."),
+    attr!("labelfontcolor", E, Color, "Color used for headlabel and taillabel."),
+    attr!("labelfontname", E, Text, "Font for headlabel and taillabel."),
+    attr!("labelfontsize", E, Double, "Font size of headlabel and taillabel."),
+    attr!("labelhref", E, Text, "Synonym for labelURL."),
+    attr!("labeljust", G | C, Enum(LABELJUST), "Justification for graph and cluster labels."),
+    attr!("labelloc", N | G | C, Enum(LABELLOC), "Vertical placement of labels for nodes, root graphs and clusters."),
+    attr!("labeltarget", E, Text, "Browser window to open labelURL links in."),
+    attr!("labeltooltip", E, Label, "Tooltip annotation attached to label of an edge."),
+    attr!("labelURL", E, Label, "If defined, labelURL is the link used for the label of an edge."),
+    attr!("landscape", G, Bool, "If true, the graph is rendered in landscape mode."),
+    attr!("layer", E | N | C, Text, "Specifies layers in which the node, edge or cluster is present."),
+    attr!("layerlistsep", G, Text, "The separator characters used to split attributes of type layerRange."),
+    attr!("layers", G, Text, "A linearly ordered list of layer names attached to the graph."),
+    attr!("layerselect", G, Text, "Selects a list of layers to be emitted."),
+    attr!("layersep", G, Text, "The separator characters for splitting the layers attribute."),
+    attr!("layout", G, Enum(&["dot", "neato", "fdp", "sfdp", "circo", "twopi", "osage", "patchwork", "nop", "nop2"]), "Which layout engine to use."),
+    attr!("len", E, Double, "Preferred edge length, in inches (neato, fdp)."),
+    attr!("levels", G, Int, "Number of levels allowed in the multilevel scheme (sfdp)."),
+    attr!("levelsgap", G, Double, "Strictness of neato level constraints."),
+    attr!("lhead", E, Text, "Logical head of an edge: the name of a cluster to clip the edge at."),
+    attr!("lheight", G | C, Double, "Height of graph or cluster label, in inches (set by the layout)."),
+    attr!("linelength", G, Int, "How long strings should get before overflowing to next line, for text output."),
+    attr!("lp", E | G | C, Point, "Label center position (set by the layout)."),
+    attr!("ltail", E, Text, "Logical tail of an edge: the name of a cluster to clip the edge at."),
+    attr!("lwidth", G | C, Double, "Width of graph or cluster label, in inches (set by the layout)."),
+    attr!("margin", N | C | G, Text, "For graphs, this sets x and y margins of canvas, in inches."),
+    attr!("maxiter", G, Int, "Sets the number of iterations used (neato, fdp)."),
+    attr!("mclimit", G, Double, "Scale factor for mincross (mc) edge crossing minimiser parameters."),
+    attr!("mindist", G, Double, "Specifies the minimum separation between all nodes (circo)."),
+    attr!("minlen", E, Int, "Minimum edge length (rank difference between head and tail)."),
+    attr!("mode", G, Text, "Technique for optimizing the layout (neato, sfdp)."),
+    attr!("model", G, Text, "Specifies how the distance matrix is computed for the input graph (neato)."),
+    attr!("newrank", G, Bool, "Whether to use a single global ranking, ignoring clusters."),
+    attr!("nodesep", G, Double, "In dot, nodesep specifies the minimum space between two adjacent nodes in the same rank, in inches."),
+    attr!("nojustify", G | C | N | E, Bool, "Whether to justify multiline text vs the previous text line (rather than the side of the container)."),
+    attr!("normalize", G, Text, "Normalizes coordinates of final layout (neato, fdp, sfdp, twopi, circo)."),
+    attr!("notranslate", G, Bool, "Whether to avoid translating layout to the origin point (neato)."),
+    attr!("nslimit", G, Double, "Sets number of iterations in network simplex applications."),
+    attr!("nslimit1", G, Double, "Sets number of iterations in network simplex applications."),
+    attr!("oneblock", G, Bool, "Whether to draw circo graphs around one circle."),
+    attr!("ordering", G | N, Enum(ORDERING), "Constrains the left-to-right ordering of node edges."),
+    attr!("orientation", N | G, Text, "Node shape rotation angle, or graph orientation."),
+    attr!("outputorder", G, Enum(OUTPUTORDER), "Specify order in which nodes and edges are drawn."),
+    attr!("overlap", G, Enum(OVERLAP), "Determines if and how node overlaps should be removed (not dot)."),
+    attr!("overlap_scaling", G, Double, "Scale layout by factor, to reduce node overlap (prism, neato, sfdp)."),
+    attr!("overlap_shrink", G, Bool, "Whether the overlap removal algorithm should perform a compression pass."),
+    attr!("pack", G, Text, "Whether each connected component of the graph should be laid out separately."),
+    attr!("packmode", G, Enum(PACKMODE), "How connected components should be packed."),
+    attr!("pad", G, Text, "Inches to extend the drawing area around the minimal area needed to draw the graph."),
+    attr!("page", G, Point, "Width and height of output pages, in inches."),
+    attr!("pagedir", G, Enum(PAGEDIR), "The order in which pages are emitted."),
+    attr!("pencolor", C, Color, "Color used to draw the bounding box around a cluster."),
+    attr!("penwidth", C | N | E, Double, "Specifies the width of the pen, in points, used to draw lines and curves."),
+    attr!("peripheries", N | C, Int, "Set number of peripheries used in polygonal shapes and cluster boundaries."),
+    attr!("pin", N, Bool, "Keeps the node at the node's given input position (neato, fdp)."),
+    attr!("pos", E | N, Text, "Position of node, or spline control points."),
+    attr!("quadtree", G, Text, "Quadtree scheme to use (sfdp)."),
+    attr!("quantum", G, Double, "If quantum > 0.0, node label dimensions will be rounded to integral multiples of the quantum."),
+    attr!("rank", S, Enum(RANK), "Rank constraints on the nodes in a subgraph."),
+    attr!("rankdir", G, Enum(RANKDIR), "Sets direction of graph layout."),
+    attr!("ranksep", G, Text, "Specifies separation between ranks, in inches."),
+    attr!("ratio", G, Enum(RATIO), "Sets the aspect ratio (drawing height/drawing width) for the drawing."),
+    attr!("rects", N, Text, "Rectangles for fields of records, in points (set by the layout)."),
+    attr!("regular", N, Bool, "If true, force polygon to be regular."),
+    attr!("remincross", G, Bool, "If there are multiple clusters, whether to run edge crossing minimization a second time."),
+    attr!("repulsiveforce", G, Double, "The power of the repulsive force used in an extended Fruchterman-Reingold force directed model (sfdp)."),
+    attr!("resolution", G, Double, "Synonym for dpi."),
+    attr!("root", G | N, Text, "Specifies nodes to be used as the center of the layout (twopi, circo)."),
+    attr!("rotate", G, Int, "If rotate=90, sets drawing orientation to landscape."),
+    attr!("rotation", G, Double, "Rotates the final layout counter-clockwise by the specified number of degrees (sfdp)."),
+    attr!("samehead", E, Text, "Edges with the same head and the same samehead value are aimed at the same point on the head."),
+    attr!("sametail", E, Text, "Edges with the same tail and the same sametail value are aimed at the same point on the tail."),
+    attr!("samplepoints", N, Int, "Gives the number of points used for a circle/ellipse node."),
+    attr!("scale", G, Text, "Scales layout by the given factor after the initial layout (neato, twopi)."),
+    attr!("searchsize", G, Int, "During network simplex, the maximum number of edges with negative cut values to search."),
+    attr!("sep", G, Text, "Margin to leave around nodes when removing node overlap."),
+    attr!("shape", N, Enum(SHAPES), "Sets the shape of a node."),
+    attr!("shapefile", N, Text, "A file containing user-supplied node content."),
+    attr!("showboxes", E | N | G, Int, "Print guide boxes for debugging."),
+    attr!("sides", N, Int, "Number of sides when shape=polygon."),
+    attr!("size", G, Point, "Maximum width and height of drawing, in inches."),
+    attr!("skew", N, Double, "Skew factor for shape=polygon."),
+    attr!("smoothing", G, Text, "Specifies a post-processing step used to smooth out an uneven distribution of nodes (sfdp)."),
+    attr!("sortv", G | C | N, Int, "Sort order of graph components for ordering packmode packing."),
+    attr!("splines", G, Enum(SPLINES), "Controls how, and if, edges are represented."),
+    attr!("start", G, Text, "Parameter used to determine the initial layout of nodes (neato, fdp, sfdp)."),
+    attr!("style", E | N | C | G, Style, "Set style information for components of the graph."),
+    attr!("stylesheet", G, Text, "A URL or pathname specifying an XML style sheet, used in SVG output."),
+    attr!("tail_lp", E, Point, "Position of an edge's tail label, in points (set by the layout)."),
+    attr!("tailclip", E, Bool, "If true, the tail of an edge is clipped to the boundary of the tail node."),
+    attr!("tailhref", E, Text, "Synonym for tailURL."),
+    attr!("taillabel", E, Label, "Text label to be placed near tail of edge."),
+    attr!("tailport", E, Text, "Indicates where on the tail node to attach the tail of the edge."),
+    attr!("tailtarget", E, Text, "Browser window to use for the tailURL link."),
+    attr!("tailtooltip", E, Label, "Tooltip annotation attached to the tail of an edge."),
+    attr!("tailURL", E, Label, "If defined, tailURL is output as part of the tail label of the edge."),
+    attr!("target", E | N | G | C, Enum(TARGETS), "If the object has a URL, this attribute determines which window of the browser is used for the URL."),
+    attr!("TBbalance", G, Enum(&["min", "max"]), "Which rank to move floating (loose) nodes to."),
+    attr!("tooltip", N | E | G | C, Label, "Tooltip (mouse hover text) attached to the node, edge, cluster, or graph."),
+    attr!("truecolor", G, Bool, "Whether internal bitmap rendering relies on a truecolor color model or uses a color palette."),
+    attr!("URL", E | N | G | C, Label, "Hyperlinks incorporated into device-dependent output."),
+    attr!("vertices", N, Text, "Sets the coordinates of the vertices of the node's polygon, in inches (set by the layout)."),
+    attr!("viewport", G, Text, "Clipping window on final drawing."),
+    attr!("voro_margin", G, Double, "Tuning margin of Voronoi technique (neato, fdp, sfdp, twopi, circo)."),
+    attr!("weight", E, Double, "Weight of edge; heavier edges are kept shorter and straighter."),
+    attr!("width", N, Double, "Width of node, in inches."),
+    attr!("xdotversion", G, Text, "Determines the version of xdot used in output."),
+    attr!("xlabel", E | N, Label, "External label for a node or edge."),
+    attr!("xlp", N | E, Point, "Position of an exterior label, in points (set by the layout)."),
+    attr!("z", N, Double, "Z-coordinate value for 3D layouts and displays."),
+];
+
+/// Look up an attribute by name. Attribute names are case-sensitive.
+pub fn lookup(name: &str) -> Option<&'static AttrInfo> {
+    ATTRIBUTES.iter().find(|a| a.name == name)
+}
+
+/// The attributes that can be set in `ctx`.
+pub fn for_context(ctx: Context) -> impl Iterator<Item = &'static AttrInfo> {
+    ATTRIBUTES.iter().filter(move |a| a.applies_to(ctx))
+}
+
+impl ValueKind {
+    /// The keywords this kind accepts, for completion. Empty for free-form kinds.
+    pub fn suggestions(&self) -> &'static [&'static str] {
+        match self {
+            Bool => BOOLS,
+            Enum(values) => values,
+            ArrowType => ARROW_SHAPES,
+            Style => STYLES,
+            _ => &[],
+        }
+    }
+
+    /// Check a value, returning a message describing the problem if it isn't
+    /// valid for this kind.
+    pub fn check(&self, value: &str) -> Result<(), String> {
+        match self {
+            Bool => {
+                let lower = value.to_ascii_lowercase();
+                if matches!(lower.as_str(), "true" | "false" | "yes" | "no")
+                    || value.parse::<i64>().is_ok()
+                {
+                    Ok(())
+                } else {
+                    Err(format!("'{value}' is not a boolean"))
+                }
+            }
+            Int => value
+                .parse::<i64>()
+                .map(|_| ())
+                .map_err(|_| format!("'{value}' is not an integer")),
+            Double => value
+                .parse::<f64>()
+                .map(|_| ())
+                .map_err(|_| format!("'{value}' is not a number")),
+            Color | ColorList => check_colors(value),
+            Enum(values) => {
+                if values.contains(&value) {
+                    Ok(())
+                } else {
+                    Err(unknown_value(value, values))
+                }
+            }
+            ArrowType => check_arrow(value),
+            Style => {
+                for part in value.split(',').map(str::trim) {
+                    // setlinewidth(2) and friends take arguments.
+                    let name = part.split('(').next().unwrap_or(part);
+                    if name != "setlinewidth" && !STYLES.contains(&name) {
+                        return Err(unknown_value(name, STYLES));
+                    }
+                }
+                Ok(())
+            }
+            Point => {
+                let coords = value.trim_end_matches('!');
+                let ok = coords
+                    .split(',')
+                    .all(|c| !c.is_empty() && c.trim().parse::<f64>().is_ok());
+                if ok {
+                    Ok(())
+                } else {
+                    Err(format!("'{value}' is not a point like '1,2'"))
+                }
+            }
+            Label | Text => Ok(()),
+        }
+    }
+}
+
+fn unknown_value(value: &str, values: &[&str]) -> String {
+    match closest(value, values.iter().copied()) {
+        Some(suggestion) => format!("unknown value '{value}'; did you mean '{suggestion}'?"),
+        None => format!("unknown value '{value}'"),
+    }
+}
+
+fn check_colors(value: &str) -> Result<(), String> {
+    // Only `#rrggbb[aa]` values can be checked without the full X11/SVG color
+    // table; names are left to Graphviz.
+    for color in value.split(':') {
+        let color = color.split(';').next().unwrap_or(color).trim();
+        if let Some(hex) = color.strip_prefix('#') {
+            let valid =
+                matches!(hex.len(), 3 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit());
+            if !valid {
+                return Err(format!("'{color}' is not a valid hex color"));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn check_arrow(value: &str) -> Result<(), String> {
+    if ARROW_LEGACY.contains(&value) {
+        return Ok(());
+    }
+    // Up to four `[o][l|r]shape` groups, e.g. `lteeoldiamond`.
+    let mut rest = value;
+    let mut groups = 0;
+    while !rest.is_empty() {
+        rest = rest.strip_prefix('o').unwrap_or(rest);
+        rest = rest
+            .strip_prefix('l')
+            .or_else(|| rest.strip_prefix('r'))
+            .unwrap_or(rest);
+        let Some(shape) = ARROW_PRIMITIVES.iter().find(|s| rest.starts_with(**s)) else {
+            return Err(unknown_value(value, ARROW_SHAPES));
+        };
+        rest = &rest[shape.len()..];
+        groups += 1;
+    }
+    if groups == 0 || groups > 4 {
+        return Err(unknown_value(value, ARROW_SHAPES));
+    }
+    Ok(())
+}
+
+/// The candidate closest to `word` by edit distance, if it is close enough to
+/// plausibly be a typo.
+pub fn closest<'a>(word: &str, candidates: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    let max = (word.chars().count() / 3).clamp(1, 3);
+    candidates
+        .map(|c| {
+            (
+                edit_distance(&word.to_ascii_lowercase(), &c.to_ascii_lowercase()),
+                c,
+            )
+        })
+        .filter(|(d, _)| *d <= max)
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, c)| c)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut cur = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            cur[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
@@ -0,0 +1,366 @@
+//! Context-aware completion for DOT source.
+//!
+//! Like the highlighter this works from the token stream, since the statement
+//! around the cursor is usually incomplete while the user is typing it. Node
+//! IDs come from a recovering parse of the whole document.
+use std::collections::BTreeSet;
+use std::ops::Range;
+
+use crate::ast::{EdgeTarget, Stmt};
+use crate::attrs::{self, Context};
+use crate::format::{is_plain_ident, quote};
+use crate::lexer::{tokenize, Token, TokenKind};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CompletionKind {
+    Keyword,
+    Attribute,
+    Value,
+    Node,
+    Subgraph,
+    /// A `.dot` file in storage, for `URL` and `href`
+    Link,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    /// What to show in the list.
+    pub label: String,
+    /// What to put in the source, quoted if needed.
+    pub insert: String,
+    pub kind: CompletionKind,
+    /// A short description, e.g. the attribute's documentation.
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completions {
+    /// The byte range of the source an accepted item replaces.
+    pub range: Range<usize>,
+    /// The text already typed in `range`, up to the cursor.
+    pub prefix: String,
+    pub items: Vec<Completion>,
+}
+
+/// What the cursor is positioned on.
+enum Position {
+    /// Start of a statement inside a graph body of the given context.
+    Stmt(Context),
+    /// Outside any graph.
+    TopLevel,
+    /// An attribute name inside `[ ... ]`.
+    AttrName(Context),
+    /// The value after `key =`.
+    AttrValue(String),
+    /// The endpoint after `->` or `--`.
+    EdgeTarget,
+}
+
+/// Suggestions for the cursor at byte `offset` in `src`.
+///
+/// `links` are the storage keys offered inside `URL="..."`. Returns `None`
+/// when there is nothing sensible to suggest, e.g. inside a comment.
+pub fn complete(src: &str, offset: usize, links: &[String]) -> Option<Completions> {
+    let (tokens, _) = tokenize(src);
+
+    let mut in_string = false;
+    let mut range = offset..offset;
+    for tok in &tokens {
+        let (start, end) = (tok.span.start.offset, tok.span.end.offset);
+        if start >= offset {
+            break;
+        }
+        let text = tok.text(src);
+        match tok.kind {
+            // Inside a comment, or right after a line comment.
+            k if k.is_trivia()
+                && (offset < end || k == TokenKind::LineComment && offset == end) =>
+            {
+                return None;
+            }
+            TokenKind::QuotedString => {
+                let terminated = text.len() > 1 && text.ends_with('"');
+                if offset < end || !terminated {
+                    in_string = true;
+                    range = start + 1..if terminated { end - 1 } else { end };
+                }
+            }
+            TokenKind::Html if offset < end || !text.ends_with('>') => return None,
+            k if (k == TokenKind::Ident || k == TokenKind::Numeral || k.is_keyword())
+                && offset <= end =>
+            {
+                range = start..end;
+            }
+            _ => {}
+        }
+    }
+    let prefix = src.get(range.start..offset)?.to_string();
+
+    // Significant tokens strictly before the word being completed.
+    let before: Vec<&Token> = tokens
+        .iter()
+        .filter(|t| {
+            !t.kind.is_trivia()
+                && t.span.end.offset <= range.start.saturating_sub(usize::from(in_string))
+        })
+        .collect();
+    let position = position(src, &before)?;
+
+    let mut items = match &position {
+        Position::TopLevel => keywords(&["digraph", "graph", "strict"]),
+        Position::Stmt(ctx) => {
+            let mut items = keywords(&["node", "edge", "graph", "subgraph"]);
+            items.extend(attribute_names(*ctx));
+            items.extend(node_ids(src, offset));
+            items
+        }
+        Position::AttrName(ctx) => attribute_names(*ctx),
+        Position::AttrValue(key) => match key.as_str() {
+            "URL" | "href" => link_items(links),
+            "lhead" | "ltail" => subgraph_names(src, true),
+            _ => attrs::lookup(key)
+                .map(|info| {
+                    info.kind
+                        .suggestions()
+                        .iter()
+                        .map(|v| Completion {
+                            label: v.to_string(),
+                            insert: quote_id(v),
+                            kind: CompletionKind::Value,
+                            detail: None,
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+        },
+        Position::EdgeTarget => {
+            let mut items = node_ids(src, offset);
+            items.extend(keywords(&["subgraph"]));
+            items
+        }
+    };
+
+    if in_string {
+        // The quotes are already there; insert the raw value.
+        for item in &mut items {
+            item.insert = item.label.replace('"', "\\\"");
+        }
+    }
+
+    let needle = prefix.trim_start_matches('/').to_ascii_lowercase();
+    items.retain(|item| {
+        item.label
+            .trim_start_matches('/')
+            .to_ascii_lowercase()
+            .starts_with(&needle)
+    });
+    Some(Completions {
+        range,
+        prefix,
+        items,
+    })
+}
+
+fn position(src: &str, before: &[&Token]) -> Option<Position> {
+    // Track which kind of block and which attribute list we are in.
+    let mut blocks: Vec<Context> = Vec::new();
+    let mut list: Option<Context> = None;
+    for (i, tok) in before.iter().enumerate() {
+        match tok.kind {
+            TokenKind::LBrace => blocks.push(block_context(src, &before[..i])),
+            TokenKind::RBrace => {
+                blocks.pop();
+            }
+            TokenKind::LBracket => {
+                let enclosing = blocks.last().copied().unwrap_or(Context::Graph);
+                list = Some(list_context(&before[..i], enclosing));
+            }
+            TokenKind::RBracket => list = None,
+            _ => {}
+        }
+    }
+
+    let prev = before.last().map(|t| t.kind);
+    if prev == Some(TokenKind::Equals) {
+        let key = before.get(before.len().checked_sub(2)?)?;
+        if !key.kind.is_id() {
+            return None;
+        }
+        return Some(Position::AttrValue(id_value(key.text(src))));
+    }
+    if let Some(ctx) = list {
+        return Some(Position::AttrName(ctx));
+    }
+    let Some(&ctx) = blocks.last() else {
+        return Some(Position::TopLevel);
+    };
+    match prev {
+        Some(TokenKind::DirectedEdge | TokenKind::UndirectedEdge) => Some(Position::EdgeTarget),
+        // `node`, `edge` and `graph` must be followed by `[`; `subgraph` by a name.
+        Some(k) if k.is_keyword() => None,
+        _ => Some(Position::Stmt(ctx)),
+    }
+}
+
+/// The context of the block opened by a `{` preceded by `before`.
+fn block_context(src: &str, before: &[&Token]) -> Context {
+    let kinds: Vec<TokenKind> = before.iter().rev().take(2).map(|t| t.kind).collect();
+    match kinds.as_slice() {
+        [TokenKind::Graph | TokenKind::Digraph, ..] => Context::Graph,
+        [k, TokenKind::Graph | TokenKind::Digraph] if k.is_id() => Context::Graph,
+        [k, TokenKind::Subgraph] if k.is_id() => {
+            let name = id_value(before[before.len() - 1].text(src));
+            if name.starts_with("cluster") {
+                Context::Cluster
+            } else {
+                Context::Subgraph
+            }
+        }
+        _ => Context::Subgraph,
+    }
+}
+
+/// The context of an attribute list opened by a `[` preceded by `before`.
+fn list_context(before: &[&Token], enclosing: Context) -> Context {
+    let mut i = before.len();
+    loop {
+        let Some(tok) = i.checked_sub(1).map(|j| before[j]) else {
+            return Context::Node;
+        };
+        match tok.kind {
+            TokenKind::Node => return Context::Node,
+            TokenKind::Edge => return Context::Edge,
+            TokenKind::Graph => return enclosing,
+            // Only edge statements can have a subgraph before their attributes.
+            TokenKind::RBrace => return Context::Edge,
+            // Skip back over an earlier list of the same statement.
+            TokenKind::RBracket => {
+                while i > 0 && before[i - 1].kind != TokenKind::LBracket {
+                    i -= 1;
+                }
+                i = i.saturating_sub(1);
+            }
+            // A node ID, possibly with a port: look at what comes before it.
+            k if k.is_id() || k == TokenKind::Colon => i -= 1,
+            TokenKind::DirectedEdge | TokenKind::UndirectedEdge => return Context::Edge,
+            _ => return Context::Node,
+        }
+    }
+}
+
+fn id_value(text: &str) -> String {
+    text.strip_prefix('"')
+        .and_then(|t| t.strip_suffix('"'))
+        .unwrap_or(text)
+        .replace("\\\"", "\"")
+}
+
+/// `value` as a DOT ID, quoted if it wouldn't lex as a single identifier or
+/// number.
+fn quote_id(value: &str) -> String {
+    if is_plain_ident(value) || value.parse::<f64>().is_ok() {
+        value.to_string()
+    } else {
+        quote(value)
+    }
+}
+
+fn keywords(words: &[&str]) -> Vec<Completion> {
+    words
+        .iter()
+        .map(|w| Completion {
+            label: w.to_string(),
+            insert: w.to_string(),
+            kind: CompletionKind::Keyword,
+            detail: None,
+        })
+        .collect()
+}
+
+fn attribute_names(ctx: Context) -> Vec<Completion> {
+    let mut items: Vec<Completion> = attrs::for_context(ctx)
+        .map(|info| Completion {
+            label: info.name.to_string(),
+            insert: info.name.to_string(),
+            kind: CompletionKind::Attribute,
+            detail: Some(info.doc.to_string()),
+        })
+        .collect();
+    items.sort_by_key(|c| c.label.to_ascii_lowercase());
+    items
+}
+
+fn link_items(links: &[String]) -> Vec<Completion> {
+    links
+        .iter()
+        .filter(|key| key.ends_with(".dot"))
+        .map(|key| {
+            let path = format!("/{}", key.trim_start_matches('/'));
+            Completion {
+                insert: quote(&path),
+                label: path,
+                kind: CompletionKind::Link,
+                detail: None,
+            }
+        })
+        .collect()
+}
+
+/// Every node ID in the document, except one being typed at `offset`.
+fn node_ids(src: &str, offset: usize) -> Vec<Completion> {
+    let doc = crate::parse(src).document;
+    let mut names = BTreeSet::new();
+    let mut add = |id: &crate::ast::Id| {
+        let typing = id.span.start.offset < offset && offset <= id.span.end.offset;
+        if !typing {
+            names.insert(id.value.clone());
+        }
+    };
+    for graph in &doc.graphs {
+        graph.walk(|stmt| match stmt {
+            Stmt::Node(node) => add(&node.node.id),
+            Stmt::Edge(edge) => {
+                for target in edge.targets() {
+                    if let EdgeTarget::Node(node) = target {
+                        add(&node.id);
+                    }
+                }
+            }
+            _ => {}
+        });
+    }
+    names
+        .into_iter()
+        .map(|name| Completion {
+            insert: quote_id(&name),
+            label: name,
+            kind: CompletionKind::Node,
+            detail: None,
+        })
+        .collect()
+}
+
+fn subgraph_names(src: &str, clusters_only: bool) -> Vec<Completion> {
+    let doc = crate::parse(src).document;
+    let mut names = BTreeSet::new();
+    for graph in &doc.graphs {
+        graph.walk(|stmt| {
+            if let Stmt::Subgraph(sub) = stmt {
+                if let Some(id) = &sub.id {
+                    if sub.has_body && (!clusters_only || sub.is_cluster()) {
+                        names.insert(id.value.clone());
+                    }
+                }
+            }
+        });
+    }
+    names
+        .into_iter()
+        .map(|name| Completion {
+            insert: quote_id(&name),
+            label: name,
+            kind: CompletionKind::Subgraph,
+            detail: None,
+        })
+        .collect()
+}
@@ -0,0 +1,524 @@
+//! Structural comparison of two DOT documents.
+//!
+//! Nodes, edges and clusters are matched by the [GraphElement] keys Graphviz
+//! would draw them under, so reordering statements or reformatting the source
+//! is not a change. An element's attributes are those written on its own
+//! statements; the graph's are its top-level `graph`, `node` and `edge`
+//! defaults, the latter two as `node.<name>` and `edge.<name>`.
+//!
+//! ```
+//! use dot_lang::diff::diff;
+//!
+//! let old = dot_lang::parse("digraph { a -> b; b [color=red] }").document;
+//! let new = dot_lang::parse("digraph { a -> c; b [color=blue] }").document;
+//! assert_eq!(
+//!     diff(&old, &new).summary(),
+//!     "+ node c\n\
+//!      + edge a->c\n\
+//!      ~ node b: color changed from \"red\" to \"blue\"\n\
+//!      - edge a->b"
+//! );
+//! ```
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use crate::ast::{
+    AttrList, AttrTarget, Document, EdgeStmt, EdgeTarget, GraphKind, IdKind, NodeId, Stmt, Subgraph,
+};
+use crate::format::{is_plain_ident, quote};
+use crate::source_map::{endpoint, subgraph_nodes, GraphElement};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    /// Present in both, with different attributes.
+    Changed,
+}
+
+/// One attribute that differs. `old` or `new` is `None` where it is unset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttrChange {
+    pub key: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    /// `None` for the graph's own attributes.
+    pub element: Option<GraphElement>,
+    pub kind: ChangeKind,
+    /// For a change, the attributes that differ; for an addition or removal,
+    /// all of the element's attributes.
+    pub attrs: Vec<AttrChange>,
+}
+
+/// The differences between two documents' first graphs, which are the ones
+/// Graphviz renders.
+#[derive(Debug, Clone)]
+pub struct Diff {
+    /// Graph attributes first, then additions and changes in the new graph's
+    /// order, then removals in the old graph's.
+    pub changes: Vec<Change>,
+    old: Snapshot,
+    /// Edges per title in the new graph, to number the removed ones after.
+    new_edge_counts: HashMap<String, usize>,
+    new_kind: Option<GraphKind>,
+    /// Where the new graph's closing `}` is.
+    new_end: Option<usize>,
+}
+
+/// A source to render to show a [Diff], and how to mark its elements.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Preview {
+    /// The new source with the removed nodes, edges and clusters put back.
+    pub source: String,
+    pub marks: Vec<(GraphElement, ChangeKind)>,
+}
+
+pub fn diff(old: &Document, new: &Document) -> Diff {
+    let old = Snapshot::new(old);
+    let new_snapshot = Snapshot::new(new);
+    let mut changes = Vec::new();
+
+    let graph_attrs = attr_changes(&old.graph, &new_snapshot.graph);
+    if !graph_attrs.is_empty() {
+        changes.push(Change {
+            element: None,
+            kind: ChangeKind::Changed,
+            attrs: graph_attrs,
+        });
+    }
+    for (element, item) in &new_snapshot.items {
+        let (kind, attrs) = match old.get(element) {
+            None => (ChangeKind::Added, attr_changes(&Attrs::new(), &item.attrs)),
+            Some(before) => (
+                ChangeKind::Changed,
+                attr_changes(&before.attrs, &item.attrs),
+            ),
+        };
+        if kind == ChangeKind::Added || !attrs.is_empty() {
+            changes.push(Change {
+                element: Some(element.clone()),
+                kind,
+                attrs,
+            });
+        }
+    }
+    for (element, item) in &old.items {
+        if new_snapshot.get(element).is_none() {
+            changes.push(Change {
+                element: Some(element.clone()),
+                kind: ChangeKind::Removed,
+                attrs: attr_changes(&item.attrs, &Attrs::new()),
+            });
+        }
+    }
+
+    let mut new_edge_counts = HashMap::new();
+    for (element, _) in &new_snapshot.items {
+        if let GraphElement::Edge { title, .. } = element {
+            *new_edge_counts.entry(title.clone()).or_default() += 1;
+        }
+    }
+    let graph = new.graphs.first();
+    Diff {
+        changes,
+        old,
+        new_edge_counts,
+        new_kind: graph.map(|g| g.kind),
+        new_end: graph.map(|g| g.span.end.offset),
+    }
+}
+
+impl Diff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// One line per change: `+` added, `-` removed, `~` changed.
+    pub fn summary(&self) -> String {
+        let lines: Vec<String> = self.changes.iter().map(Change::to_string).collect();
+        lines.join("\n")
+    }
+
+    /// `new_source`, the source the new document was parsed from, with the
+    /// removed elements added back so one rendering shows both sides.
+    ///
+    /// Removed nodes and edges keep their old attributes, and removed clusters
+    /// are drawn around the removed nodes they held. Removed edges are
+    /// numbered after the new graph's edges with the same title.
+    pub fn preview(&self, new_source: &str) -> Preview {
+        let mut marks = Vec::new();
+        let mut removed = String::new();
+        let mut clusters: BTreeMap<&str, String> = BTreeMap::new();
+        let mut edge_counts = self.new_edge_counts.clone();
+
+        for change in &self.changes {
+            let Some(element) = &change.element else {
+                continue;
+            };
+            if change.kind != ChangeKind::Removed {
+                marks.push((element.clone(), change.kind));
+                continue;
+            }
+            let Some(item) = self.old.get(element) else {
+                continue;
+            };
+            let attrs = attrs_source(&item.attrs);
+            match element {
+                GraphElement::Node(name) => {
+                    let stmt = format!("{}{attrs};\n", id_source(name, false));
+                    match item
+                        .cluster
+                        .as_deref()
+                        .filter(|c| self.is_removed_cluster(c))
+                    {
+                        Some(cluster) => clusters
+                            .entry(cluster)
+                            .or_default()
+                            .push_str(&format!("    {stmt}")),
+                        None => removed.push_str(&format!("  {stmt}")),
+                    }
+                    marks.push((element.clone(), ChangeKind::Removed));
+                }
+                GraphElement::Edge { title, .. } => {
+                    let Some((tail, head)) = &item.ends else {
+                        continue;
+                    };
+                    let op = self.new_kind.unwrap_or(self.old.kind).edge_op().as_str();
+                    removed.push_str(&format!("  {tail} {op} {head}{attrs};\n"));
+                    let count = edge_counts.entry(title.clone()).or_default();
+                    let index = *count;
+                    *count += 1;
+                    let title = title.clone();
+                    marks.push((GraphElement::Edge { title, index }, ChangeKind::Removed));
+                }
+                GraphElement::Cluster(name) => {
+                    clusters.entry(name).or_default();
+                    marks.push((element.clone(), ChangeKind::Removed));
+                }
+            }
+        }
+
+        for (name, body) in clusters {
+            let item = self.old.get(&GraphElement::Cluster(name.to_string()));
+            let mut attrs = String::new();
+            for (key, value) in item.into_iter().flat_map(|item| &item.attrs) {
+                let attr = match key.split_once('.') {
+                    Some((target @ ("node" | "edge"), key)) => {
+                        format!("{target} [{}]", attr_source(key, value))
+                    }
+                    _ => attr_source(key, value),
+                };
+                attrs.push_str(&format!("    {attr};\n"));
+            }
+            removed.push_str(&format!(
+                "  subgraph {} {{\n{attrs}{body}  }}\n",
+                id_source(name, false)
+            ));
+        }
+
+        // Before the closing `}`, or at the end when it is missing
+        let source = match self.new_end.and_then(|end| new_source.get(..end)) {
+            Some(before) => match before.strip_suffix('}') {
+                Some(body) => {
+                    let after = &new_source[body.len()..];
+                    format!("{}\n{removed}{after}", body.trim_end())
+                }
+                None => format!(
+                    "{}\n{removed}}}{}",
+                    before.trim_end(),
+                    &new_source[before.len()..]
+                ),
+            },
+            None => format!("{} {{\n{removed}}}\n", self.old.kind.keyword()),
+        };
+        Preview { source, marks }
+    }
+
+    fn is_removed_cluster(&self, name: &str) -> bool {
+        self.changes.iter().any(|change| {
+            change.kind == ChangeKind::Removed
+                && change.element.as_ref() == Some(&GraphElement::Cluster(name.to_string()))
+        })
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = match self.kind {
+            ChangeKind::Added => '+',
+            ChangeKind::Removed => '-',
+            ChangeKind::Changed => '~',
+        };
+        write!(f, "{sign} ")?;
+        match &self.element {
+            None => write!(f, "graph")?,
+            Some(GraphElement::Node(name)) => write!(f, "node {name}")?,
+            Some(GraphElement::Edge { title, index: 0 }) => write!(f, "edge {title}")?,
+            Some(GraphElement::Edge { title, index }) => write!(f, "edge {title} #{}", index + 1)?,
+            Some(GraphElement::Cluster(name)) => write!(f, "cluster {name}")?,
+        }
+        if self.kind != ChangeKind::Changed {
+            return Ok(());
+        }
+        for (i, attr) in self.attrs.iter().enumerate() {
+            f.write_str(if i == 0 { ": " } else { "; " })?;
+            match (&attr.old, &attr.new) {
+                (Some(old), Some(new)) => write!(
+                    f,
+                    "{} changed from {} to {}",
+                    attr.key,
+                    quote(old),
+                    quote(new)
+                )?,
+                (None, Some(new)) => write!(f, "{} set to {}", attr.key, quote(new))?,
+                (Some(old), None) => write!(f, "{} unset (was {})", attr.key, quote(old))?,
+                (None, None) => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+// ------------------------- Snapshots -------------------------
+
+/// Attribute values by name, with whether each was an HTML string.
+type Attrs = BTreeMap<String, (String, bool)>;
+
+#[derive(Debug, Clone, Default)]
+struct Item {
+    attrs: Attrs,
+    /// For nodes, the innermost cluster they were first mentioned in.
+    cluster: Option<String>,
+    /// For edges, the tail and head as DOT source.
+    ends: Option<(String, String)>,
+}
+
+/// The elements of a document's first graph, in order of first mention.
+#[derive(Debug, Clone)]
+struct Snapshot {
+    kind: GraphKind,
+    graph: Attrs,
+    items: Vec<(GraphElement, Item)>,
+    index: HashMap<GraphElement, usize>,
+}
+
+impl Snapshot {
+    fn new(doc: &Document) -> Self {
+        let mut snapshot = Snapshot {
+            kind: GraphKind::Digraph,
+            graph: Attrs::new(),
+            items: Vec::new(),
+            index: HashMap::new(),
+        };
+        if let Some(graph) = doc.graphs.first() {
+            snapshot.kind = graph.kind;
+            let mut builder = Builder {
+                edge_op: graph.kind.edge_op().as_str(),
+                edge_counts: HashMap::new(),
+                cluster: None,
+                snapshot: &mut snapshot,
+            };
+            builder.stmts(&graph.stmts, true);
+        }
+        snapshot
+    }
+
+    fn get(&self, element: &GraphElement) -> Option<&Item> {
+        self.index.get(element).map(|&i| &self.items[i].1)
+    }
+
+    fn entry(&mut self, element: GraphElement, cluster: Option<&str>) -> &mut Item {
+        let i = match self.index.get(&element) {
+            Some(&i) => i,
+            None => {
+                let item = Item {
+                    cluster: cluster.map(str::to_string),
+                    ..Item::default()
+                };
+                self.index.insert(element.clone(), self.items.len());
+                self.items.push((element, item));
+                self.items.len() - 1
+            }
+        };
+        &mut self.items[i].1
+    }
+}
+
+struct Builder<'a> {
+    edge_op: &'static str,
+    edge_counts: HashMap<String, usize>,
+    /// The innermost cluster being walked.
+    cluster: Option<String>,
+    snapshot: &'a mut Snapshot,
+}
+
+impl Builder<'_> {
+    /// `top` is whether `stmts` is the graph's own body, whose attribute
+    /// statements are the graph's attributes.
+    fn stmts(&mut self, stmts: &[Stmt], top: bool) {
+        for stmt in stmts {
+            match stmt {
+                Stmt::Node(node) => {
+                    let name = node.node.id.value.clone();
+                    let cluster = self.cluster.clone();
+                    let item = self
+                        .snapshot
+                        .entry(GraphElement::Node(name), cluster.as_deref());
+                    add_attrs(&mut item.attrs, &node.attrs, "");
+                }
+                Stmt::Edge(edge) => self.edge(edge),
+                Stmt::Subgraph(sub) => self.subgraph(sub),
+                Stmt::Attr(attr) => {
+                    let prefix = match attr.target {
+                        AttrTarget::Graph => "",
+                        AttrTarget::Node => "node.",
+                        AttrTarget::Edge => "edge.",
+                    };
+                    if let Some(attrs) = self.own_attrs(top) {
+                        add_attrs(attrs, &attr.attrs, prefix);
+                    }
+                }
+                Stmt::Assign(attr) => {
+                    if let Some(attrs) = self.own_attrs(top) {
+                        let value = (attr.value.value.clone(), attr.value.is_html());
+                        attrs.insert(attr.key.value.clone(), value);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Where attribute statements in the body being walked are recorded: the
+    /// graph's or the cluster's. Those in other subgraphs are not.
+    fn own_attrs(&mut self, top: bool) -> Option<&mut Attrs> {
+        if top {
+            return Some(&mut self.snapshot.graph);
+        }
+        let cluster = GraphElement::Cluster(self.cluster.clone()?);
+        Some(&mut self.snapshot.entry(cluster, None).attrs)
+    }
+
+    fn subgraph(&mut self, sub: &Subgraph) {
+        if !sub.has_body {
+            return;
+        }
+        let Some(id) = sub.id.as_ref().filter(|_| sub.is_cluster()) else {
+            // Attribute statements here belong to neither the graph nor a
+            // cluster, so they are left out
+            let outer = self.cluster.take();
+            self.stmts(&sub.stmts, false);
+            self.cluster = outer;
+            return;
+        };
+        self.snapshot
+            .entry(GraphElement::Cluster(id.value.clone()), None);
+        let outer = self.cluster.replace(id.value.clone());
+        self.stmts(&sub.stmts, false);
+        self.cluster = outer;
+    }
+
+    fn edge(&mut self, edge: &EdgeStmt) {
+        let mut ends: Vec<Vec<(String, String)>> = Vec::new();
+        for target in edge.targets() {
+            match target {
+                EdgeTarget::Node(node) => {
+                    let cluster = self.cluster.clone();
+                    self.snapshot.entry(
+                        GraphElement::Node(node.id.value.clone()),
+                        cluster.as_deref(),
+                    );
+                    ends.push(vec![(endpoint(node), end_source(node))]);
+                }
+                EdgeTarget::Subgraph(sub) => {
+                    self.subgraph(sub);
+                    let nodes = subgraph_nodes(sub).into_iter();
+                    ends.push(nodes.map(|n| (n.clone(), id_source(&n, false))).collect());
+                }
+            }
+        }
+        for pair in ends.windows(2) {
+            for (tail, tail_source) in &pair[0] {
+                for (head, head_source) in &pair[1] {
+                    let title = format!("{tail}{}{head}", self.edge_op);
+                    let count = self.edge_counts.entry(title.clone()).or_default();
+                    let index = *count;
+                    *count += 1;
+                    let item = self
+                        .snapshot
+                        .entry(GraphElement::Edge { title, index }, None);
+                    item.ends = Some((tail_source.clone(), head_source.clone()));
+                    add_attrs(&mut item.attrs, &edge.attrs, "");
+                }
+            }
+        }
+    }
+}
+
+fn add_attrs(attrs: &mut Attrs, lists: &[AttrList], prefix: &str) {
+    for attr in lists.iter().flat_map(|list| &list.attrs) {
+        let value = (attr.value.value.clone(), attr.value.is_html());
+        attrs.insert(format!("{prefix}{}", attr.key.value), value);
+    }
+}
+
+fn attr_changes(old: &Attrs, new: &Attrs) -> Vec<AttrChange> {
+    let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    keys.into_iter()
+        .filter_map(|key| {
+            let old = old.get(key).map(|(value, _)| value.clone());
+            let new = new.get(key).map(|(value, _)| value.clone());
+            (old != new).then(|| AttrChange {
+                key: key.clone(),
+                old,
+                new,
+            })
+        })
+        .collect()
+}
+
+// ------------------------- Source -------------------------
+
+fn id_source(value: &str, html: bool) -> String {
+    if html {
+        format!("<{value}>")
+    } else if is_plain_ident(value) {
+        value.to_string()
+    } else {
+        quote(value)
+    }
+}
+
+/// A node ID with its port, as written in an edge statement.
+fn end_source(node: &NodeId) -> String {
+    let mut out = id_source(&node.id.value, node.id.kind == IdKind::Html);
+    if let Some(port) = &node.port {
+        for id in std::iter::once(&port.name).chain(&port.compass) {
+            out.push(':');
+            out.push_str(&id_source(&id.value, id.is_html()));
+        }
+    }
+    out
+}
+
+fn attr_source(key: &str, (value, html): &(String, bool)) -> String {
+    format!("{}={}", id_source(key, false), id_source(value, *html))
+}
+
+/// ` [a=1, b=2]`, or nothing without attributes. The graph defaults that
+/// [Snapshot] prefixes never end up on a node or edge.
+fn attrs_source(attrs: &Attrs) -> String {
+    if attrs.is_empty() {
+        return String::new();
+    }
+    let attrs: Vec<String> = attrs
+        .iter()
+        .map(|(key, value)| attr_source(key, value))
+        .collect();
+    format!(" [{}]", attrs.join(", "))
+}
@@ -0,0 +1,29 @@
+//! Parse errors
+use crate::span::Span;
+use thiserror::Error;
+
+/// A recoverable syntax error.
+///
+/// The parser never stops at the first error; it records one of these and
+/// resynchronises at the next statement so the rest of the document is still
+/// available to tooling.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("{message} (line {}, column {})", span.start.line, span.start.column)]
+pub struct ParseError {
+    pub message: String,
+    pub span: Span,
+}
+
+impl ParseError {
+    pub fn new(message: impl Into<String>, span: Span) -> Self {
+        Self {
+            message: message.into(),
+            span,
+        }
+    }
+
+    /// The 1-based line the error starts on.
+    pub fn line(&self) -> u32 {
+        self.span.start.line
+    }
+}
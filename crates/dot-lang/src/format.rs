@@ -0,0 +1,516 @@
+//! Canonical pretty-printer for DOT source.
+//!
+//! The formatter works from the parsed [Document], so it only runs on source
+//! that parses cleanly. Comments are re-attached to the statement they precede
+//! (or follow on the same line), blank lines between statements are kept
+//! (collapsed to one), and attribute lists that were written across several
+//! lines stay multi-line. Formatting is idempotent: running it on its own
+//! output is a no-op.
+use crate::ast::*;
+use crate::error::ParseError;
+use crate::lexer::{tokenize, TokenKind};
+use crate::parse;
+
+/// How IDs are quoted in the output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QuoteStyle {
+    /// Only quote IDs that need it, e.g. `style=filled`, `label="Two words"`.
+    #[default]
+    Minimal,
+    /// Quote every attribute value (HTML labels are left alone). Node names
+    /// and attribute keys are still quoted minimally.
+    AlwaysValues,
+    /// Keep each ID exactly as it was written.
+    Preserve,
+}
+
+/// How statements are ordered within each graph or subgraph body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StmtOrder {
+    /// Keep statements in source order.
+    #[default]
+    Preserve,
+    /// Move graph attributes (`key=value` and `graph [...]`) to the top of
+    /// their block. `node [...]` and `edge [...]` defaults only apply to
+    /// statements after them, so they are never moved.
+    GraphAttrsFirst,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatOptions {
+    /// The string used for one level of indentation.
+    pub indent: String,
+    pub quote_style: QuoteStyle,
+    pub stmt_order: StmtOrder,
+    /// Terminate statements with `;`.
+    pub semicolons: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            indent: "  ".to_string(),
+            quote_style: QuoteStyle::default(),
+            stmt_order: StmtOrder::default(),
+            semicolons: true,
+        }
+    }
+}
+
+/// Format DOT source. Returns the parse errors instead if the source does not
+/// parse cleanly, since formatting a partial tree would drop the user's text.
+pub fn format(src: &str, options: &FormatOptions) -> Result<String, Vec<ParseError>> {
+    let parse = parse(src);
+    if !parse.is_ok() {
+        return Err(parse.errors);
+    }
+
+    let doc = &parse.document;
+    let mut f = Formatter {
+        src,
+        opts: options,
+        comments: &doc.comments,
+        next_comment: 0,
+        hoisted: Vec::new(),
+    };
+
+    let mut out = String::new();
+    let mut prev_end_line = None;
+    for graph in &doc.graphs {
+        for comment in f.take_comments_before(graph.span.start.offset) {
+            f.blank_line_between(&mut out, prev_end_line, comment.span.start.line);
+            out.push_str(comment.text.trim_end());
+            out.push('\n');
+            prev_end_line = Some(comment.span.end.line);
+        }
+        f.blank_line_between(&mut out, prev_end_line, graph.span.start.line);
+        out.push_str(&f.graph(graph));
+        out.push('\n');
+        prev_end_line = Some(graph.span.end.line);
+    }
+    for comment in f.take_comments_before(usize::MAX) {
+        f.blank_line_between(&mut out, prev_end_line, comment.span.start.line);
+        out.push_str(comment.text.trim_end());
+        out.push('\n');
+        prev_end_line = Some(comment.span.end.line);
+    }
+    Ok(out)
+}
+
+/// A formatted statement together with the comments that travel with it.
+struct Item {
+    /// Position in the source block, which `stmt_order` may change.
+    index: usize,
+    first_line: u32,
+    last_line: u32,
+    leading: Vec<String>,
+    body: String,
+    trailing: Option<String>,
+    is_graph_attr: bool,
+    /// A node statement with no attributes, e.g. `u1`. Runs of these that
+    /// shared a line in the source keep sharing it.
+    is_bare_node: bool,
+}
+
+struct Formatter<'a> {
+    src: &'a str,
+    opts: &'a FormatOptions,
+    comments: &'a [Comment],
+    next_comment: usize,
+    /// Comments found inside a statement that is printed on one line; they
+    /// are emitted above the statement instead.
+    hoisted: Vec<&'a Comment>,
+}
+
+impl<'a> Formatter<'a> {
+    // ------------------------- Comments -------------------------
+
+    fn take_comments_before(&mut self, offset: usize) -> Vec<&'a Comment> {
+        let start = self.next_comment;
+        while self
+            .comments
+            .get(self.next_comment)
+            .is_some_and(|c| c.span.start.offset < offset)
+        {
+            self.next_comment += 1;
+        }
+        self.comments[start..self.next_comment].iter().collect()
+    }
+
+    /// A comment that starts on `line` after `offset` but before the next
+    /// item at `before`, e.g. `a -> b; // why`.
+    fn take_trailing_comment(
+        &mut self,
+        line: u32,
+        offset: usize,
+        before: usize,
+    ) -> Option<&'a Comment> {
+        let c = self.comments.get(self.next_comment)?;
+        let start = c.span.start;
+        if start.line == line && start.offset >= offset && start.offset < before {
+            self.next_comment += 1;
+            Some(c)
+        } else {
+            None
+        }
+    }
+
+    /// Move any comments before `offset` onto the hoisted list.
+    fn hoist_comments_before(&mut self, offset: usize) {
+        let taken = self.take_comments_before(offset);
+        self.hoisted.extend(taken);
+    }
+
+    fn blank_line_between(&self, out: &mut String, prev_end_line: Option<u32>, next_line: u32) {
+        if prev_end_line.is_some_and(|prev| next_line > prev + 1) {
+            out.push('\n');
+        }
+    }
+
+    fn indent(&self, depth: usize) -> String {
+        self.opts.indent.repeat(depth)
+    }
+
+    // ------------------------- Blocks -------------------------
+
+    fn graph(&mut self, graph: &Graph) -> String {
+        let mut header = String::new();
+        if graph.strict {
+            header.push_str("strict ");
+        }
+        header.push_str(graph.kind.keyword());
+        if let Some(id) = &graph.id {
+            header.push(' ');
+            header.push_str(&self.id(id, false));
+        }
+        header.push(' ');
+        header.push_str(&self.block(&graph.stmts, graph.span.end.offset, 0));
+        header
+    }
+
+    /// Format `{ stmts }` whose closing brace is just before `end_offset`.
+    /// `depth` is the depth of the line holding the opening brace.
+    fn block(&mut self, stmts: &[Stmt], end_offset: usize, depth: usize) -> String {
+        let inner = depth + 1;
+        // Comments hoisted by an enclosing statement belong above that statement,
+        // not to the first statement of this block.
+        let outer_hoisted = std::mem::take(&mut self.hoisted);
+        let mut items = Vec::with_capacity(stmts.len());
+        for (i, stmt) in stmts.iter().enumerate() {
+            let span = stmt.span();
+            let next_start = stmts
+                .get(i + 1)
+                .map_or(end_offset, |s| s.span().start.offset);
+            let leading: Vec<&Comment> = self.take_comments_before(span.start.offset);
+            let body = self.stmt(stmt, inner);
+            let hoisted = std::mem::take(&mut self.hoisted);
+            let trailing = self.take_trailing_comment(span.end.line, span.end.offset, next_start);
+
+            let first_line = leading
+                .first()
+                .map_or(span.start.line, |c| c.span.start.line);
+            let last_line = trailing.map_or(span.end.line, |c| c.span.end.line);
+            items.push(Item {
+                index: i,
+                first_line,
+                last_line,
+                leading: leading
+                    .iter()
+                    .chain(hoisted.iter())
+                    .map(|c| c.text.trim_end().to_string())
+                    .collect(),
+                body,
+                trailing: trailing.map(|c| c.text.trim_end().to_string()),
+                is_graph_attr: matches!(
+                    stmt,
+                    Stmt::Assign(_)
+                        | Stmt::Attr(AttrStmt {
+                            target: AttrTarget::Graph,
+                            ..
+                        })
+                ),
+                is_bare_node: matches!(stmt, Stmt::Node(node) if node.attrs.is_empty()),
+            });
+        }
+        let dangling = self.take_comments_before(end_offset);
+        self.hoisted = outer_hoisted;
+
+        if items.is_empty() && dangling.is_empty() {
+            return "{}".to_string();
+        }
+
+        if self.opts.stmt_order == StmtOrder::GraphAttrsFirst {
+            // Stable, so relative order within each group is kept.
+            items.sort_by_key(|item| !item.is_graph_attr);
+        }
+
+        let pad = self.indent(inner);
+        let mut out = String::from("{\n");
+        let mut prev: Option<&Item> = None;
+        for item in &items {
+            let shares_line = prev.is_some_and(|p| {
+                p.is_bare_node
+                    && p.trailing.is_none()
+                    && item.is_bare_node
+                    && item.leading.is_empty()
+                    && item.first_line == p.last_line
+            });
+            if shares_line {
+                out.pop();
+                out.push(' ');
+            } else {
+                // Blank lines only separate statements that were neighbours
+                let prev_end_line = prev
+                    .filter(|p| p.index + 1 == item.index)
+                    .map(|p| p.last_line);
+                self.blank_line_between(&mut out, prev_end_line, item.first_line);
+                for comment in &item.leading {
+                    out.push_str(&pad);
+                    out.push_str(comment);
+                    out.push('\n');
+                }
+                out.push_str(&pad);
+            }
+            out.push_str(&item.body);
+            if let Some(comment) = &item.trailing {
+                out.push(' ');
+                out.push_str(comment);
+            }
+            out.push('\n');
+            prev = Some(item);
+        }
+        let mut prev_last_line = prev.map(|p| p.last_line);
+        for comment in dangling {
+            self.blank_line_between(&mut out, prev_last_line, comment.span.start.line);
+            out.push_str(&pad);
+            out.push_str(comment.text.trim_end());
+            out.push('\n');
+            prev_last_line = Some(comment.span.end.line);
+        }
+        out.push_str(&self.indent(depth));
+        out.push('}');
+        out
+    }
+
+    // ------------------------- Statements -------------------------
+
+    fn terminator(&self) -> &'static str {
+        if self.opts.semicolons {
+            ";"
+        } else {
+            ""
+        }
+    }
+
+    /// Format one statement at `depth`. The returned string does not include
+    /// the indentation of its first line.
+    fn stmt(&mut self, stmt: &Stmt, depth: usize) -> String {
+        let mut out = match stmt {
+            Stmt::Node(node) => {
+                self.hoist_comments_before(attrs_start(&node.attrs, node.span));
+                let mut out = self.node_id(&node.node);
+                out.push_str(&self.attr_lists(&node.attrs, depth, true));
+                out
+            }
+            Stmt::Edge(edge) => {
+                let mut out = self.edge_target(&edge.head, depth);
+                for rhs in &edge.rest {
+                    self.hoist_comments_before(rhs.target.span().start.offset);
+                    out.push(' ');
+                    out.push_str(rhs.op.as_str());
+                    out.push(' ');
+                    out.push_str(&self.edge_target(&rhs.target, depth));
+                }
+                self.hoist_comments_before(attrs_start(&edge.attrs, edge.span));
+                out.push_str(&self.attr_lists(&edge.attrs, depth, true));
+                out
+            }
+            Stmt::Attr(attr) => {
+                let mut out = attr.target.keyword().to_string();
+                out.push_str(&self.attr_lists(&attr.attrs, depth, false));
+                out
+            }
+            Stmt::Assign(assign) => {
+                self.hoist_comments_before(assign.span.end.offset);
+                format!(
+                    "{}={}",
+                    self.id(&assign.key, false),
+                    self.id(&assign.value, true)
+                )
+            }
+            Stmt::Subgraph(sub) if !sub.has_body => self.subgraph(sub, depth),
+            Stmt::Subgraph(sub) => return self.subgraph(sub, depth),
+        };
+        // Anything left inside a statement printed on one line goes above it.
+        self.hoist_comments_before(stmt.span().end.offset);
+        out.push_str(self.terminator());
+        out
+    }
+
+    fn subgraph(&mut self, sub: &Subgraph, depth: usize) -> String {
+        let mut out = String::new();
+        if sub.keyword {
+            out.push_str("subgraph ");
+            if let Some(id) = &sub.id {
+                out.push_str(&self.id(id, false));
+                if !sub.has_body {
+                    return out;
+                }
+                out.push(' ');
+            }
+        }
+        if sub.span.start.line == sub.span.end.line {
+            // `{ rank=same; a; b; }` style one-liners stay on one line.
+            self.hoist_comments_before(sub.span.end.offset);
+            let parts: Vec<String> = sub
+                .stmts
+                .iter()
+                .map(|s| {
+                    let mut s = self.stmt(s, depth);
+                    if s.ends_with(';') {
+                        s.pop();
+                    }
+                    s
+                })
+                .collect();
+            if parts.is_empty() {
+                out.push_str("{}");
+            } else {
+                out.push_str("{ ");
+                out.push_str(&parts.join("; "));
+                out.push_str(self.terminator());
+                out.push_str(" }");
+            }
+        } else {
+            out.push_str(&self.block(&sub.stmts, sub.span.end.offset, depth));
+        }
+        out
+    }
+
+    fn edge_target(&mut self, target: &EdgeTarget, depth: usize) -> String {
+        match target {
+            EdgeTarget::Node(node) => self.node_id(node),
+            EdgeTarget::Subgraph(sub) => self.subgraph(sub, depth),
+        }
+    }
+
+    fn node_id(&self, node: &NodeId) -> String {
+        let mut out = self.id(&node.id, false);
+        if let Some(port) = &node.port {
+            out.push(':');
+            out.push_str(&self.id(&port.name, false));
+            if let Some(compass) = &port.compass {
+                out.push(':');
+                out.push_str(&self.id(compass, false));
+            }
+        }
+        out
+    }
+
+    // ------------------------- Attributes -------------------------
+
+    /// Format the attribute lists of a statement, merged into one `[...]`.
+    /// If any list was written over several lines, the result is one
+    /// attribute per line. The returned string starts with a space.
+    fn attr_lists(&mut self, lists: &[AttrList], depth: usize, omit_empty: bool) -> String {
+        let attrs: Vec<&Attribute> = lists.iter().flat_map(|l| &l.attrs).collect();
+        if attrs.is_empty() {
+            return if omit_empty {
+                String::new()
+            } else {
+                " []".to_string()
+            };
+        }
+
+        let multiline = lists.iter().any(|l| l.span.start.line != l.span.end.line);
+        if !multiline {
+            if let Some(last) = lists.last() {
+                self.hoist_comments_before(last.span.end.offset);
+            }
+            let parts: Vec<String> = attrs.iter().map(|a| self.attribute(a)).collect();
+            return format!(" [{}]", parts.join(", "));
+        }
+
+        let pad = self.indent(depth + 1);
+        let mut out = String::from(" [\n");
+        for (i, attr) in attrs.iter().enumerate() {
+            let next_start = attrs.get(i + 1).map_or(usize::MAX, |a| a.span.start.offset);
+            for comment in self.take_comments_before(attr.span.start.offset) {
+                out.push_str(&pad);
+                out.push_str(comment.text.trim_end());
+                out.push('\n');
+            }
+            out.push_str(&pad);
+            out.push_str(&self.attribute(attr));
+            out.push_str(self.terminator());
+            if let Some(comment) =
+                self.take_trailing_comment(attr.span.end.line, attr.span.end.offset, next_start)
+            {
+                out.push(' ');
+                out.push_str(comment.text.trim_end());
+            }
+            out.push('\n');
+        }
+        if let Some(last) = lists.last() {
+            for comment in self.take_comments_before(last.span.end.offset) {
+                out.push_str(&pad);
+                out.push_str(comment.text.trim_end());
+                out.push('\n');
+            }
+        }
+        out.push_str(&self.indent(depth));
+        out.push(']');
+        out
+    }
+
+    fn attribute(&self, attr: &Attribute) -> String {
+        format!(
+            "{}={}",
+            self.id(&attr.key, false),
+            self.id(&attr.value, true)
+        )
+    }
+
+    // ------------------------- IDs -------------------------
+
+    fn id(&self, id: &Id, is_value: bool) -> String {
+        match (self.opts.quote_style, id.kind) {
+            (QuoteStyle::Preserve, _) => self.src[id.span.range()].to_string(),
+            (_, IdKind::Html) => format!("<{}>", id.value),
+            (QuoteStyle::AlwaysValues, _) if is_value => quote(&id.value),
+            (_, IdKind::Numeral) => id.value.clone(),
+            _ if is_plain_ident(&id.value) => id.value.clone(),
+            _ => quote(&id.value),
+        }
+    }
+}
+
+/// Where a statement's attribute lists start, or its end if it has none.
+fn attrs_start(attrs: &[AttrList], stmt_span: crate::span::Span) -> usize {
+    attrs
+        .first()
+        .map_or(stmt_span.end.offset, |l| l.span.start.offset)
+}
+
+/// Whether `value` can be written without quotes and still lex as a single,
+/// non-keyword identifier.
+pub(crate) fn is_plain_ident(value: &str) -> bool {
+    let (tokens, errors) = tokenize(value);
+    errors.is_empty()
+        && tokens.len() == 1
+        && tokens[0].kind == TokenKind::Ident
+        && tokens[0].span.len() == value.len()
+}
+
+pub(crate) fn quote(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        if c == '"' {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out.push('"');
+    out
+}
@@ -0,0 +1,121 @@
+//! Token classification for syntax highlighting.
+//!
+//! Highlighting works from the lexer alone rather than the parse tree, so it
+//! stays stable while the user is halfway through typing a statement.
+use crate::lexer::{tokenize, Token, TokenKind};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HighlightKind {
+    /// Whitespace and anything between tokens
+    Plain,
+    /// `graph`, `digraph`, `node`, `edge`, `subgraph`, `strict`
+    Keyword,
+    /// Unquoted IDs: node names, subgraph names, ports
+    Id,
+    /// The key of an `ID = ID` pair
+    AttrName,
+    /// An unquoted attribute value, e.g. `box` in `shape=box`
+    AttrValue,
+    Number,
+    /// Double-quoted string, anywhere
+    String,
+    /// HTML label `<...>`
+    Html,
+    Comment,
+    /// `->` and `--`
+    EdgeOp,
+    /// Brackets, braces, `=`, `;`, `,`, `:` and `+`
+    Punctuation,
+    /// Characters the lexer couldn't make sense of
+    Error,
+}
+
+/// A run of source text with a single highlight kind. Never spans a newline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Segment<'a> {
+    pub kind: HighlightKind,
+    pub text: &'a str,
+    /// Byte offset of `text` in the source.
+    pub offset: usize,
+}
+
+/// Classify every token in `src`. The result is in source order and does not
+/// include the whitespace between tokens.
+pub fn highlight(src: &str) -> Vec<(HighlightKind, Token)> {
+    let (tokens, _) = tokenize(src);
+    // Neighbours are looked up among non-trivia tokens so that a comment
+    // between a key and its `=` doesn't change the key's color.
+    let significant: Vec<usize> = (0..tokens.len())
+        .filter(|&i| !tokens[i].kind.is_trivia())
+        .collect();
+    let mut kinds = vec![HighlightKind::Comment; tokens.len()];
+
+    for (n, &i) in significant.iter().enumerate() {
+        let prev = n.checked_sub(1).map(|p| tokens[significant[p]].kind);
+        let next = significant.get(n + 1).map(|&j| tokens[j].kind);
+        kinds[i] = classify(tokens[i].kind, prev, next);
+    }
+
+    kinds.into_iter().zip(tokens).collect()
+}
+
+fn classify(kind: TokenKind, prev: Option<TokenKind>, next: Option<TokenKind>) -> HighlightKind {
+    match kind {
+        k if k.is_keyword() => HighlightKind::Keyword,
+        TokenKind::QuotedString => HighlightKind::String,
+        TokenKind::Html => HighlightKind::Html,
+        TokenKind::Ident | TokenKind::Numeral if next == Some(TokenKind::Equals) => {
+            HighlightKind::AttrName
+        }
+        TokenKind::Numeral => HighlightKind::Number,
+        TokenKind::Ident if prev == Some(TokenKind::Equals) => HighlightKind::AttrValue,
+        TokenKind::Ident => HighlightKind::Id,
+        TokenKind::DirectedEdge | TokenKind::UndirectedEdge => HighlightKind::EdgeOp,
+        TokenKind::Unknown => HighlightKind::Error,
+        k if k.is_trivia() => HighlightKind::Comment,
+        _ => HighlightKind::Punctuation,
+    }
+}
+
+/// Split `src` into lines of highlighted segments.
+///
+/// Concatenating the segments of each line and joining the lines with `\n`
+/// gives back `src` exactly. Tokens that span lines (block comments, strings,
+/// HTML labels) are split at the line breaks. A trailing newline produces a
+/// final empty line, matching what a textarea shows.
+pub fn highlight_lines(src: &str) -> Vec<Vec<Segment<'_>>> {
+    let mut lines = vec![Vec::new()];
+    let mut offset = 0;
+    for (kind, token) in highlight(src) {
+        let range = token.span.range();
+        emit(&mut lines, HighlightKind::Plain, src, offset..range.start);
+        emit(&mut lines, kind, src, range.clone());
+        offset = range.end;
+    }
+    emit(&mut lines, HighlightKind::Plain, src, offset..src.len());
+    lines
+}
+
+fn emit<'a>(
+    lines: &mut Vec<Vec<Segment<'a>>>,
+    kind: HighlightKind,
+    src: &'a str,
+    range: std::ops::Range<usize>,
+) {
+    let mut offset = range.start;
+    for (i, part) in src[range].split('\n').enumerate() {
+        if i > 0 {
+            lines.push(Vec::new());
+            offset += 1;
+        }
+        if !part.is_empty() {
+            let line = lines.last_mut().expect("there is always a current line");
+            line.push(Segment {
+                kind,
+                text: part,
+                offset,
+            });
+        }
+        offset += part.len();
+    }
+}
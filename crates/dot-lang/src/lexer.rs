@@ -0,0 +1,341 @@
+//! DOT tokenizer.
+//!
+//! The lexer is lossless with respect to everything except whitespace: comments
+//! are emitted as trivia tokens so that formatters and syntax highlighters can
+//! work from the same token stream as the parser.
+use crate::error::ParseError;
+use crate::span::{Position, Span};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    // Keywords (case-insensitive in DOT)
+    Strict,
+    Graph,
+    Digraph,
+    Node,
+    Edge,
+    Subgraph,
+
+    // Identifiers
+    /// Alphanumeric identifier, e.g. `node_1`
+    Ident,
+    /// Numeral, e.g. `-1.5`
+    Numeral,
+    /// Double-quoted string, including the quotes
+    QuotedString,
+    /// HTML string, including the outer `<` `>`
+    Html,
+
+    // Punctuation
+    LBrace,
+    RBrace,
+    LBracket,
+    RBracket,
+    Equals,
+    Semicolon,
+    Comma,
+    Colon,
+    /// `+`, used to concatenate quoted strings
+    Plus,
+    /// `->`
+    DirectedEdge,
+    /// `--`
+    UndirectedEdge,
+
+    // Trivia
+    /// `// ...`
+    LineComment,
+    /// `/* ... */`
+    BlockComment,
+    /// A line starting with `#`, treated as a comment by Graphviz
+    Preprocessor,
+
+    /// A character that cannot start any token
+    Unknown,
+}
+
+impl TokenKind {
+    /// Comments are trivia: the parser skips them but keeps them on the side.
+    pub fn is_trivia(self) -> bool {
+        matches!(
+            self,
+            TokenKind::LineComment | TokenKind::BlockComment | TokenKind::Preprocessor
+        )
+    }
+
+    pub fn is_keyword(self) -> bool {
+        matches!(
+            self,
+            TokenKind::Strict
+                | TokenKind::Graph
+                | TokenKind::Digraph
+                | TokenKind::Node
+                | TokenKind::Edge
+                | TokenKind::Subgraph
+        )
+    }
+
+    /// Whether this token can be used as a DOT `ID`.
+    pub fn is_id(self) -> bool {
+        matches!(
+            self,
+            TokenKind::Ident | TokenKind::Numeral | TokenKind::QuotedString | TokenKind::Html
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Span,
+}
+
+impl Token {
+    /// The source text of this token.
+    pub fn text<'a>(&self, src: &'a str) -> &'a str {
+        &src[self.span.range()]
+    }
+}
+
+/// Split `src` into tokens, including comment trivia.
+///
+/// Lexical problems (unterminated strings, stray characters) are returned as
+/// errors alongside the tokens rather than aborting, so the token stream always
+/// covers the whole input.
+pub fn tokenize(src: &str) -> (Vec<Token>, Vec<ParseError>) {
+    let mut lexer = Lexer::new(src);
+    let mut tokens = Vec::new();
+    while let Some(tok) = lexer.next_token() {
+        tokens.push(tok);
+    }
+    (tokens, lexer.errors)
+}
+
+struct Lexer<'a> {
+    src: &'a str,
+    pos: Position,
+    errors: Vec<ParseError>,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(src: &'a str) -> Self {
+        Self {
+            src,
+            pos: Position::START,
+            errors: Vec::new(),
+        }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.src[self.pos.offset..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn peek_nth(&self, n: usize) -> Option<char> {
+        self.rest().chars().nth(n)
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos.offset += c.len_utf8();
+        if c == '\n' {
+            self.pos.line += 1;
+            self.pos.column = 1;
+        } else {
+            self.pos.column += 1;
+        }
+        Some(c)
+    }
+
+    fn bump_while(&mut self, mut pred: impl FnMut(char) -> bool) {
+        while let Some(c) = self.peek() {
+            if !pred(c) {
+                break;
+            }
+            self.bump();
+        }
+    }
+
+    /// Whether the lexer is at the first non-blank character of a line.
+    fn at_line_start(&self) -> bool {
+        self.src[..self.pos.offset]
+            .rsplit('\n')
+            .next()
+            .is_none_or(|before| before.chars().all(|c| c == ' ' || c == '\t'))
+    }
+
+    fn next_token(&mut self) -> Option<Token> {
+        self.bump_while(char::is_whitespace);
+        let start = self.pos;
+        let c = self.peek()?;
+
+        let kind = match c {
+            '{' => self.single(TokenKind::LBrace),
+            '}' => self.single(TokenKind::RBrace),
+            '[' => self.single(TokenKind::LBracket),
+            ']' => self.single(TokenKind::RBracket),
+            '=' => self.single(TokenKind::Equals),
+            ';' => self.single(TokenKind::Semicolon),
+            ',' => self.single(TokenKind::Comma),
+            ':' => self.single(TokenKind::Colon),
+            '+' => self.single(TokenKind::Plus),
+            '"' => self.quoted_string(start),
+            '<' => self.html(start),
+            '#' if self.at_line_start() => {
+                self.bump_while(|c| c != '\n');
+                TokenKind::Preprocessor
+            }
+            '/' if self.peek_nth(1) == Some('/') => {
+                self.bump_while(|c| c != '\n');
+                TokenKind::LineComment
+            }
+            '/' if self.peek_nth(1) == Some('*') => self.block_comment(start),
+            '-' if self.peek_nth(1) == Some('>') => {
+                self.bump();
+                self.bump();
+                TokenKind::DirectedEdge
+            }
+            '-' if self.peek_nth(1) == Some('-') => {
+                self.bump();
+                self.bump();
+                TokenKind::UndirectedEdge
+            }
+            '-' | '.' | '0'..='9' if self.numeral_ahead() => self.numeral(),
+            c if is_ident_start(c) => {
+                self.bump_while(is_ident_continue);
+                keyword_or_ident(&self.src[start.offset..self.pos.offset])
+            }
+            _ => {
+                self.bump();
+                let span = Span::new(start, self.pos);
+                self.errors
+                    .push(ParseError::new(format!("unexpected character '{c}'"), span));
+                TokenKind::Unknown
+            }
+        };
+
+        Some(Token {
+            kind,
+            span: Span::new(start, self.pos),
+        })
+    }
+
+    fn single(&mut self, kind: TokenKind) -> TokenKind {
+        self.bump();
+        kind
+    }
+
+    fn numeral_ahead(&self) -> bool {
+        let mut chars = self.rest().chars();
+        let mut c = chars.next();
+        if c == Some('-') {
+            c = chars.next();
+        }
+        match c {
+            Some('0'..='9') => true,
+            Some('.') => matches!(chars.next(), Some('0'..='9')),
+            _ => false,
+        }
+    }
+
+    fn numeral(&mut self) -> TokenKind {
+        if self.peek() == Some('-') {
+            self.bump();
+        }
+        self.bump_while(|c| c.is_ascii_digit());
+        if self.peek() == Some('.') {
+            self.bump();
+            self.bump_while(|c| c.is_ascii_digit());
+        }
+        TokenKind::Numeral
+    }
+
+    fn quoted_string(&mut self, start: Position) -> TokenKind {
+        self.bump(); // opening quote
+        loop {
+            match self.bump() {
+                Some('"') => break,
+                Some('\\') => {
+                    // Skip the escaped char, whatever it is.
+                    self.bump();
+                }
+                Some(_) => {}
+                None => {
+                    self.errors.push(ParseError::new(
+                        "unterminated string",
+                        Span::new(start, self.pos),
+                    ));
+                    break;
+                }
+            }
+        }
+        TokenKind::QuotedString
+    }
+
+    fn html(&mut self, start: Position) -> TokenKind {
+        let mut depth = 0usize;
+        loop {
+            match self.bump() {
+                Some('<') => depth += 1,
+                Some('>') => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                Some(_) => {}
+                None => {
+                    self.errors.push(ParseError::new(
+                        "unterminated HTML string",
+                        Span::new(start, self.pos),
+                    ));
+                    break;
+                }
+            }
+        }
+        TokenKind::Html
+    }
+
+    fn block_comment(&mut self, start: Position) -> TokenKind {
+        self.bump();
+        self.bump();
+        loop {
+            if self.rest().starts_with("*/") {
+                self.bump();
+                self.bump();
+                break;
+            }
+            if self.bump().is_none() {
+                self.errors.push(ParseError::new(
+                    "unterminated block comment",
+                    Span::new(start, self.pos),
+                ));
+                break;
+            }
+        }
+        TokenKind::BlockComment
+    }
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_' || !c.is_ascii()
+}
+
+fn is_ident_continue(c: char) -> bool {
+    is_ident_start(c) || c.is_ascii_digit()
+}
+
+fn keyword_or_ident(text: &str) -> TokenKind {
+    match text.to_ascii_lowercase().as_str() {
+        "strict" => TokenKind::Strict,
+        "graph" => TokenKind::Graph,
+        "digraph" => TokenKind::Digraph,
+        "node" => TokenKind::Node,
+        "edge" => TokenKind::Edge,
+        "subgraph" => TokenKind::Subgraph,
+        _ => TokenKind::Ident,
+    }
+}
@@ -0,0 +1,29 @@
+//! A parser for the Graphviz DOT language.
+//!
+//! Produces a typed syntax tree ([ast::Document]) with source spans on every
+//! node, and keeps going after syntax errors so that editors and linters can
+//! still reason about the rest of the graph while the user is typing.
+//!
+//! ```
+//! let parse = dot_lang::parse("digraph { a -> b [label=\"hi\"] }");
+//! assert!(parse.is_ok());
+//! assert_eq!(parse.document.graphs.len(), 1);
+//! ```
+pub mod ast;
+pub mod lexer;
+pub mod span;
+
+mod error;
+pub use error::ParseError;
+
+mod parser;
+pub use parser::{parse, Parse};
+
+pub mod attrs;
+pub mod complete;
+pub mod diff;
+pub mod format;
+pub mod highlight;
+pub mod lint;
+pub mod navigate;
+pub mod source_map;
@@ -0,0 +1,353 @@
+//! Pre-render checks for DOT documents.
+//!
+//! Graphviz accepts a lot of input that silently does nothing: misspelled
+//! attribute names, unknown shapes, `lhead` pointing at a cluster that doesn't
+//! exist. The linter catches those before the source is handed to the renderer.
+use std::collections::{HashMap, HashSet};
+
+use crate::ast::{
+    AttrList, AttrTarget, Attribute, Document, EdgeTarget, Graph, GraphKind, Stmt, Subgraph,
+};
+use crate::attrs::{self, AttrInfo, Context, ValueKind};
+use crate::span::Span;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub span: Span,
+}
+
+impl Diagnostic {
+    /// 1-based line the diagnostic starts on.
+    pub fn line(&self) -> u32 {
+        self.span.line()
+    }
+}
+
+#[derive(Default)]
+pub struct LintOptions<'a> {
+    /// Resolves internal `.dot` link targets (the path part of a `URL` or
+    /// `href`, without any `#fragment`). Links are not checked when unset.
+    pub link_exists: Option<&'a dyn Fn(&str) -> bool>,
+}
+
+/// Parse `src` and lint whatever could be recovered. Syntax errors are not
+/// included; they come from [crate::parse].
+pub fn lint_source(src: &str, options: &LintOptions) -> Vec<Diagnostic> {
+    lint(&crate::parse(src).document, options)
+}
+
+/// Run every check over `doc`. Diagnostics are sorted by position.
+pub fn lint(doc: &Document, options: &LintOptions) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for graph in &doc.graphs {
+        let mut linter = Linter::new(graph, options);
+        linter.stmts(&graph.stmts, Context::Graph);
+        diagnostics.append(&mut linter.diagnostics);
+    }
+    diagnostics.sort_by_key(|d| d.span.start.offset);
+    diagnostics
+}
+
+struct Linter<'a> {
+    kind: GraphKind,
+    options: &'a LintOptions<'a>,
+    /// Subgraphs that have a body, by name; `true` if it is a cluster.
+    subgraphs: HashMap<String, bool>,
+    /// Values of every `id` attribute, for `#fragment` links.
+    ids: HashSet<String>,
+    compound: bool,
+    /// Attributes given to each node by node statements, with the line they
+    /// were set on.
+    node_attrs: HashMap<String, HashMap<String, (String, u32)>>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Linter<'a> {
+    fn new(graph: &Graph, options: &'a LintOptions<'a>) -> Self {
+        let mut linter = Self {
+            kind: graph.kind,
+            options,
+            subgraphs: HashMap::new(),
+            ids: HashSet::new(),
+            compound: false,
+            node_attrs: HashMap::new(),
+            diagnostics: Vec::new(),
+        };
+        linter.collect(&graph.stmts);
+        linter
+    }
+
+    fn push(&mut self, severity: Severity, message: impl Into<String>, span: Span) {
+        self.diagnostics.push(Diagnostic {
+            severity,
+            message: message.into(),
+            span,
+        });
+    }
+
+    // ------------------------- Definitions -------------------------
+
+    /// First pass: gather names that later checks resolve against.
+    fn collect(&mut self, stmts: &[Stmt]) {
+        for stmt in stmts {
+            match stmt {
+                Stmt::Subgraph(sub) => self.collect_subgraph(sub),
+                Stmt::Edge(edge) => {
+                    for target in edge.targets() {
+                        if let EdgeTarget::Subgraph(sub) = target {
+                            self.collect_subgraph(sub);
+                        }
+                    }
+                    self.collect_attrs(&edge.attrs);
+                }
+                Stmt::Node(node) => self.collect_attrs(&node.attrs),
+                Stmt::Attr(attr) => {
+                    if attr.target == AttrTarget::Graph {
+                        for list in &attr.attrs {
+                            for a in &list.attrs {
+                                self.collect_graph_attr(a);
+                            }
+                        }
+                    }
+                    self.collect_attrs(&attr.attrs);
+                }
+                Stmt::Assign(a) => {
+                    self.collect_graph_attr(a);
+                    if a.key.value == "id" {
+                        self.ids.insert(a.value.value.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    fn collect_subgraph(&mut self, sub: &Subgraph) {
+        if !sub.has_body {
+            return;
+        }
+        if let Some(id) = &sub.id {
+            self.subgraphs.insert(id.value.clone(), sub.is_cluster());
+        }
+        self.collect(&sub.stmts);
+    }
+
+    fn collect_attrs(&mut self, lists: &[AttrList]) {
+        for a in lists.iter().flat_map(|l| &l.attrs) {
+            if a.key.value == "id" {
+                self.ids.insert(a.value.value.clone());
+            }
+        }
+    }
+
+    fn collect_graph_attr(&mut self, a: &Attribute) {
+        if a.key.value == "compound" && ValueKind::Bool.check(&a.value.value).is_ok() {
+            self.compound = matches!(a.value.value.to_ascii_lowercase().as_str(), "true" | "yes")
+                || a.value.value.parse::<i64>().is_ok_and(|n| n != 0);
+        }
+    }
+
+    // ------------------------- Statements -------------------------
+
+    fn stmts(&mut self, stmts: &[Stmt], ctx: Context) {
+        for stmt in stmts {
+            match stmt {
+                Stmt::Node(node) => {
+                    self.attr_lists(&node.attrs, Context::Node);
+                    self.node_definition(&node.node.id.value, &node.attrs);
+                }
+                Stmt::Edge(edge) => {
+                    for rhs in &edge.rest {
+                        if rhs.op != self.kind.edge_op() {
+                            let expected = self.kind.edge_op().as_str();
+                            self.push(
+                                Severity::Error,
+                                format!(
+                                    "'{}' is not valid in a {}; use '{expected}'",
+                                    rhs.op.as_str(),
+                                    self.kind.keyword()
+                                ),
+                                rhs.op_span,
+                            );
+                        }
+                    }
+                    for target in edge.targets() {
+                        if let EdgeTarget::Subgraph(sub) = target {
+                            self.subgraph(sub);
+                        }
+                    }
+                    self.attr_lists(&edge.attrs, Context::Edge);
+                }
+                Stmt::Attr(attr) => {
+                    let target = match attr.target {
+                        AttrTarget::Graph => ctx,
+                        AttrTarget::Node => Context::Node,
+                        AttrTarget::Edge => Context::Edge,
+                    };
+                    self.attr_lists(&attr.attrs, target);
+                }
+                Stmt::Assign(a) => self.attribute(a, ctx),
+                Stmt::Subgraph(sub) => self.subgraph(sub),
+            }
+        }
+    }
+
+    fn subgraph(&mut self, sub: &Subgraph) {
+        if !sub.has_body {
+            if let Some(id) = &sub.id {
+                if !self.subgraphs.contains_key(&id.value) {
+                    self.push(
+                        Severity::Warning,
+                        format!("subgraph '{}' is referenced but never defined", id.value),
+                        id.span,
+                    );
+                }
+            }
+            return;
+        }
+        let ctx = if sub.is_cluster() {
+            Context::Cluster
+        } else {
+            Context::Subgraph
+        };
+        self.stmts(&sub.stmts, ctx);
+    }
+
+    /// Warn when a node statement gives a node a different value for an
+    /// attribute it was already given; the later one silently wins.
+    fn node_definition(&mut self, name: &str, lists: &[AttrList]) {
+        let mut conflicts = Vec::new();
+        let known = self.node_attrs.entry(name.to_string()).or_default();
+        for a in lists.iter().flat_map(|l| &l.attrs) {
+            let line = a.span.line();
+            match known.get(&a.key.value) {
+                Some((value, prev_line)) if *value != a.value.value && *prev_line != line => {
+                    conflicts.push(Diagnostic {
+                        severity: Severity::Warning,
+                        message: format!(
+                            "node '{name}' already has {}={} (line {prev_line}); this overrides it",
+                            a.key.value, value
+                        ),
+                        span: a.span,
+                    });
+                }
+                _ => {}
+            }
+            known.insert(a.key.value.clone(), (a.value.value.clone(), line));
+        }
+        self.diagnostics.append(&mut conflicts);
+    }
+
+    // ------------------------- Attributes -------------------------
+
+    fn attr_lists(&mut self, lists: &[AttrList], ctx: Context) {
+        for a in lists.iter().flat_map(|l| &l.attrs) {
+            self.attribute(a, ctx);
+        }
+    }
+
+    fn attribute(&mut self, a: &Attribute, ctx: Context) {
+        let key = a.key.as_str();
+        let Some(info) = attrs::lookup(key) else {
+            let names = attrs::ATTRIBUTES.iter().map(|a| a.name);
+            let message = match attrs::closest(key, names) {
+                Some(name) => format!("unknown attribute '{key}'; did you mean '{name}'?"),
+                None => format!("unknown attribute '{key}'"),
+            };
+            self.push(Severity::Warning, message, a.key.span);
+            return;
+        };
+
+        if !applies(info, ctx) {
+            self.push(
+                Severity::Warning,
+                format!("'{key}' has no effect on {}", ctx.name()),
+                a.key.span,
+            );
+        }
+
+        if !a.value.is_html() {
+            if let Err(problem) = info.kind.check(&a.value.value) {
+                self.push(Severity::Warning, format!("{key}: {problem}"), a.value.span);
+            }
+        }
+
+        match key {
+            "lhead" | "ltail" => self.cluster_ref(a),
+            "URL" | "href" => self.link(a),
+            _ => {}
+        }
+    }
+
+    fn cluster_ref(&mut self, a: &Attribute) {
+        let name = a.value.as_str();
+        let message = match self.subgraphs.get(name) {
+            None => format!("no subgraph named '{name}'"),
+            Some(false) => format!("'{name}' is not a cluster; its name must start with 'cluster'"),
+            Some(true) if !self.compound => {
+                format!(
+                    "{} has no effect unless the graph sets compound=true",
+                    a.key.value
+                )
+            }
+            Some(true) => return,
+        };
+        self.push(Severity::Warning, message, a.value.span);
+    }
+
+    fn link(&mut self, a: &Attribute) {
+        let url = a.value.as_str();
+        // Escapes like `\N` are expanded per node by Graphviz.
+        if a.value.is_html() || url.contains('\\') {
+            return;
+        }
+        if let Some(fragment) = url.strip_prefix('#') {
+            if !self.ids.contains(fragment) {
+                self.push(
+                    Severity::Warning,
+                    format!("no element has id=\"{fragment}\""),
+                    a.value.span,
+                );
+            }
+            return;
+        }
+        let Some(exists) = self.options.link_exists else {
+            return;
+        };
+        if url.contains("://") || url.starts_with("mailto:") {
+            return;
+        }
+        let path = url.split(['#', '?']).next().unwrap_or(url);
+        if path.ends_with(".dot") && !exists(path) {
+            self.push(
+                Severity::Warning,
+                format!("link target '{path}' does not exist"),
+                a.value.span,
+            );
+        }
+    }
+}
+
+/// Graph-level attribute statements also set defaults for nested subgraphs and
+/// clusters, so anything a subgraph or cluster understands is fine there too.
+fn applies(info: &AttrInfo, ctx: Context) -> bool {
+    match ctx {
+        Context::Graph => {
+            info.applies_to(Context::Graph)
+                || info.applies_to(Context::Subgraph)
+                || info.applies_to(Context::Cluster)
+        }
+        Context::Subgraph | Context::Cluster => {
+            info.applies_to(Context::Subgraph) || info.applies_to(Context::Cluster)
+        }
+        Context::Node | Context::Edge => info.applies_to(ctx),
+    }
+}
@@ -0,0 +1,275 @@
+//! Hover information and go-to-definition for DOT source.
+use crate::ast::{
+    walk_stmts, AttrList, AttrTarget, Attribute, Document, EdgeTarget, Id, NodeId, Stmt, Subgraph,
+};
+use crate::attrs::{self, Context, ValueKind};
+use crate::span::Span;
+
+/// What to show when hovering a position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hover {
+    /// The source range the information is about.
+    pub span: Span,
+    pub title: String,
+    /// Paragraphs of plain text.
+    pub body: Vec<String>,
+    /// Where to read more, e.g. the Graphviz documentation.
+    pub url: Option<String>,
+}
+
+/// Where go-to-definition leads.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Definition {
+    /// A place in the same document.
+    Span(Span),
+    /// The target of a `URL` or `href` attribute, as written.
+    Link(String),
+}
+
+/// The thing under the cursor.
+enum Symbol<'a> {
+    AttrKey(&'a Attribute, Context),
+    AttrValue(&'a Attribute),
+    Node(&'a Id),
+    /// `subgraph name` without a body
+    SubgraphRef(&'a Id),
+}
+
+/// Hover information for byte `offset`, if it is on an attribute or node ID.
+pub fn hover(doc: &Document, offset: usize) -> Option<Hover> {
+    match symbol_at(doc, offset)? {
+        Symbol::AttrKey(attr, ctx) => {
+            let info = attrs::lookup(attr.key.as_str())?;
+            let mut body = vec![info.doc.to_string()];
+            let used_by: Vec<&str> = info.contexts().iter().map(|c| c.name()).collect();
+            body.push(format!("Used by {}.", used_by.join(", ")));
+            if !info.applies_to(ctx) && ctx != Context::Graph {
+                body.push(format!("Has no effect on {}.", ctx.name()));
+            }
+            if let Some(values) = allowed_values(info.kind) {
+                body.push(values);
+            }
+            Some(Hover {
+                span: attr.key.span,
+                title: info.name.to_string(),
+                body,
+                url: Some(info.doc_url()),
+            })
+        }
+        Symbol::AttrValue(attr) => {
+            let key = attr.key.as_str();
+            if matches!(key, "URL" | "href") {
+                return Some(Hover {
+                    span: attr.value.span,
+                    title: attr.value.value.clone(),
+                    body: vec!["Ctrl+click to open.".to_string()],
+                    url: None,
+                });
+            }
+            let info = attrs::lookup(key)?;
+            let mut body = vec![info.doc.to_string()];
+            body.extend(allowed_values(info.kind));
+            Some(Hover {
+                span: attr.value.span,
+                title: format!("{key}={}", attr.value.value),
+                body,
+                url: Some(info.doc_url()),
+            })
+        }
+        Symbol::Node(id) => {
+            let occurrences = node_occurrences(doc, &id.value);
+            let first = occurrences
+                .iter()
+                .find(|o| o.declared)
+                .or(occurrences.first())?;
+            let mut body = vec![format!("Declared on line {}.", first.id.span.line())];
+            let attrs: Vec<String> = occurrences
+                .iter()
+                .flat_map(|o| o.attrs.iter().flat_map(|l| &l.attrs))
+                .map(|a| format!("{}={}", a.key.value, a.value.value))
+                .collect();
+            if !attrs.is_empty() {
+                body.push(attrs.join(", "));
+            }
+            Some(Hover {
+                span: id.span,
+                title: format!("node {}", id.value),
+                body,
+                url: None,
+            })
+        }
+        Symbol::SubgraphRef(id) => Some(Hover {
+            span: id.span,
+            title: format!("subgraph {}", id.value),
+            body: vec![match find_subgraph(doc, &id.value) {
+                Some(sub) => format!("Defined on line {}.", sub.span.line()),
+                None => "Not defined in this file.".to_string(),
+            }],
+            url: None,
+        }),
+    }
+}
+
+/// Where the symbol at byte `offset` is defined: the first declaration of a
+/// node, the body of a referenced subgraph or cluster, or a link target.
+pub fn definition(doc: &Document, offset: usize) -> Option<Definition> {
+    match symbol_at(doc, offset)? {
+        Symbol::Node(id) => {
+            let occurrences = node_occurrences(doc, &id.value);
+            let first = occurrences
+                .iter()
+                .find(|o| o.declared)
+                .or(occurrences.first())?;
+            Some(Definition::Span(first.id.span))
+        }
+        Symbol::AttrValue(attr) => match attr.key.as_str() {
+            "URL" | "href" if !attr.value.is_html() => {
+                Some(Definition::Link(attr.value.value.clone()))
+            }
+            "lhead" | "ltail" => {
+                find_subgraph(doc, attr.value.as_str()).map(|sub| Definition::Span(sub.span))
+            }
+            _ => None,
+        },
+        Symbol::SubgraphRef(id) => {
+            find_subgraph(doc, &id.value).map(|sub| Definition::Span(sub.span))
+        }
+        Symbol::AttrKey(..) => None,
+    }
+}
+
+fn allowed_values(kind: ValueKind) -> Option<String> {
+    let values = kind.suggestions();
+    (!values.is_empty()).then(|| format!("Values: {}", values.join(", ")))
+}
+
+// ------------------------- Lookup -------------------------
+
+fn symbol_at(doc: &Document, offset: usize) -> Option<Symbol<'_>> {
+    doc.graphs
+        .iter()
+        .filter(|g| g.span.contains(offset))
+        .find_map(|g| stmts_symbol(&g.stmts, Context::Graph, offset))
+}
+
+fn stmts_symbol(stmts: &[Stmt], ctx: Context, offset: usize) -> Option<Symbol<'_>> {
+    let stmt = stmts.iter().find(|s| s.span().contains(offset))?;
+    match stmt {
+        Stmt::Node(node) => node_symbol(&node.node, offset)
+            .or_else(|| lists_symbol(&node.attrs, Context::Node, offset)),
+        Stmt::Edge(edge) => edge
+            .targets()
+            .find_map(|target| match target {
+                EdgeTarget::Node(node) => node_symbol(node, offset),
+                EdgeTarget::Subgraph(sub) => subgraph_symbol(sub, offset),
+            })
+            .or_else(|| lists_symbol(&edge.attrs, Context::Edge, offset)),
+        Stmt::Attr(attr) => {
+            let target = match attr.target {
+                AttrTarget::Graph => ctx,
+                AttrTarget::Node => Context::Node,
+                AttrTarget::Edge => Context::Edge,
+            };
+            lists_symbol(&attr.attrs, target, offset)
+        }
+        Stmt::Assign(a) => attr_symbol(a, ctx, offset),
+        Stmt::Subgraph(sub) => subgraph_symbol(sub, offset),
+    }
+}
+
+fn subgraph_symbol(sub: &Subgraph, offset: usize) -> Option<Symbol<'_>> {
+    if let Some(id) = sub.id.as_ref().filter(|id| id.span.contains(offset)) {
+        return (!sub.has_body).then_some(Symbol::SubgraphRef(id));
+    }
+    let ctx = if sub.is_cluster() {
+        Context::Cluster
+    } else {
+        Context::Subgraph
+    };
+    stmts_symbol(&sub.stmts, ctx, offset)
+}
+
+fn node_symbol(node: &NodeId, offset: usize) -> Option<Symbol<'_>> {
+    node.id
+        .span
+        .contains(offset)
+        .then_some(Symbol::Node(&node.id))
+}
+
+fn lists_symbol(lists: &[AttrList], ctx: Context, offset: usize) -> Option<Symbol<'_>> {
+    lists
+        .iter()
+        .flat_map(|l| &l.attrs)
+        .find_map(|a| attr_symbol(a, ctx, offset))
+}
+
+fn attr_symbol(a: &Attribute, ctx: Context, offset: usize) -> Option<Symbol<'_>> {
+    if a.key.span.contains(offset) {
+        Some(Symbol::AttrKey(a, ctx))
+    } else if a.value.span.contains(offset) {
+        Some(Symbol::AttrValue(a))
+    } else {
+        None
+    }
+}
+
+struct Occurrence<'a> {
+    id: &'a Id,
+    /// Whether this is a node statement rather than an edge endpoint.
+    declared: bool,
+    attrs: &'a [AttrList],
+}
+
+/// Every mention of node `name`, in source order.
+fn node_occurrences<'a>(doc: &'a Document, name: &str) -> Vec<Occurrence<'a>> {
+    let mut out = Vec::new();
+    for graph in &doc.graphs {
+        walk_stmts(&graph.stmts, &mut |stmt| match stmt {
+            Stmt::Node(node) if node.node.id.value == name => out.push(Occurrence {
+                id: &node.node.id,
+                declared: true,
+                attrs: &node.attrs,
+            }),
+            Stmt::Edge(edge) => {
+                for target in edge.targets() {
+                    if let EdgeTarget::Node(node) = target {
+                        if node.id.value == name {
+                            out.push(Occurrence {
+                                id: &node.id,
+                                declared: false,
+                                attrs: &[],
+                            });
+                        }
+                    }
+                }
+            }
+            _ => {}
+        });
+    }
+    out.sort_by_key(|o| o.id.span.start.offset);
+    out
+}
+
+fn find_subgraph<'a>(doc: &'a Document, name: &str) -> Option<&'a Subgraph> {
+    let mut found = None;
+    let mut check = |sub: &'a Subgraph| {
+        let named = sub.id.as_ref().is_some_and(|id| id.value == name);
+        if named && sub.has_body && found.is_none() {
+            found = Some(sub);
+        }
+    };
+    for graph in &doc.graphs {
+        walk_stmts(&graph.stmts, &mut |stmt| match stmt {
+            Stmt::Subgraph(sub) => check(sub),
+            Stmt::Edge(edge) => {
+                for target in edge.targets() {
+                    if let EdgeTarget::Subgraph(sub) = target {
+                        check(sub);
+                    }
+                }
+            }
+            _ => {}
+        });
+    }
+    found
+}
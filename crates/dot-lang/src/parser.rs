@@ -0,0 +1,584 @@
+//! Recursive-descent parser with statement-level error recovery.
+use crate::ast::*;
+use crate::error::ParseError;
+use crate::lexer::{tokenize, Token, TokenKind};
+use crate::span::{Position, Span};
+
+/// The result of parsing a document.
+///
+/// Parsing never fails outright: `document` holds everything that could be
+/// recovered and `errors` lists every syntax error found along the way.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Parse {
+    pub document: Document,
+    pub errors: Vec<ParseError>,
+}
+
+impl Parse {
+    /// Whether the source parsed without any errors.
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Parse DOT source into a [Document].
+pub fn parse(src: &str) -> Parse {
+    let (tokens, lex_errors) = tokenize(src);
+
+    let mut comments = Vec::new();
+    let mut significant = Vec::with_capacity(tokens.len());
+    for tok in tokens {
+        let kind = match tok.kind {
+            TokenKind::LineComment => CommentKind::Line,
+            TokenKind::BlockComment => CommentKind::Block,
+            TokenKind::Preprocessor => CommentKind::Preprocessor,
+            // Already reported by the lexer, nothing more the parser can do with it.
+            TokenKind::Unknown => continue,
+            _ => {
+                significant.push(tok);
+                continue;
+            }
+        };
+        comments.push(Comment {
+            kind,
+            text: tok.text(src).to_string(),
+            span: tok.span,
+        });
+    }
+
+    let eof = end_position(src);
+    let mut parser = Parser {
+        src,
+        tokens: significant,
+        pos: 0,
+        eof,
+        errors: lex_errors,
+    };
+
+    let mut graphs = Vec::new();
+    while parser.peek().is_some() {
+        if parser.at_graph_start() {
+            graphs.push(parser.graph());
+        } else {
+            parser.error_here("expected 'graph' or 'digraph'");
+            while parser.peek().is_some() && !parser.at_graph_start() {
+                parser.bump();
+            }
+        }
+    }
+
+    let mut errors = parser.errors;
+    errors.sort_by_key(|e| e.span.start);
+
+    Parse {
+        document: Document {
+            graphs,
+            comments,
+            span: Span::new(Position::START, eof),
+        },
+        errors,
+    }
+}
+
+fn end_position(src: &str) -> Position {
+    let line = src.matches('\n').count() as u32 + 1;
+    let last_line = src.rsplit('\n').next().unwrap_or_default();
+    Position {
+        offset: src.len(),
+        line,
+        column: last_line.chars().count() as u32 + 1,
+    }
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    tokens: Vec<Token>,
+    pos: usize,
+    eof: Position,
+    errors: Vec<ParseError>,
+}
+
+impl<'a> Parser<'a> {
+    // ------------------------- Token helpers -------------------------
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn nth_kind(&self, n: usize) -> Option<TokenKind> {
+        self.tokens.get(self.pos + n).map(|t| t.kind)
+    }
+
+    fn at(&self, kind: TokenKind) -> bool {
+        self.nth_kind(0) == Some(kind)
+    }
+
+    fn at_id(&self) -> bool {
+        self.nth_kind(0).is_some_and(TokenKind::is_id)
+    }
+
+    fn at_edge_op(&self) -> bool {
+        matches!(
+            self.nth_kind(0),
+            Some(TokenKind::DirectedEdge | TokenKind::UndirectedEdge)
+        )
+    }
+
+    fn at_graph_start(&self) -> bool {
+        match self.nth_kind(0) {
+            Some(TokenKind::Strict | TokenKind::Digraph) => true,
+            // `graph [...]` is an attribute statement, not a new graph.
+            Some(TokenKind::Graph) => self.nth_kind(1) != Some(TokenKind::LBracket),
+            _ => false,
+        }
+    }
+
+    fn bump(&mut self) -> Option<Token> {
+        let tok = self.tokens.get(self.pos).copied();
+        if tok.is_some() {
+            self.pos += 1;
+        }
+        tok
+    }
+
+    fn eat(&mut self, kind: TokenKind) -> Option<Token> {
+        if self.at(kind) {
+            self.bump()
+        } else {
+            None
+        }
+    }
+
+    /// The span of the current token, or an empty span at the end of input.
+    fn current_span(&self) -> Span {
+        self.peek()
+            .map(|t| t.span)
+            .unwrap_or_else(|| Span::empty(self.eof))
+    }
+
+    /// The end of the most recently consumed token.
+    fn prev_end(&self) -> Position {
+        self.pos
+            .checked_sub(1)
+            .and_then(|i| self.tokens.get(i))
+            .map(|t| t.span.end)
+            .unwrap_or(Position::START)
+    }
+
+    fn span_from(&self, start: Position) -> Span {
+        Span::new(start, self.prev_end().max(start))
+    }
+
+    fn error_here(&mut self, message: impl Into<String>) {
+        let span = self.current_span();
+        self.errors.push(ParseError::new(message, span));
+    }
+
+    /// Report a missing token just after the previous one, which is where the
+    /// user needs to type it.
+    fn error_after_prev(&mut self, message: impl Into<String>) {
+        let span = Span::empty(self.prev_end());
+        self.errors.push(ParseError::new(message, span));
+    }
+
+    fn expect(&mut self, kind: TokenKind, what: &str) -> Option<Token> {
+        let tok = self.eat(kind);
+        if tok.is_none() {
+            self.error_here(format!("expected {what}"));
+        }
+        tok
+    }
+
+    /// Skip to a point where a new statement can plausibly start: after a `;`,
+    /// before a `}`, or at the first statement-starting token on a later line.
+    fn recover(&mut self) {
+        let line = self.current_span().line();
+        while let Some(tok) = self.peek() {
+            match tok.kind {
+                TokenKind::Semicolon => {
+                    self.bump();
+                    return;
+                }
+                TokenKind::RBrace => return,
+                kind if tok.span.line() > line && can_start_stmt(kind) => return,
+                _ => {
+                    self.bump();
+                }
+            }
+        }
+    }
+
+    // ------------------------- Grammar -------------------------
+
+    fn graph(&mut self) -> Graph {
+        let start = self.current_span().start;
+        let strict = self.eat(TokenKind::Strict).is_some();
+        let kind = match self.nth_kind(0) {
+            Some(TokenKind::Digraph) => {
+                self.bump();
+                GraphKind::Digraph
+            }
+            Some(TokenKind::Graph) => {
+                self.bump();
+                GraphKind::Graph
+            }
+            _ => {
+                self.error_here("expected 'graph' or 'digraph'");
+                GraphKind::Digraph
+            }
+        };
+        let id = if self.at_id() { Some(self.id()) } else { None };
+
+        let stmts = if self.expect(TokenKind::LBrace, "'{'").is_some() {
+            let stmts = self.stmt_list();
+            self.expect(TokenKind::RBrace, "'}'");
+            stmts
+        } else {
+            Vec::new()
+        };
+
+        Graph {
+            strict,
+            kind,
+            id,
+            stmts,
+            span: self.span_from(start),
+        }
+    }
+
+    fn stmt_list(&mut self) -> Vec<Stmt> {
+        let mut stmts = Vec::new();
+        loop {
+            match self.nth_kind(0) {
+                None | Some(TokenKind::RBrace) => break,
+                Some(TokenKind::Semicolon) => {
+                    self.bump();
+                    continue;
+                }
+                _ => {}
+            }
+
+            let before = self.pos;
+            match self.stmt() {
+                Some(stmt) => {
+                    stmts.push(stmt);
+                    self.eat(TokenKind::Semicolon);
+                }
+                None => self.recover(),
+            }
+            if self.pos == before {
+                // Always make progress, even on garbage.
+                self.bump();
+            }
+        }
+        stmts
+    }
+
+    fn stmt(&mut self) -> Option<Stmt> {
+        let start = self.current_span().start;
+        match self.nth_kind(0)? {
+            TokenKind::Graph | TokenKind::Node | TokenKind::Edge => {
+                let target = match self.bump()?.kind {
+                    TokenKind::Graph => AttrTarget::Graph,
+                    TokenKind::Node => AttrTarget::Node,
+                    _ => AttrTarget::Edge,
+                };
+                if !self.at(TokenKind::LBracket) {
+                    self.error_here(format!("expected '[' after '{}'", target.keyword()));
+                    return None;
+                }
+                let attrs = self.attr_lists()?;
+                Some(Stmt::Attr(AttrStmt {
+                    target,
+                    attrs,
+                    span: self.span_from(start),
+                }))
+            }
+            TokenKind::Subgraph | TokenKind::LBrace => {
+                let sub = self.subgraph()?;
+                if self.at_edge_op() {
+                    self.edge_stmt(start, EdgeTarget::Subgraph(sub))
+                } else {
+                    Some(Stmt::Subgraph(sub))
+                }
+            }
+            kind if kind.is_id() => {
+                if self.nth_kind(1) == Some(TokenKind::Equals) {
+                    return self.attribute().map(Stmt::Assign);
+                }
+                let node = self.node_id()?;
+                if self.at_edge_op() {
+                    self.edge_stmt(start, EdgeTarget::Node(node))
+                } else {
+                    let attrs = self.attr_lists()?;
+                    Some(Stmt::Node(NodeStmt {
+                        node,
+                        attrs,
+                        span: self.span_from(start),
+                    }))
+                }
+            }
+            TokenKind::Strict | TokenKind::Digraph => {
+                self.error_here("unexpected graph declaration; is a '}' missing?");
+                None
+            }
+            _ => {
+                let text = self.peek()?.text(self.src).to_string();
+                self.error_here(format!("expected a statement, found '{text}'"));
+                None
+            }
+        }
+    }
+
+    fn edge_stmt(&mut self, start: Position, head: EdgeTarget) -> Option<Stmt> {
+        let mut rest = Vec::new();
+        while self.at_edge_op() {
+            let op_tok = self.bump()?;
+            let op = if op_tok.kind == TokenKind::DirectedEdge {
+                EdgeOp::Directed
+            } else {
+                EdgeOp::Undirected
+            };
+            let target = match self.nth_kind(0) {
+                Some(TokenKind::Subgraph | TokenKind::LBrace) => {
+                    EdgeTarget::Subgraph(self.subgraph()?)
+                }
+                Some(kind) if kind.is_id() => EdgeTarget::Node(self.node_id()?),
+                _ => {
+                    self.error_here(format!(
+                        "expected a node or subgraph after '{}'",
+                        op.as_str()
+                    ));
+                    return None;
+                }
+            };
+            rest.push(EdgeRhs {
+                op,
+                op_span: op_tok.span,
+                target,
+            });
+        }
+        let attrs = self.attr_lists()?;
+        Some(Stmt::Edge(EdgeStmt {
+            head,
+            rest,
+            attrs,
+            span: self.span_from(start),
+        }))
+    }
+
+    fn subgraph(&mut self) -> Option<Subgraph> {
+        let start = self.current_span().start;
+        let keyword = self.eat(TokenKind::Subgraph).is_some();
+        let id = if keyword && self.at_id() {
+            Some(self.id())
+        } else {
+            None
+        };
+        if id.is_some() && !self.at(TokenKind::LBrace) {
+            // `subgraph ID` on its own refers to a subgraph defined elsewhere.
+            return Some(Subgraph {
+                keyword,
+                id,
+                has_body: false,
+                stmts: Vec::new(),
+                span: self.span_from(start),
+            });
+        }
+        self.expect(TokenKind::LBrace, "'{'")?;
+        let stmts = self.stmt_list();
+        self.expect(TokenKind::RBrace, "'}'");
+        Some(Subgraph {
+            keyword,
+            id,
+            has_body: true,
+            stmts,
+            span: self.span_from(start),
+        })
+    }
+
+    fn node_id(&mut self) -> Option<NodeId> {
+        let id = self.id();
+        let start = id.span.start;
+        let port = if self.at(TokenKind::Colon) {
+            let port_start = self.bump()?.span.start;
+            if !self.at_id() {
+                self.error_here("expected a port name after ':'");
+                return None;
+            }
+            let name = self.id();
+            let compass = if self.eat(TokenKind::Colon).is_some() {
+                if !self.at_id() {
+                    self.error_here("expected a compass point after ':'");
+                    return None;
+                }
+                Some(self.id())
+            } else {
+                None
+            };
+            Some(Port {
+                name,
+                compass,
+                span: self.span_from(port_start),
+            })
+        } else {
+            None
+        };
+        Some(NodeId {
+            id,
+            port,
+            span: self.span_from(start),
+        })
+    }
+
+    fn attr_lists(&mut self) -> Option<Vec<AttrList>> {
+        let mut lists = Vec::new();
+        while self.at(TokenKind::LBracket) {
+            lists.push(self.attr_list()?);
+        }
+        Some(lists)
+    }
+
+    fn attr_list(&mut self) -> Option<AttrList> {
+        let open = self.bump()?;
+        let mut attrs = Vec::new();
+        loop {
+            match self.peek().copied() {
+                Some(tok) if tok.kind == TokenKind::RBracket => {
+                    self.bump();
+                    break;
+                }
+                Some(tok) if tok.kind.is_id() && self.nth_kind(1) == Some(TokenKind::Equals) => {
+                    attrs.push(self.attribute()?);
+                    if self.eat(TokenKind::Semicolon).is_none() {
+                        self.eat(TokenKind::Comma);
+                    }
+                }
+                // An ID on the same line that isn't `key=` is missing its `=`.
+                // One on a new line most likely starts the next statement, so
+                // it falls through to the forgotten `]` below.
+                Some(tok) if tok.kind.is_id() && tok.span.line() == self.prev_end().line => {
+                    self.errors.push(ParseError::new(
+                        "expected '=' after attribute name",
+                        Span::new(tok.span.end, tok.span.end),
+                    ));
+                    return None;
+                }
+                _ => {
+                    self.error_after_prev("expected ']'");
+                    break;
+                }
+            }
+        }
+        Some(AttrList {
+            attrs,
+            span: self.span_from(open.span.start),
+        })
+    }
+
+    /// `ID = ID`. The caller has checked that an ID and `=` are next.
+    fn attribute(&mut self) -> Option<Attribute> {
+        let key = self.id();
+        self.bump(); // '='
+        if !self.at_id() {
+            self.error_here(format!("expected a value for '{}'", key.value));
+            return None;
+        }
+        let value = self.id();
+        let span = key.span.to(value.span);
+        Some(Attribute { key, value, span })
+    }
+
+    /// Parse an ID. The caller has checked that one is next.
+    fn id(&mut self) -> Id {
+        let tok = self.bump().expect("caller checked for an ID");
+        let text = tok.text(self.src);
+        match tok.kind {
+            TokenKind::QuotedString => {
+                let mut value = unquote(text);
+                let mut span = tok.span;
+                // "a" + "b" concatenation
+                while self.at(TokenKind::Plus) && self.nth_kind(1) == Some(TokenKind::QuotedString)
+                {
+                    self.bump();
+                    let next = self.bump().expect("checked above");
+                    value.push_str(&unquote(next.text(self.src)));
+                    span = span.to(next.span);
+                }
+                Id {
+                    kind: IdKind::Quoted,
+                    value,
+                    span,
+                }
+            }
+            TokenKind::Html => {
+                let inner = text.strip_prefix('<').unwrap_or(text);
+                let inner = inner.strip_suffix('>').unwrap_or(inner);
+                Id {
+                    kind: IdKind::Html,
+                    value: inner.to_string(),
+                    span: tok.span,
+                }
+            }
+            TokenKind::Numeral => Id {
+                kind: IdKind::Numeral,
+                value: text.to_string(),
+                span: tok.span,
+            },
+            _ => Id {
+                kind: IdKind::Ident,
+                value: text.to_string(),
+                span: tok.span,
+            },
+        }
+    }
+}
+
+fn can_start_stmt(kind: TokenKind) -> bool {
+    kind.is_id()
+        || matches!(
+            kind,
+            TokenKind::Graph
+                | TokenKind::Node
+                | TokenKind::Edge
+                | TokenKind::Subgraph
+                | TokenKind::LBrace
+        )
+}
+
+/// Strip the quotes from a quoted string token and resolve the escapes that
+/// belong to the DOT grammar itself (`\"` and line continuations).
+fn unquote(text: &str) -> String {
+    let inner = text.strip_prefix('"').unwrap_or(text);
+    let inner = inner.strip_suffix('"').unwrap_or(inner);
+
+    let mut out = String::with_capacity(inner.len());
+    let mut chars = inner.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.peek() {
+            Some('"') => {
+                chars.next();
+                out.push('"');
+            }
+            Some('\n') => {
+                chars.next();
+            }
+            Some('\r') => {
+                chars.next();
+                if chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+            }
+            Some('\\') => {
+                chars.next();
+                out.push_str("\\\\");
+            }
+            _ => out.push('\\'),
+        }
+    }
+    out
+}
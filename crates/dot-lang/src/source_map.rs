@@ -0,0 +1,188 @@
+//! Correspondence between DOT statements and the elements Graphviz draws for
+//! them.
+//!
+//! Graphviz gives every node, edge and cluster in its SVG output a `<title>`:
+//! the node name, `tail->head` with any ports, or the cluster name. A
+//! [GraphElement] is that title plus, for edges, which of several edges with
+//! the same title it is, so the same key can be built from the source and from
+//! the rendered SVG.
+use std::collections::HashMap;
+
+use crate::ast::{walk_stmts, Document, EdgeStmt, EdgeTarget, NodeId, Stmt, Subgraph};
+use crate::span::Span;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GraphElement {
+    Node(String),
+    /// `index` counts the earlier edges with the same title, from 0.
+    Edge {
+        title: String,
+        index: usize,
+    },
+    Cluster(String),
+}
+
+struct Entry {
+    element: GraphElement,
+    /// The source that selects the element.
+    span: Span,
+    /// Whether `span` is where the element is defined rather than mentioned.
+    defines: bool,
+}
+
+/// Every element of the first graph in a document, with the source it comes
+/// from. Graphviz only renders the first graph of a file.
+pub struct SourceMap {
+    entries: Vec<Entry>,
+}
+
+impl SourceMap {
+    pub fn new(doc: &Document) -> Self {
+        let mut builder = Builder {
+            edge_op: "",
+            edge_counts: HashMap::new(),
+            entries: Vec::new(),
+        };
+        if let Some(graph) = doc.graphs.first() {
+            builder.edge_op = graph.kind.edge_op().as_str();
+            builder.stmts(&graph.stmts);
+        }
+        SourceMap {
+            entries: builder.entries,
+        }
+    }
+
+    /// The elements for the innermost statement at byte `offset`.
+    ///
+    /// On a node ID that is the node; elsewhere in an edge statement it is every
+    /// edge the statement creates; inside a cluster but outside any statement
+    /// it is the cluster.
+    pub fn elements_at(&self, offset: usize) -> Vec<GraphElement> {
+        let containing = || self.entries.iter().filter(|e| e.span.contains(offset));
+        let Some(innermost) = containing().map(|e| e.span.len()).min() else {
+            return Vec::new();
+        };
+        let mut out: Vec<GraphElement> = Vec::new();
+        for entry in containing().filter(|e| e.span.len() == innermost) {
+            if !out.contains(&entry.element) {
+                out.push(entry.element.clone());
+            }
+        }
+        out
+    }
+
+    /// Where `element` is defined: the first node statement for a node (or its
+    /// first mention if it has none), the edge statement for an edge, and the
+    /// subgraph for a cluster.
+    pub fn span_of(&self, element: &GraphElement) -> Option<Span> {
+        let mut matching = self.entries.iter().filter(|e| e.element == *element);
+        let first = matching.next()?;
+        if first.defines {
+            return Some(first.span);
+        }
+        Some(matching.find(|e| e.defines).unwrap_or(first).span)
+    }
+}
+
+struct Builder {
+    edge_op: &'static str,
+    edge_counts: HashMap<String, usize>,
+    entries: Vec<Entry>,
+}
+
+impl Builder {
+    fn push(&mut self, element: GraphElement, span: Span, defines: bool) {
+        self.entries.push(Entry {
+            element,
+            span,
+            defines,
+        });
+    }
+
+    fn stmts(&mut self, stmts: &[Stmt]) {
+        for stmt in stmts {
+            match stmt {
+                Stmt::Node(node) => {
+                    let name = node.node.id.value.clone();
+                    self.push(GraphElement::Node(name), node.span, true);
+                }
+                Stmt::Edge(edge) => self.edge(edge),
+                Stmt::Subgraph(sub) => self.subgraph(sub),
+                Stmt::Attr(_) | Stmt::Assign(_) => {}
+            }
+        }
+    }
+
+    fn subgraph(&mut self, sub: &Subgraph) {
+        if !sub.has_body {
+            return;
+        }
+        if let Some(id) = sub.id.as_ref().filter(|_| sub.is_cluster()) {
+            self.push(GraphElement::Cluster(id.value.clone()), sub.span, true);
+        }
+        self.stmts(&sub.stmts);
+    }
+
+    /// `a -> b -> c` is two edges; `a -> { b c }` is one edge to each of `b`
+    /// and `c`.
+    fn edge(&mut self, edge: &EdgeStmt) {
+        let mut ends = Vec::new();
+        for target in edge.targets() {
+            match target {
+                EdgeTarget::Node(node) => {
+                    let name = node.id.value.clone();
+                    self.push(GraphElement::Node(name), node.id.span, false);
+                    ends.push(vec![endpoint(node)]);
+                }
+                EdgeTarget::Subgraph(sub) => {
+                    self.subgraph(sub);
+                    ends.push(subgraph_nodes(sub));
+                }
+            }
+        }
+        for pair in ends.windows(2) {
+            for tail in &pair[0] {
+                for head in &pair[1] {
+                    let title = format!("{tail}{}{head}", self.edge_op);
+                    let count = self.edge_counts.entry(title.clone()).or_default();
+                    let index = *count;
+                    *count += 1;
+                    self.push(GraphElement::Edge { title, index }, edge.span, true);
+                }
+            }
+        }
+    }
+}
+
+/// A node as Graphviz writes it in edge titles: `name` or `name:port`.
+pub(crate) fn endpoint(node: &NodeId) -> String {
+    match &node.port {
+        Some(port) => match &port.compass {
+            Some(compass) => format!("{}:{}:{}", node.id.value, port.name.value, compass.value),
+            None => format!("{}:{}", node.id.value, port.name.value),
+        },
+        None => node.id.value.clone(),
+    }
+}
+
+/// The nodes in `sub` and its nested subgraphs, in order of first mention.
+pub(crate) fn subgraph_nodes(sub: &Subgraph) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    let mut add = |name: &str| {
+        if !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+    };
+    walk_stmts(&sub.stmts, &mut |stmt| match stmt {
+        Stmt::Node(node) => add(&node.node.id.value),
+        Stmt::Edge(edge) => {
+            for target in edge.targets() {
+                if let EdgeTarget::Node(node) = target {
+                    add(&node.id.value);
+                }
+            }
+        }
+        _ => {}
+    });
+    names
+}
@@ -0,0 +1,84 @@
+//! Source locations for tokens and AST nodes.
+use std::ops::Range;
+
+/// A location in the source text.
+///
+/// `offset` is a byte offset into the source, `line` and `column` are 1-based
+/// (matching the line numbers Graphviz reports in its own errors). Columns are
+/// counted in `char`s, not bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Position {
+    pub offset: usize,
+    pub line: u32,
+    pub column: u32,
+}
+
+impl Position {
+    /// The position of the first character of a document.
+    pub const START: Position = Position {
+        offset: 0,
+        line: 1,
+        column: 1,
+    };
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        Self::START
+    }
+}
+
+/// A half-open range `[start, end)` in the source text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
+impl Span {
+    pub fn new(start: Position, end: Position) -> Self {
+        Self { start, end }
+    }
+
+    /// A zero-width span at `pos`.
+    pub fn empty(pos: Position) -> Self {
+        Self {
+            start: pos,
+            end: pos,
+        }
+    }
+
+    /// A span covering both `self` and `other`.
+    pub fn to(self, other: Span) -> Span {
+        Span {
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+        }
+    }
+
+    /// The byte range of this span, suitable for slicing the source.
+    pub fn range(&self) -> Range<usize> {
+        self.start.offset..self.end.offset
+    }
+
+    /// Whether the byte `offset` falls inside this span.
+    ///
+    /// The end is inclusive so that a cursor placed directly after a token
+    /// still counts as "on" it, which is what editors expect.
+    pub fn contains(&self, offset: usize) -> bool {
+        self.start.offset <= offset && offset <= self.end.offset
+    }
+
+    /// The 1-based line this span starts on.
+    pub fn line(&self) -> u32 {
+        self.start.line
+    }
+
+    pub fn len(&self) -> usize {
+        self.end.offset - self.start.offset
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
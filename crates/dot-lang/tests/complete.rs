@@ -0,0 +1,75 @@
+//! Checks for context-aware completion. `|` in the sources marks the cursor.
+use dot_lang::complete::{complete, CompletionKind, Completions};
+
+fn at_cursor(src: &str, links: &[String]) -> Option<Completions> {
+    let offset = src.find('|').expect("cursor marker");
+    let src = src.replacen('|', "", 1);
+    complete(&src, offset, links)
+}
+
+fn labels(src: &str) -> Vec<String> {
+    at_cursor(src, &[])
+        .map(|c| c.items.into_iter().map(|i| i.label).collect())
+        .unwrap_or_default()
+}
+
+#[test]
+fn attribute_names_are_scoped_to_the_list() {
+    let node = labels("digraph { a [ran|] }");
+    assert!(node.is_empty(), "{node:?}");
+    let graph = labels("digraph { graph [ran|] }");
+    assert!(graph.contains(&"rankdir".to_string()), "{graph:?}");
+    let edge = labels("digraph { a -> b [arrowh|] }");
+    assert_eq!(edge, vec!["arrowhead"]);
+    let edge_second_list = labels("digraph { a -> b [color=red] [arrowh|] }");
+    assert_eq!(edge_second_list, vec!["arrowhead"]);
+    let node_shape = labels("digraph { node [sha|");
+    assert_eq!(node_shape, vec!["shape", "shapefile"]);
+}
+
+#[test]
+fn values_come_from_the_attribute_table() {
+    let values = labels("digraph { rankdir=|");
+    assert_eq!(values, vec!["TB", "LR", "BT", "RL"]);
+    let shapes = labels("digraph { a [shape=dou|] }");
+    assert_eq!(shapes, vec!["doublecircle", "doubleoctagon"]);
+}
+
+#[test]
+fn values_are_quoted_when_needed() {
+    let found = at_cursor("digraph { charset=|", &[]).unwrap();
+    let utf8 = found.items.iter().find(|i| i.label == "UTF-8").unwrap();
+    assert_eq!(utf8.insert, "\"UTF-8\"");
+
+    let in_string = at_cursor("digraph { charset=\"ut|\" }", &[]).unwrap();
+    assert_eq!(in_string.range, 19..21);
+    assert!(in_string.items.iter().all(|i| !i.insert.contains('"')));
+}
+
+#[test]
+fn completes_declared_node_ids() {
+    let found = labels("digraph {\n  alpha;\n  \"two words\";\n  alpha -> |\n}");
+    assert_eq!(found, vec!["alpha", "two words", "subgraph"]);
+    let found = at_cursor("digraph {\n  \"two words\";\n  a -> tw|\n}", &[]).unwrap();
+    assert_eq!(found.items[0].insert, "\"two words\"");
+}
+
+#[test]
+fn completes_links_from_storage() {
+    let links = vec!["sequence.dot".to_string(), "notes.txt".to_string()];
+    let found = at_cursor("digraph { a [URL=\"/seq|\"] }", &links).unwrap();
+    let items: Vec<_> = found
+        .items
+        .iter()
+        .map(|i| (i.kind, i.insert.as_str()))
+        .collect();
+    assert_eq!(items, vec![(CompletionKind::Link, "/sequence.dot")]);
+    let found = at_cursor("digraph { a [URL=|] }", &links).unwrap();
+    assert_eq!(found.items[0].insert, "\"/sequence.dot\"");
+}
+
+#[test]
+fn nothing_inside_comments() {
+    assert!(at_cursor("digraph { // sha|\n}", &[]).is_none());
+    assert!(at_cursor("digraph { /* sha| */ }", &[]).is_none());
+}
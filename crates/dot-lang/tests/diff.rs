@@ -0,0 +1,102 @@
+//! Checks for the structural diff and its merged preview.
+use dot_lang::diff::{diff, ChangeKind, Diff};
+use dot_lang::source_map::GraphElement;
+
+fn diff_sources(old: &str, new: &str) -> Diff {
+    diff(
+        &dot_lang::parse(old).document,
+        &dot_lang::parse(new).document,
+    )
+}
+
+fn edge(title: &str, index: usize) -> GraphElement {
+    GraphElement::Edge {
+        title: title.to_string(),
+        index,
+    }
+}
+
+#[test]
+fn layout_and_formatting_are_not_changes() {
+    let old = "digraph { a -> b [color=red]; b [shape=box] }";
+    let new = "digraph G {\n  b [shape=\"box\"];\n  // moved\n  a -> b [color=red];\n}";
+    assert!(diff_sources(old, new).is_empty());
+}
+
+#[test]
+fn attribute_changes_are_itemised() {
+    let old =
+        "digraph {\n  rankdir=LR;\n  node [shape=box];\n  a [label=A, color=red];\n  a -> b\n}";
+    let new = "digraph {\n  node [shape=oval];\n  a [label=\"A2\", style=bold];\n  a -> b\n}";
+    assert_eq!(
+        diff_sources(old, new).summary(),
+        "~ graph: node.shape changed from \"box\" to \"oval\"; rankdir unset (was \"LR\")\n\
+         ~ node a: color unset (was \"red\"); label changed from \"A\" to \"A2\"; style set to \"bold\""
+    );
+}
+
+#[test]
+fn repeated_edges_and_clusters_are_matched() {
+    let old = "digraph {\n  a -> b;\n  a -> b [label=2];\n  subgraph cluster_x { label=X; c }\n}";
+    let new = "digraph {\n  a -> b;\n  subgraph cluster_x { label=Y; c }\n}";
+    let diff = diff_sources(old, new);
+    let changes: Vec<_> = diff
+        .changes
+        .iter()
+        .map(|c| (c.element.clone(), c.kind))
+        .collect();
+    assert_eq!(
+        changes,
+        vec![
+            (
+                Some(GraphElement::Cluster("cluster_x".into())),
+                ChangeKind::Changed
+            ),
+            (Some(edge("a->b", 1)), ChangeKind::Removed),
+        ]
+    );
+    assert_eq!(
+        diff.summary(),
+        "~ cluster cluster_x: label changed from \"X\" to \"Y\"\n- edge a->b #2"
+    );
+}
+
+#[test]
+fn preview_puts_removed_elements_back() {
+    let old = "digraph {\n  a -> b [label=\"x y\"];\n  subgraph cluster_old { label=Old; c }\n  c:p:n -> a\n}";
+    let new = "digraph {\n  a -> b;\n  a -> d\n}\n";
+    let diff = diff_sources(old, new);
+    let preview = diff.preview(new);
+
+    assert_eq!(
+        preview.source,
+        "digraph {\n  a -> b;\n  a -> d\n  c:p:n -> a;\n  subgraph cluster_old {\n    label=Old;\n    c;\n  }\n}\n"
+    );
+    assert!(dot_lang::parse(&preview.source).is_ok());
+    assert_eq!(
+        preview.marks,
+        vec![
+            (edge("a->b", 0), ChangeKind::Changed),
+            (GraphElement::Node("d".into()), ChangeKind::Added),
+            (edge("a->d", 0), ChangeKind::Added),
+            (
+                GraphElement::Cluster("cluster_old".into()),
+                ChangeKind::Removed
+            ),
+            (GraphElement::Node("c".into()), ChangeKind::Removed),
+            (edge("c:p:n->a", 0), ChangeKind::Removed),
+        ]
+    );
+}
+
+#[test]
+fn removed_duplicate_edges_are_numbered_after_the_kept_ones() {
+    let old = "graph { a -- b [color=red]; a -- b [color=blue] }";
+    let new = "graph { a -- b [color=red] }";
+    let preview = diff_sources(old, new).preview(new);
+    assert_eq!(
+        preview.source,
+        "graph { a -- b [color=red]\n  a -- b [color=blue];\n}"
+    );
+    assert_eq!(preview.marks, vec![(edge("a--b", 1), ChangeKind::Removed)]);
+}
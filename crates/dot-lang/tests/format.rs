@@ -0,0 +1,197 @@
+//! Golden tests for the formatter over the DOT files bundled with the apps.
+//!
+//! Regenerate the expected output after an intentional formatting change with:
+//!
+//! ```sh
+//! UPDATE_GOLDEN=1 cargo test --package dot-lang --test format
+//! ```
+use dot_lang::ast::{EdgeTarget, Stmt};
+use dot_lang::format::{format, FormatOptions, QuoteStyle, StmtOrder};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const ASSET_DIRS: &[&str] = &[
+    "../../packages/ui/assets/dot",
+    "../../packages/web/assets/dot",
+];
+
+fn assets() -> Vec<PathBuf> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let mut files: Vec<PathBuf> = ASSET_DIRS
+        .iter()
+        .flat_map(|dir| fs::read_dir(root.join(dir)).expect("asset dir exists"))
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "dot"))
+        .collect();
+    files.sort();
+    assert!(!files.is_empty(), "no DOT assets found");
+    files
+}
+
+fn golden_path(asset: &Path) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(asset.file_name().unwrap())
+}
+
+/// The IDs of every statement in document order, ignoring layout and quoting,
+/// so we can check formatting didn't change what the graph means.
+fn summary(src: &str) -> Vec<String> {
+    let parse = dot_lang::parse(src);
+    assert!(parse.is_ok(), "{:?}", parse.errors);
+    let mut out = Vec::new();
+    for graph in &parse.document.graphs {
+        graph.walk(|stmt| {
+            let line = match stmt {
+                Stmt::Node(n) => format!("node {}", n.node.id.value),
+                Stmt::Edge(e) => {
+                    let ends: Vec<String> = e
+                        .targets()
+                        .map(|t| match t {
+                            EdgeTarget::Node(n) => n.id.value.clone(),
+                            EdgeTarget::Subgraph(_) => "{}".to_string(),
+                        })
+                        .collect();
+                    format!("edge {}", ends.join(" "))
+                }
+                Stmt::Attr(a) => format!("attr {}", a.target.keyword()),
+                Stmt::Assign(a) => format!("{}={}", a.key.value, a.value.value),
+                Stmt::Subgraph(s) => format!("subgraph {:?}", s.id.as_ref().map(|id| &id.value)),
+            };
+            out.push(line);
+        });
+    }
+    out
+}
+
+#[test]
+fn bundled_assets_match_golden_output() {
+    let options = FormatOptions::default();
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+
+    for asset in assets() {
+        let src = fs::read_to_string(&asset).unwrap();
+        let formatted = format(&src, &options)
+            .unwrap_or_else(|errs| panic!("{} failed to parse: {errs:?}", asset.display()));
+
+        let golden = golden_path(&asset);
+        if update {
+            fs::write(&golden, &formatted).unwrap();
+            continue;
+        }
+        let expected = fs::read_to_string(&golden)
+            .unwrap_or_else(|_| panic!("missing golden file {}", golden.display()));
+        assert_eq!(
+            formatted,
+            expected,
+            "{} differs from golden",
+            asset.display()
+        );
+    }
+}
+
+/// Each non-default option applied to `tests/golden/options/input.dot`, and
+/// the golden file it should produce.
+fn option_cases() -> Vec<(&'static str, FormatOptions)> {
+    let default = FormatOptions::default;
+    vec![
+        (
+            "no_semicolons.dot",
+            FormatOptions {
+                semicolons: false,
+                ..default()
+            },
+        ),
+        (
+            "always_values.dot",
+            FormatOptions {
+                quote_style: QuoteStyle::AlwaysValues,
+                ..default()
+            },
+        ),
+        (
+            "preserve_quotes.dot",
+            FormatOptions {
+                quote_style: QuoteStyle::Preserve,
+                ..default()
+            },
+        ),
+        (
+            "graph_attrs_first.dot",
+            FormatOptions {
+                stmt_order: StmtOrder::GraphAttrsFirst,
+                indent: "\t".to_string(),
+                ..default()
+            },
+        ),
+    ]
+}
+
+#[test]
+fn options_match_golden_output() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden/options");
+    let src = fs::read_to_string(dir.join("input.dot")).unwrap();
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+
+    for (name, options) in option_cases() {
+        let formatted = format(&src, &options).unwrap();
+        assert_eq!(
+            format(&formatted, &options).unwrap(),
+            formatted,
+            "{name} is not idempotent"
+        );
+        // Statements may move with `stmt_order`, but none are lost
+        let sorted = |s: &str| {
+            let mut summary = summary(s);
+            summary.sort();
+            summary
+        };
+        assert_eq!(sorted(&src), sorted(&formatted), "{name}");
+
+        let golden = dir.join(name);
+        if update {
+            fs::write(&golden, &formatted).unwrap();
+            continue;
+        }
+        let expected = fs::read_to_string(&golden)
+            .unwrap_or_else(|_| panic!("missing golden file {}", golden.display()));
+        assert_eq!(formatted, expected, "{name} differs from golden");
+    }
+}
+
+#[test]
+fn formatting_is_idempotent() {
+    let options = FormatOptions::default();
+    for asset in assets() {
+        let src = fs::read_to_string(&asset).unwrap();
+        let once = format(&src, &options).unwrap();
+        let twice = format(&once, &options).unwrap();
+        assert_eq!(once, twice, "{} is not idempotent", asset.display());
+    }
+}
+
+#[test]
+fn formatting_preserves_statements_and_comments() {
+    let options = FormatOptions::default();
+    for asset in assets() {
+        let src = fs::read_to_string(&asset).unwrap();
+        let formatted = format(&src, &options).unwrap();
+        assert_eq!(summary(&src), summary(&formatted), "{}", asset.display());
+
+        let comments = |s: &str| -> Vec<String> {
+            dot_lang::parse(s)
+                .document
+                .comments
+                .into_iter()
+                .map(|c| c.text.trim_end().to_string())
+                .collect()
+        };
+        assert_eq!(comments(&src), comments(&formatted), "{}", asset.display());
+    }
+}
+
+#[test]
+fn refuses_to_format_invalid_source() {
+    let errors = format("digraph { a -> }", &FormatOptions::default()).unwrap_err();
+    assert_eq!(errors.len(), 1);
+}
@@ -0,0 +1,61 @@
+//! Checks for the syntax highlighter.
+use dot_lang::highlight::{highlight_lines, HighlightKind};
+use std::fs;
+use std::path::Path;
+
+#[test]
+fn lines_reconstruct_the_source() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../packages/ui/assets/dot");
+    for entry in fs::read_dir(dir).expect("asset dir exists") {
+        let src = fs::read_to_string(entry.unwrap().path()).unwrap();
+        let lines = highlight_lines(&src);
+        assert_eq!(lines.len(), src.split('\n').count());
+        let rebuilt: Vec<String> = lines
+            .iter()
+            .map(|line| line.iter().map(|s| s.text).collect())
+            .collect();
+        assert_eq!(rebuilt.join("\n"), src);
+        for segment in lines.iter().flatten() {
+            assert_eq!(&src[segment.offset..][..segment.text.len()], segment.text);
+        }
+    }
+}
+
+#[test]
+fn classifies_tokens() {
+    let src = "digraph {\n  a -> b [shape=box, label=\"x\", width=2]; /* c\n d */\n}";
+    let lines = highlight_lines(src);
+    let kinds = |line: usize| -> Vec<(HighlightKind, &str)> {
+        lines[line]
+            .iter()
+            .filter(|s| s.kind != HighlightKind::Plain)
+            .map(|s| (s.kind, s.text))
+            .collect()
+    };
+    use HighlightKind::*;
+    assert_eq!(kinds(0), vec![(Keyword, "digraph"), (Punctuation, "{")]);
+    assert_eq!(
+        kinds(1),
+        vec![
+            (Id, "a"),
+            (EdgeOp, "->"),
+            (Id, "b"),
+            (Punctuation, "["),
+            (AttrName, "shape"),
+            (Punctuation, "="),
+            (AttrValue, "box"),
+            (Punctuation, ","),
+            (AttrName, "label"),
+            (Punctuation, "="),
+            (String, "\"x\""),
+            (Punctuation, ","),
+            (AttrName, "width"),
+            (Punctuation, "="),
+            (Number, "2"),
+            (Punctuation, "]"),
+            (Punctuation, ";"),
+            (Comment, "/* c"),
+        ]
+    );
+    assert_eq!(kinds(2), vec![(Comment, " d */")]);
+}
@@ -0,0 +1,112 @@
+//! Checks for the tokenizer.
+use dot_lang::lexer::{tokenize, TokenKind};
+
+fn kinds(src: &str) -> Vec<(TokenKind, &str)> {
+    let (tokens, errors) = tokenize(src);
+    assert!(errors.is_empty(), "{errors:?}");
+    tokens.iter().map(|t| (t.kind, t.text(src))).collect()
+}
+
+#[test]
+fn keywords_are_case_insensitive() {
+    assert_eq!(
+        kinds("STRICT DiGraph node Edge subGRAPH nodes"),
+        [
+            (TokenKind::Strict, "STRICT"),
+            (TokenKind::Digraph, "DiGraph"),
+            (TokenKind::Node, "node"),
+            (TokenKind::Edge, "Edge"),
+            (TokenKind::Subgraph, "subGRAPH"),
+            (TokenKind::Ident, "nodes"),
+        ]
+    );
+}
+
+#[test]
+fn numerals_and_edge_operators() {
+    assert_eq!(
+        kinds("a->-1.5 -- .5 -> b2"),
+        [
+            (TokenKind::Ident, "a"),
+            (TokenKind::DirectedEdge, "->"),
+            (TokenKind::Numeral, "-1.5"),
+            (TokenKind::UndirectedEdge, "--"),
+            (TokenKind::Numeral, ".5"),
+            (TokenKind::DirectedEdge, "->"),
+            (TokenKind::Ident, "b2"),
+        ]
+    );
+}
+
+#[test]
+fn strings_keep_escaped_quotes_and_html_nests() {
+    assert_eq!(
+        kinds(r#""a \"b\" c" <x <y>z</y>>"#),
+        [
+            (TokenKind::QuotedString, r#""a \"b\" c""#),
+            (TokenKind::Html, "<x <y>z</y>>"),
+        ]
+    );
+}
+
+#[test]
+fn comments_are_trivia() {
+    let tokens = kinds("# at line start\na // line\n/* block\n */ b");
+    assert_eq!(
+        tokens[..5],
+        [
+            (TokenKind::Preprocessor, "# at line start"),
+            (TokenKind::Ident, "a"),
+            (TokenKind::LineComment, "// line"),
+            (TokenKind::BlockComment, "/* block\n */"),
+            (TokenKind::Ident, "b"),
+        ]
+    );
+    assert!(tokens.iter().filter(|(k, _)| k.is_trivia()).count() == 3);
+
+    // Only at the start of a line
+    let (tokens, errors) = tokenize("a # b");
+    assert_eq!(tokens[1].kind, TokenKind::Unknown);
+    assert_eq!(errors.len(), 1);
+}
+
+#[test]
+fn positions_count_lines_and_chars() {
+    let src = "é\n  ab";
+    let (tokens, _) = tokenize(src);
+    let ab = tokens[1].span;
+    assert_eq!((ab.start.line, ab.start.column, ab.start.offset), (2, 3, 5));
+    assert_eq!((ab.end.line, ab.end.column, ab.end.offset), (2, 5, 7));
+    assert_eq!(tokens[0].span.end.column, 2);
+}
+
+#[test]
+fn unterminated_tokens_run_to_the_end_with_an_error() {
+    for (src, message) in [
+        ("a \"open", "unterminated string"),
+        ("a <b <c>", "unterminated HTML string"),
+        ("a /* open", "unterminated block comment"),
+    ] {
+        let (tokens, errors) = tokenize(src);
+        assert_eq!(tokens.len(), 2, "{src}");
+        assert_eq!(tokens[1].span.end.offset, src.len(), "{src}");
+        assert_eq!(errors.len(), 1, "{src}");
+        assert_eq!(errors[0].message, message);
+        assert_eq!(errors[0].span.start.column, 3);
+    }
+}
+
+#[test]
+fn stray_characters_become_unknown_tokens() {
+    let (tokens, errors) = tokenize("a @ b");
+    let kinds: Vec<_> = tokens.iter().map(|t| t.kind).collect();
+    assert_eq!(
+        kinds,
+        [TokenKind::Ident, TokenKind::Unknown, TokenKind::Ident]
+    );
+    assert_eq!(errors[0].message, "unexpected character '@'");
+    assert_eq!(
+        errors[0].to_string(),
+        "unexpected character '@' (line 1, column 3)"
+    );
+}
@@ -0,0 +1,76 @@
+//! Checks for the pre-render linter.
+use dot_lang::lint::{lint_source, LintOptions, Severity};
+
+fn messages(src: &str) -> Vec<(u32, Severity, String)> {
+    lint_source(src, &LintOptions::default())
+        .into_iter()
+        .map(|d| (d.line(), d.severity, d.message))
+        .collect()
+}
+
+#[test]
+fn clean_graph_has_no_diagnostics() {
+    let src = r#"digraph {
+        compound=true;
+        subgraph cluster_a { a; }
+        node [shape=box, style="filled,rounded"];
+        a -> b [lhead=cluster_a, arrowhead=onormal];
+    }"#;
+    assert_eq!(messages(src), vec![]);
+}
+
+#[test]
+fn reports_unknown_attributes_and_values() {
+    let src = "digraph {\n  a [shape=boxx];\n  b [colour=red];\n}";
+    let found = messages(src);
+    assert_eq!(found.len(), 2, "{found:?}");
+    assert_eq!(found[0].0, 2);
+    assert!(found[0].2.contains("did you mean 'box'"), "{found:?}");
+    assert_eq!(found[1].0, 3);
+    assert!(found[1].2.contains("did you mean 'color'"), "{found:?}");
+}
+
+#[test]
+fn reports_wrong_edge_operator() {
+    let found = messages("graph {\n  a -> b;\n}");
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].1, Severity::Error);
+    assert_eq!(found[0].0, 2);
+}
+
+#[test]
+fn reports_conflicting_node_definitions() {
+    let found = messages("digraph {\n  a [color=red];\n  a [color=red];\n  a [color=blue];\n}");
+    assert_eq!(found.len(), 1, "{found:?}");
+    assert_eq!(found[0].0, 4);
+}
+
+#[test]
+fn reports_undefined_subgraph_references() {
+    let src = "digraph {\n  subgraph cluster_x;\n  a -> b [lhead=cluster_y];\n}";
+    let found = messages(src);
+    assert_eq!(found.len(), 2, "{found:?}");
+    assert!(found[0].2.contains("cluster_x"));
+    assert!(found[1].2.contains("cluster_y"));
+}
+
+#[test]
+fn reports_missing_link_targets() {
+    let exists = |path: &str| path == "/here.dot";
+    let options = LintOptions {
+        link_exists: Some(&exists),
+    };
+    let src = r##"digraph {
+        a [URL="/here.dot"];
+        b [URL="/gone.dot"];
+        c [URL="https://graphviz.org"];
+        d [URL="#missing"];
+        e [id=present];
+        f [URL="#present"];
+    }"##;
+    let found: Vec<u32> = lint_source(src, &options)
+        .iter()
+        .map(|d| d.line())
+        .collect();
+    assert_eq!(found, vec![3, 5]);
+}
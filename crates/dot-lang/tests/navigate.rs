@@ -0,0 +1,62 @@
+//! Checks for hover and go-to-definition. `|` in the sources marks the cursor.
+use dot_lang::navigate::{definition, hover, Definition};
+
+fn at_cursor(src: &str) -> (dot_lang::ast::Document, usize) {
+    let offset = src.find('|').expect("cursor marker");
+    let src = src.replacen('|', "", 1);
+    (dot_lang::parse(&src).document, offset)
+}
+
+#[test]
+fn hovering_an_attribute_shows_its_documentation() {
+    let (doc, offset) = at_cursor("digraph { a [sh|ape=box] }");
+    let info = hover(&doc, offset).unwrap();
+    assert_eq!(info.title, "shape");
+    assert!(info.body.iter().any(|p| p.contains("Mrecord")), "{info:?}");
+    assert_eq!(
+        info.url.as_deref(),
+        Some("https://graphviz.org/docs/attrs/shape/")
+    );
+
+    let (doc, offset) = at_cursor("digraph { rankdir=L|R }");
+    assert_eq!(hover(&doc, offset).unwrap().title, "rankdir=LR");
+}
+
+#[test]
+fn hovering_a_node_shows_where_it_is_declared() {
+    let (doc, offset) = at_cursor("digraph {\n  a -> b;\n  b [label=\"B\"];\n  |a -> b;\n}");
+    let info = hover(&doc, offset).unwrap();
+    assert_eq!(info.title, "node a");
+    assert_eq!(info.body[0], "Declared on line 2.");
+
+    let (doc, offset) = at_cursor("digraph {\n  a -> b;\n  b [label=\"B\"];\n  a -> |b;\n}");
+    let info = hover(&doc, offset).unwrap();
+    assert_eq!(info.body, vec!["Declared on line 3.", "label=B"]);
+}
+
+#[test]
+fn jumps_to_the_first_declaration() {
+    let src = "digraph {\n  a -> b;\n  b [label=\"B\"];\n  a -> |b;\n}";
+    let (doc, offset) = at_cursor(src);
+    let Some(Definition::Span(span)) = definition(&doc, offset) else {
+        panic!("no definition");
+    };
+    assert_eq!((span.line(), span.start.column), (3, 3));
+}
+
+#[test]
+fn follows_links_and_cluster_references() {
+    let (doc, offset) = at_cursor("digraph { a [URL=\"/oth|er.dot\"] }");
+    assert_eq!(
+        definition(&doc, offset),
+        Some(Definition::Link("/other.dot".to_string()))
+    );
+
+    let src =
+        "digraph {\n  compound=true;\n  subgraph cluster_x { x; }\n  a -> x [lhead=clus|ter_x];\n}";
+    let (doc, offset) = at_cursor(src);
+    let Some(Definition::Span(span)) = definition(&doc, offset) else {
+        panic!("no definition");
+    };
+    assert_eq!(span.line(), 3);
+}
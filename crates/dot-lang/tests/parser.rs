@@ -0,0 +1,240 @@
+//! Checks for the parser: the tree it builds, and how it recovers from errors.
+use dot_lang::ast::{EdgeTarget, GraphKind, IdKind, Stmt};
+use dot_lang::parse;
+
+fn errors(src: &str) -> Vec<(u32, u32, String)> {
+    parse(src)
+        .errors
+        .into_iter()
+        .map(|e| (e.span.start.line, e.span.start.column, e.message))
+        .collect()
+}
+
+/// The IDs of the node statements in the first graph, in order.
+fn node_ids(src: &str) -> Vec<String> {
+    let parse = parse(src);
+    let mut ids = Vec::new();
+    parse.document.graphs[0].walk(|stmt| {
+        if let Stmt::Node(node) = stmt {
+            ids.push(node.node.id.value.clone());
+        }
+    });
+    ids
+}
+
+#[test]
+fn parses_graphs_statements_and_attributes() {
+    let parse = parse("strict digraph G {\n  rankdir=LR\n  node [shape=box]\n  a -> b -> c [color=red, weight=2]\n}");
+    assert!(parse.is_ok(), "{:?}", parse.errors);
+    let graph = &parse.document.graphs[0];
+    assert!(graph.strict);
+    assert_eq!(graph.kind, GraphKind::Digraph);
+    assert_eq!(graph.id.as_ref().unwrap().value, "G");
+    assert_eq!(graph.stmts.len(), 3);
+
+    let Stmt::Assign(rankdir) = &graph.stmts[0] else {
+        panic!("{:?}", graph.stmts[0]);
+    };
+    assert_eq!(
+        (rankdir.key.as_str(), rankdir.value.as_str()),
+        ("rankdir", "LR")
+    );
+
+    let Stmt::Edge(edge) = &graph.stmts[2] else {
+        panic!("{:?}", graph.stmts[2]);
+    };
+    assert_eq!(edge.targets().count(), 3);
+    let attrs: Vec<_> = edge.attrs[0]
+        .attrs
+        .iter()
+        .map(|a| (a.key.as_str(), a.value.kind))
+        .collect();
+    assert_eq!(
+        attrs,
+        [("color", IdKind::Ident), ("weight", IdKind::Numeral)]
+    );
+}
+
+#[test]
+fn quoted_strings_are_unquoted_and_concatenated() {
+    let parse = parse(r#"graph { a [label="say \"hi\"" + " there"] }"#);
+    assert!(parse.is_ok(), "{:?}", parse.errors);
+    let Stmt::Node(node) = &parse.document.graphs[0].stmts[0] else {
+        panic!();
+    };
+    let label = &node.attrs[0].attrs[0].value;
+    assert_eq!(label.kind, IdKind::Quoted);
+    assert_eq!(label.value, r#"say "hi" there"#);
+}
+
+#[test]
+fn html_labels_keep_their_nested_markup() {
+    let src = "digraph { a [label=<<b>bold</b> <i>and</i> plain>] }";
+    let parse = parse(src);
+    assert!(parse.is_ok(), "{:?}", parse.errors);
+    let Stmt::Node(node) = &parse.document.graphs[0].stmts[0] else {
+        panic!();
+    };
+    let label = &node.attrs[0].attrs[0].value;
+    assert!(label.is_html());
+    assert_eq!(label.value, "<b>bold</b> <i>and</i> plain");
+    assert_eq!(&src[label.span.range()], "<<b>bold</b> <i>and</i> plain>");
+}
+
+#[test]
+fn ports_and_compass_points() {
+    let parse = parse("digraph { a:out:se -> b:in; c:n }");
+    assert!(parse.is_ok(), "{:?}", parse.errors);
+    let stmts = &parse.document.graphs[0].stmts;
+
+    let Stmt::Edge(edge) = &stmts[0] else {
+        panic!("{:?}", stmts[0]);
+    };
+    let ports: Vec<_> = edge
+        .targets()
+        .map(|target| match target {
+            EdgeTarget::Node(node) => {
+                let port = node.port.as_ref().unwrap();
+                (
+                    port.name.value.clone(),
+                    port.compass.as_ref().map(|c| c.value.clone()),
+                )
+            }
+            EdgeTarget::Subgraph(_) => panic!("expected a node"),
+        })
+        .collect();
+    assert_eq!(
+        ports,
+        [
+            ("out".to_string(), Some("se".to_string())),
+            ("in".to_string(), None)
+        ]
+    );
+
+    let Stmt::Node(node) = &stmts[1] else {
+        panic!("{:?}", stmts[1]);
+    };
+    assert_eq!(node.node.id.value, "c");
+    assert_eq!(node.node.port.as_ref().unwrap().name.value, "n");
+}
+
+#[test]
+fn subgraphs_nest_and_can_be_edge_targets() {
+    let parse = parse("digraph { subgraph cluster_x { a; subgraph { b } } a -> { c d } }");
+    assert!(parse.is_ok(), "{:?}", parse.errors);
+    let stmts = &parse.document.graphs[0].stmts;
+    let Stmt::Subgraph(cluster) = &stmts[0] else {
+        panic!("{:?}", stmts[0]);
+    };
+    assert!(cluster.is_cluster());
+    assert_eq!(cluster.stmts.len(), 2);
+    let Stmt::Edge(edge) = &stmts[1] else {
+        panic!("{:?}", stmts[1]);
+    };
+    assert!(matches!(&edge.rest[0].target, EdgeTarget::Subgraph(s) if s.stmts.len() == 2));
+    assert_eq!(
+        node_ids("digraph { subgraph cluster_x { a; subgraph { b } } a -> { c d } }"),
+        ["a", "b", "c", "d"]
+    );
+}
+
+#[test]
+fn spans_have_one_based_lines_and_char_columns() {
+    let src = "digraph {\n  \"é\" -> b\n}";
+    let parse = parse(src);
+    let Stmt::Edge(edge) = &parse.document.graphs[0].stmts[0] else {
+        panic!();
+    };
+    let head = edge.head.span();
+    assert_eq!((head.start.line, head.start.column), (2, 3));
+    assert_eq!((head.end.line, head.end.column), (2, 6));
+    assert_eq!(&src[head.range()], "\"é\"");
+
+    let tail = edge.rest[0].target.span();
+    // The quoted `é` is three chars but four bytes
+    assert_eq!((tail.start.line, tail.start.column), (2, 10));
+    assert_eq!(tail.start.offset, 20);
+
+    let end = parse.document.span.end;
+    assert_eq!((end.line, end.column, end.offset), (3, 2, src.len()));
+}
+
+#[test]
+fn recovers_at_the_next_statement_after_an_error() {
+    let src = "digraph {\n  a -> ;\n  b -> c\n  d [color=]\n  e\n}";
+    assert_eq!(
+        errors(src),
+        [
+            (2, 8, "expected a node or subgraph after '->'".to_string()),
+            (4, 12, "expected a value for 'color'".to_string()),
+        ]
+    );
+    let parse = parse(src);
+    assert_eq!(parse.document.graphs[0].stmts.len(), 2);
+    assert_eq!(node_ids(src), ["e"]);
+}
+
+#[test]
+fn a_forgotten_bracket_ends_the_list_at_the_line_break() {
+    let src = "digraph {\n  a [label=\"A\"\n  b -> c\n}";
+    assert_eq!(errors(src), [(2, 15, "expected ']'".to_string())]);
+    let parse = parse(src);
+    let stmts = &parse.document.graphs[0].stmts;
+    assert_eq!(stmts.len(), 2);
+    assert!(matches!(&stmts[1], Stmt::Edge(_)));
+}
+
+#[test]
+fn a_missing_equals_sign_is_reported_after_the_name() {
+    let src = "digraph {\n  a [color red]\n  b\n}";
+    assert_eq!(
+        errors(src),
+        [(2, 11, "expected '=' after attribute name".to_string())]
+    );
+    assert_eq!(node_ids(src), ["b"]);
+}
+
+#[test]
+fn a_missing_closing_brace_is_reported_at_the_end() {
+    let src = "digraph {\n  a -> b\n";
+    assert_eq!(errors(src), [(3, 1, "expected '}'".to_string())]);
+    assert_eq!(parse(src).document.graphs[0].stmts.len(), 1);
+}
+
+#[test]
+fn garbage_before_a_graph_is_skipped() {
+    let parse = parse("oops digraph { a }");
+    assert_eq!(parse.errors.len(), 1);
+    assert_eq!(parse.errors[0].message, "expected 'graph' or 'digraph'");
+    assert_eq!(parse.document.graphs.len(), 1);
+}
+
+#[test]
+fn lexer_errors_are_merged_in_source_order() {
+    assert_eq!(
+        errors("digraph {\n  a -> b\n  c @ d\n  e [label=\"open\n}"),
+        [
+            (3, 5, "unexpected character '@'".to_string()),
+            (4, 12, "unterminated string".to_string()),
+            (5, 2, "expected ']'".to_string()),
+            (5, 2, "expected '}'".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn comments_are_kept_on_the_side() {
+    let parse = parse("# pragma\ndigraph {\n  // line\n  a /* block */ -> b\n}");
+    assert!(parse.is_ok(), "{:?}", parse.errors);
+    let comments: Vec<_> = parse
+        .document
+        .comments
+        .iter()
+        .map(|c| (c.span.start.line, c.text.as_str()))
+        .collect();
+    assert_eq!(
+        comments,
+        [(1, "# pragma"), (3, "// line"), (4, "/* block */")]
+    );
+    assert_eq!(parse.document.graphs[0].stmts.len(), 1);
+}
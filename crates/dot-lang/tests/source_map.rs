@@ -0,0 +1,56 @@
+//! Checks for the statement <-> SVG element mapping. `|` marks the cursor.
+use dot_lang::source_map::{GraphElement, SourceMap};
+
+fn at_cursor(src: &str) -> (SourceMap, usize) {
+    let offset = src.find('|').expect("cursor marker");
+    let src = src.replacen('|', "", 1);
+    (SourceMap::new(&dot_lang::parse(&src).document), offset)
+}
+
+fn edge(title: &str, index: usize) -> GraphElement {
+    GraphElement::Edge {
+        title: title.to_string(),
+        index,
+    }
+}
+
+#[test]
+fn cursor_selects_the_innermost_element() {
+    let (map, offset) = at_cursor("digraph {\n  a -> b;\n  |b [label=\"B\"];\n}");
+    assert_eq!(
+        map.elements_at(offset),
+        vec![GraphElement::Node("b".into())]
+    );
+
+    let (map, offset) = at_cursor("digraph {\n  a -> b -|> c [color=red];\n}");
+    assert_eq!(
+        map.elements_at(offset),
+        vec![edge("a->b", 0), edge("b->c", 0)]
+    );
+
+    let (map, offset) = at_cursor("graph { subgraph cluster_x {\n  label=X|\n  a -- { b c }\n} }");
+    assert_eq!(
+        map.elements_at(offset),
+        vec![GraphElement::Cluster("cluster_x".into())]
+    );
+
+    let (map, offset) = at_cursor("graph { a -- { b c } [style=|dashed] }");
+    assert_eq!(
+        map.elements_at(offset),
+        vec![edge("a--b", 0), edge("a--c", 0)]
+    );
+}
+
+#[test]
+fn elements_map_back_to_their_definitions() {
+    let src = "digraph {\n  a -> b;\n  a -> b [label=2];\n  b [shape=box];\n  c:p:n -> a\n}";
+    let map = SourceMap::new(&dot_lang::parse(src).document);
+    let line = |element: &GraphElement| map.span_of(element).map(|s| s.line());
+
+    assert_eq!(line(&GraphElement::Node("a".into())), Some(2));
+    assert_eq!(line(&GraphElement::Node("b".into())), Some(4));
+    assert_eq!(line(&edge("a->b", 0)), Some(2));
+    assert_eq!(line(&edge("a->b", 1)), Some(3));
+    assert_eq!(line(&edge("c:p:n->a", 0)), Some(5));
+    assert_eq!(line(&GraphElement::Cluster("cluster_x".into())), None);
+}
@@ -5,6 +5,7 @@ pub mod graphvism_wrapper;
 pub mod storage;
 
 use dioxus::prelude::*;
+use dot_repl_ui::hooks::use_theme_provider;
 use dot_repl_ui::{GVizProvider, StorageProvider};
 use graphvizm::Graphvizm;
 
@@ -13,6 +14,9 @@ pub fn DesktopApp(path: String, children: Element) -> Element {
     let storage = storage::GitStorage::new(path).unwrap();
     let storage_provider = StorageProvider::new(storage.clone());
 
+    // The graph theme, remembered in storage across sessions
+    use_theme_provider(storage_provider.clone());
+
     // provide storage in context for all child elements
     use_context_provider(|| storage_provider);
 
@@ -1,6 +1,7 @@
 //! A desktop application built with Dioxus that features routing and a navbar.
 use dioxus::prelude::*;
 use dot_repl_desktop::DesktopApp;
+use dot_repl_ui::components::{FileBrowser, ThemePicker};
 use dot_repl_ui::Navbar;
 use views::{Blog, GraphVizDesktopView, Home};
 mod views;
@@ -48,6 +49,7 @@ fn App() -> Element {
 fn DesktopNavbar() -> Element {
     let navigator = use_navigator();
     let route = use_route::<Route>();
+    let browser_route = route.clone();
     let mut rough_enabled = use_context::<Signal<bool>>();
 
     rsx! {
@@ -81,6 +83,7 @@ fn DesktopNavbar() -> Element {
                 }
                 "Rough Style"
             }
+            ThemePicker {}
             if let Route::GraphVizDesktopView { key_path } = route {
                 div {
                     class: "m-4 font-mono text-xs text-gray-500",
@@ -89,6 +92,13 @@ fn DesktopNavbar() -> Element {
             }
         }
 
-        Outlet::<Route> {}
+        div {
+            class: "flex flex-1 min-h-0",
+            FileBrowser { route: browser_route }
+            div {
+                class: "flex-1 min-w-0 overflow-auto",
+                Outlet::<Route> {}
+            }
+        }
     }
 }
@@ -1,8 +1,10 @@
 //! Native storage
 use crate::Error;
 use directories::ProjectDirs;
-use dot_repl_ui::PlatformStorage;
-use std::path::PathBuf;
+use dot_repl_ui::{content_hash, Metadata, PlatformStorage, StorageError};
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+use std::time::UNIX_EPOCH;
 
 #[derive(Clone, Default)]
 pub struct DesktopStorage {
@@ -30,24 +32,36 @@ impl GitStorage {
 }
 
 impl PlatformStorage for GitStorage {
-    fn save(&self, key: &str, data: &[u8]) -> Result<(), String> {
-        let path = self.repo_dir.join(key);
-        std::fs::write(path, data).map_err(|err| format!("Failed to save data: {:?}", err))
+    fn save(&self, key: &str, data: &[u8]) -> Result<(), StorageError> {
+        ignore_hidden_folder(&self.repo_dir, key)?;
+        write_file(&self.repo_dir, key, data)
     }
 
-    fn load(&self, key: &str) -> Result<Vec<u8>, String> {
-        let path = self.repo_dir.join(key);
-        std::fs::read(path).map_err(|err| format!("Failed to load data: {:?}", err))
+    fn load(&self, key: &str) -> Result<Vec<u8>, StorageError> {
+        let path = key_path(&self.repo_dir, key)?;
+        std::fs::read(path).map_err(|err| storage_error(key, err))
     }
 
-    fn delete(&self, key: &str) -> Result<(), String> {
-        let path = self.repo_dir.join(key);
-        std::fs::remove_file(path).map_err(|err| format!("Failed to delete data: {:?}", err))
+    fn delete(&self, key: &str) -> Result<(), StorageError> {
+        let path = key_path(&self.repo_dir, key)?;
+        std::fs::remove_file(path).map_err(|err| storage_error(key, err))
     }
 
     fn exists(&self, key: &str) -> bool {
-        let path = self.repo_dir.join(key);
-        path.exists()
+        key_path(&self.repo_dir, key).is_ok_and(|path| path.exists())
+    }
+
+    fn list(&self, prefix: &str) -> Result<Vec<String>, StorageError> {
+        list_files(&self.repo_dir, prefix)
+    }
+
+    fn rename(&self, from: &str, to: &str) -> Result<(), StorageError> {
+        ignore_hidden_folder(&self.repo_dir, to)?;
+        rename_file(&self.repo_dir, from, to)
+    }
+
+    fn metadata(&self, key: &str) -> Result<Metadata, StorageError> {
+        file_metadata(&self.repo_dir, key)
     }
 }
 
@@ -84,23 +98,135 @@ impl DesktopStorage {
 }
 
 impl PlatformStorage for DesktopStorage {
-    fn save(&self, key: &str, data: &[u8]) -> Result<(), String> {
-        let path = self.data_dir.join(key);
-        std::fs::write(path, data).map_err(|err| format!("Failed to save data: {:?}", err))
+    fn save(&self, key: &str, data: &[u8]) -> Result<(), StorageError> {
+        write_file(&self.data_dir, key, data)
     }
 
-    fn load(&self, key: &str) -> Result<Vec<u8>, String> {
-        let path = self.data_dir.join(key);
-        std::fs::read(path).map_err(|err| format!("Failed to load data: {:?}", err))
+    fn load(&self, key: &str) -> Result<Vec<u8>, StorageError> {
+        let path = key_path(&self.data_dir, key)?;
+        std::fs::read(path).map_err(|err| storage_error(key, err))
     }
 
-    fn delete(&self, key: &str) -> Result<(), String> {
-        let path = self.data_dir.join(key);
-        std::fs::remove_file(path).map_err(|err| format!("Failed to delete data: {:?}", err))
+    fn delete(&self, key: &str) -> Result<(), StorageError> {
+        let path = key_path(&self.data_dir, key)?;
+        std::fs::remove_file(path).map_err(|err| storage_error(key, err))
     }
 
     fn exists(&self, key: &str) -> bool {
-        let path = self.data_dir.join(key);
-        path.exists()
+        key_path(&self.data_dir, key).is_ok_and(|path| path.exists())
+    }
+
+    fn list(&self, prefix: &str) -> Result<Vec<String>, StorageError> {
+        list_files(&self.data_dir, prefix)
+    }
+
+    fn rename(&self, from: &str, to: &str) -> Result<(), StorageError> {
+        rename_file(&self.data_dir, from, to)
+    }
+
+    fn metadata(&self, key: &str) -> Result<Metadata, StorageError> {
+        file_metadata(&self.data_dir, key)
+    }
+}
+
+fn storage_error(key: &str, err: std::io::Error) -> StorageError {
+    match err.kind() {
+        ErrorKind::NotFound => StorageError::NotFound(key.to_string()),
+        ErrorKind::AlreadyExists => StorageError::AlreadyExists(key.to_string()),
+        ErrorKind::StorageFull | ErrorKind::QuotaExceeded => StorageError::QuotaExceeded,
+        _ => StorageError::Backend(format!("{key}: {err}")),
+    }
+}
+
+/// The path of the file for `key` in `dir`. Keys are relative paths that
+/// stay inside `dir`, so absolute paths and `..` are rejected.
+fn key_path(dir: &Path, key: &str) -> Result<PathBuf, StorageError> {
+    let relative = Path::new(key);
+    let mut components = relative.components().peekable();
+    let valid = components.peek().is_some()
+        && components.all(|component| matches!(component, Component::Normal(_)));
+    if !valid {
+        return Err(StorageError::InvalidKey(key.to_string()));
     }
+    Ok(dir.join(relative))
+}
+
+/// Write `data` to the file for `key` in `dir`, creating the folders of
+/// `/`-separated keys.
+fn write_file(dir: &Path, key: &str, data: &[u8]) -> Result<(), StorageError> {
+    let path = key_path(dir, key)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|err| storage_error(key, err))?;
+    }
+    std::fs::write(path, data).map_err(|err| storage_error(key, err))
+}
+
+/// Keep the hidden top-level folder of `key`, if it is in one, out of the git
+/// repository at `dir`. The app keeps settings and history there, not
+/// documents.
+fn ignore_hidden_folder(dir: &Path, key: &str) -> Result<(), StorageError> {
+    key_path(dir, key)?;
+    let Some((folder, _)) = key.split_once('/') else {
+        return Ok(());
+    };
+    if !folder.starts_with('.') || dir.join(folder).join(".gitignore").exists() {
+        return Ok(());
+    }
+    write_file(dir, &format!("{folder}/.gitignore"), b"*\n")
+}
+
+/// Keys of the regular files under `dir` starting with `prefix`, sorted. Files
+/// in folders are keyed by their `/`-separated path; hidden folders are
+/// skipped.
+fn list_files(dir: &Path, prefix: &str) -> Result<Vec<String>, StorageError> {
+    let mut names = Vec::new();
+    collect_files(dir, "", &mut names).map_err(|err| storage_error(prefix, err))?;
+    names.retain(|name| name.starts_with(prefix));
+    names.sort();
+    Ok(names)
+}
+
+fn collect_files(dir: &Path, base: &str, names: &mut Vec<String>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)?.filter_map(|entry| entry.ok()) {
+        let Ok(name) = entry.file_name().into_string() else {
+            continue;
+        };
+        let key = format!("{base}{name}");
+        match entry.file_type() {
+            Ok(t) if t.is_dir() && !name.starts_with('.') => {
+                collect_files(&entry.path(), &format!("{key}/"), names)?
+            }
+            Ok(t) if t.is_file() => names.push(key),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn rename_file(dir: &Path, from: &str, to: &str) -> Result<(), StorageError> {
+    let source = key_path(dir, from)?;
+    let target = key_path(dir, to)?;
+    if target.exists() {
+        return Err(StorageError::AlreadyExists(to.to_string()));
+    }
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent).map_err(|err| storage_error(to, err))?;
+    }
+    std::fs::rename(source, target).map_err(|err| storage_error(from, err))
+}
+
+fn file_metadata(dir: &Path, key: &str) -> Result<Metadata, StorageError> {
+    let path = key_path(dir, key)?;
+    let metadata = std::fs::metadata(&path).map_err(|err| storage_error(key, err))?;
+    let data = std::fs::read(&path).map_err(|err| storage_error(key, err))?;
+    let modified_ms = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|elapsed| elapsed.as_millis() as u64);
+    Ok(Metadata {
+        size: metadata.len(),
+        modified_ms,
+        hash: content_hash(&data),
+    })
 }
@@ -1,79 +1,504 @@
-//! A code editor component with line highlighting support
+//! A DOT code editor with syntax highlighting, line numbers and error markers
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::error::{ErrorInfo, ErrorLevel};
+use crate::hooks::use_code_completion;
+use crate::platform;
 use dioxus::prelude::*;
+use dot_lang::complete::CompletionKind;
+use dot_lang::highlight::{highlight_lines, HighlightKind};
+use dot_lang::navigate::{definition, hover, Definition, Hover};
+use dot_lang::span::Span;
+
+/// Gives every editor on the page its own textarea id, for caret lookups.
+static NEXT_EDITOR_ID: AtomicUsize = AtomicUsize::new(0);
 
-/// A code editor that supports highlighting specific lines
-/// Uses CSS background-image with linear-gradient for line highlighting
+/// A code editor that highlights DOT syntax and marks lines with problems.
+///
+/// The text is edited in a transparent `textarea` laid over a highlighted copy
+/// of the same text. Both layers share font, padding and wrapping, and each line
+/// of the highlighted copy is its own row with its gutter cell, so line numbers
+/// and markers stay aligned with the text even when long lines wrap.
+///
+/// Completion suggestions open while typing an identifier, after `=` and inside
+/// quotes, or on Ctrl+Space. Arrow keys move through them, Enter or Tab accepts
+/// and Escape closes the list.
+///
+/// Resting the mouse on an attribute or node ID shows its documentation.
+/// Ctrl-clicking (Cmd-clicking on macOS) a node ID jumps to its declaration,
+/// and Ctrl-clicking a `URL` value hands the target to `onopenlink`.
+///
+/// `oncursor` receives the byte offset of the caret whenever it moves, and
+/// the lines of `highlight` are shaded and scrolled into view.
 #[component]
 pub fn CodeEditor(
     value: String,
     oninput: EventHandler<String>,
     error_lines: Vec<u32>,
     placeholder: String,
+    /// Messages to show on the gutter markers. Lines listed in `error_lines`
+    /// without a message here are still highlighted.
+    #[props(default)]
+    diagnostics: Vec<ErrorInfo>,
+    /// Storage keys offered as completions inside `URL="..."`.
+    #[props(default)]
+    link_targets: Vec<String>,
+    /// Called with the target of a Ctrl-clicked `URL` or `href`.
+    #[props(default)]
+    onopenlink: Option<EventHandler<String>>,
+    /// Called with the byte offset of the caret after it moves.
+    #[props(default)]
+    oncursor: Option<EventHandler<usize>>,
+    /// Source to shade, e.g. the statement for an element selected elsewhere.
+    #[props(default)]
+    highlight: Option<Span>,
 ) -> Element {
-    // Build a CSS background-image that highlights error lines
-    let background_style = if error_lines.is_empty() {
-        "background: white;".to_string()
-    } else {
-        // Calculate how many lines we need (based on content + some buffer)
-        let line_count = value.lines().count().max(20) + 10;
-        let line_height = 1.5; // em
-
-        // Build gradient stops for each line
-        let mut gradient_parts = vec![];
-
-        for line_num in 1..=line_count {
-            let line_u32 = line_num as u32;
-            let start = (line_num - 1) as f32 * line_height;
-            let end = line_num as f32 * line_height;
-
-            if error_lines.contains(&line_u32) {
-                // Highlighted line - yellow with opacity
-                gradient_parts.push(format!("rgba(250, 204, 21, 0.3) {}em", start));
-                gradient_parts.push(format!("rgba(250, 204, 21, 0.3) {}em", end));
+    let lines = highlight_lines(&value);
+    let markers = line_markers(&diagnostics);
+    let editor_id = use_hook(|| NEXT_EDITOR_ID.fetch_add(1, Ordering::Relaxed));
+    let mut completion = use_code_completion();
+    let mut hover_info = use_signal(|| None::<Hover>);
+    // Bumped on every mouse move so only the last pending hover lookup lands
+    let mut hover_generation = use_signal(|| 0u64);
+
+    // Scroll to the highlight only when it changes
+    use_effect(use_reactive((&highlight,), move |(highlight,)| {
+        if let Some(span) = highlight {
+            reveal_line(editor_id, span.line());
+        }
+    }));
+    let highlighted_lines = highlight.map_or(0..=0, |span| span.start.line..=span.end.line);
+
+    // Line, column and entries of the open suggestion list
+    let popup = completion.completions.read().as_ref().map(|c| {
+        let before = value.get(..c.range.start).unwrap_or(&value);
+        let line = before.matches('\n').count();
+        let column = before.rsplit('\n').next().unwrap_or("").chars().count();
+        (line, column, c.items.clone())
+    });
+    let selected = (completion.selected)();
+    let hovered = hover_info
+        .read()
+        .as_ref()
+        .filter(|_| popup.is_none())
+        .map(|h| {
+            let line = h.span.start.line as usize - 1;
+            let column = h.span.start.column as usize - 1;
+            (line, column, h.clone())
+        });
+
+    let links = link_targets.clone();
+    let on_input = move |e: FormEvent| {
+        let text = e.value();
+        oninput.call(text.clone());
+        if hover_info.peek().is_some() {
+            hover_info.set(None);
+        }
+        let links = links.clone();
+        spawn(async move {
+            let Some(offset) = caret(editor_id, &text).await else {
+                return;
+            };
+            if let Some(handler) = oncursor {
+                handler.call(offset);
+            }
+            let typed = text[..offset].chars().next_back();
+            if typed.is_some_and(|c| c.is_alphanumeric() || "_=\"/.".contains(c)) {
+                completion.update(&text, offset, &links);
             } else {
-                // Transparent line
-                gradient_parts.push(format!("transparent {}em", start));
-                gradient_parts.push(format!("transparent {}em", end));
+                completion.close();
+            }
+        });
+    };
+
+    let current = value.clone();
+    let links = link_targets.clone();
+    let on_keydown = move |e: KeyboardEvent| {
+        if e.modifiers().contains(Modifiers::CONTROL) && e.code() == Code::Space {
+            e.prevent_default();
+            let text = current.clone();
+            let links = links.clone();
+            spawn(async move {
+                if let Some(offset) = caret(editor_id, &text).await {
+                    completion.update(&text, offset, &links);
+                }
+            });
+            return;
+        }
+        if !completion.is_open() {
+            return;
+        }
+        match e.key() {
+            Key::ArrowDown => {
+                e.prevent_default();
+                completion.move_selection(1);
+            }
+            Key::ArrowUp => {
+                e.prevent_default();
+                completion.move_selection(-1);
+            }
+            Key::Enter | Key::Tab => {
+                e.prevent_default();
+                if let Some((text, offset)) = completion.accept(&current) {
+                    oninput.call(text.clone());
+                    set_caret(editor_id, &text, offset);
+                }
             }
+            Key::Escape => {
+                e.prevent_default();
+                completion.close();
+            }
+            Key::ArrowLeft | Key::ArrowRight | Key::Home | Key::End => completion.close(),
+            _ => {}
         }
+    };
 
-        format!(
-            "background: linear-gradient(to bottom, {});",
-            gradient_parts.join(", ")
-        )
+    // Drop the hover card shortly after the mouse leaves, unless it moves onto
+    // the card itself.
+    let mut hide_hover = move || {
+        let generation = *hover_generation.peek() + 1;
+        hover_generation.set(generation);
+        spawn(async move {
+            platform::sleep(std::time::Duration::from_millis(300)).await;
+            if *hover_generation.peek() == generation && hover_info.peek().is_some() {
+                hover_info.set(None);
+            }
+        });
+    };
+
+    let current = value.clone();
+    let on_mousemove = move |e: MouseEvent| {
+        let point = e.client_coordinates();
+        let generation = *hover_generation.peek() + 1;
+        hover_generation.set(generation);
+        let text = current.clone();
+        spawn(async move {
+            platform::sleep(std::time::Duration::from_millis(400)).await;
+            if *hover_generation.peek() != generation {
+                return;
+            }
+            let info = match offset_at(editor_id, point.x, point.y).await {
+                Some(offset) => hover(&dot_lang::parse(&text).document, offset),
+                None => None,
+            };
+            if *hover_info.peek() != info {
+                hover_info.set(info);
+            }
+        });
+    };
+
+    // Report caret moves made with the keyboard
+    let current = value.clone();
+    let on_keyup = move |e: KeyboardEvent| {
+        let moved = matches!(
+            e.key(),
+            Key::ArrowUp
+                | Key::ArrowDown
+                | Key::ArrowLeft
+                | Key::ArrowRight
+                | Key::Home
+                | Key::End
+                | Key::PageUp
+                | Key::PageDown
+        );
+        if let Some(handler) = oncursor.filter(|_| moved) {
+            let text = current.clone();
+            spawn(async move {
+                if let Some(offset) = caret(editor_id, &text).await {
+                    handler.call(offset);
+                }
+            });
+        }
+    };
+
+    let current = value.clone();
+    let on_click = move |e: MouseEvent| {
+        completion.close();
+        let modifiers = e.modifiers();
+        if !modifiers.contains(Modifiers::CONTROL) && !modifiers.contains(Modifiers::META) {
+            if let Some(handler) = oncursor {
+                let text = current.clone();
+                spawn(async move {
+                    if let Some(offset) = caret(editor_id, &text).await {
+                        handler.call(offset);
+                    }
+                });
+            }
+            return;
+        }
+        let point = e.client_coordinates();
+        let text = current.clone();
+        spawn(async move {
+            let Some(offset) = offset_at(editor_id, point.x, point.y).await else {
+                return;
+            };
+            match definition(&dot_lang::parse(&text).document, offset) {
+                Some(Definition::Span(span)) => {
+                    set_caret(editor_id, &text, span.start.offset);
+                    reveal_line(editor_id, span.line());
+                }
+                Some(Definition::Link(target)) => {
+                    if let Some(handler) = onopenlink {
+                        handler.call(target);
+                    }
+                }
+                None => {}
+            }
+        });
     };
 
     rsx! {
         div {
-            class: "flex-1 relative",
+            class: "flex-1 min-h-0 relative overflow-auto bg-white",
+            div {
+                class: "relative min-h-full font-mono text-sm leading-6",
+                style: "tab-size: 4;",
 
-            // Error indicators in gutter
-            if !error_lines.is_empty() {
+                // Highlighted copy with the gutter, in normal flow so it sets the height
                 div {
-                    class: "absolute left-0 top-0 bottom-0 w-8 bg-gray-100 border-r border-gray-300 pointer-events-none z-10",
-                    for line_num in error_lines.iter() {
-                        div {
-                            key: "{line_num}",
-                            class: "absolute left-1 w-2 h-2 bg-red-500 rounded-full",
-                            style: "top: calc(({line_num} - 1) * 1.5em + 1rem);",
-                            title: "Error on line {line_num}"
+                    id: "code-editor-{editor_id}-lines",
+                    class: "py-4 select-none",
+                    for (idx, segments) in lines.iter().enumerate() {
+                        {
+                            let line_num = idx as u32 + 1;
+                            let marker = markers.get(&line_num);
+                            let row_class = match marker {
+                                Some((ErrorLevel::Error, _)) => "relative flex bg-red-50",
+                                Some((ErrorLevel::Warning, _)) => "relative flex bg-yellow-50",
+                                Some((ErrorLevel::Info, _)) => "relative flex bg-blue-50",
+                                None if error_lines.contains(&line_num) => "relative flex bg-yellow-100",
+                                None if highlighted_lines.contains(&line_num) => "relative flex bg-sky-100",
+                                None => "relative flex",
+                            };
+                            let suggestions = popup.as_ref().filter(|(line, ..)| *line == idx);
+                            let card = hovered.as_ref().filter(|(line, ..)| *line == idx);
+                            rsx! {
+                                div {
+                                    key: "{idx}",
+                                    id: "code-editor-{editor_id}-line-{line_num}",
+                                    class: row_class,
+                                    div {
+                                        class: "relative w-12 shrink-0 pr-3 text-right text-gray-400 bg-gray-100 border-r border-gray-300",
+                                        aria_hidden: "true",
+                                        if let Some((level, message)) = marker {
+                                            div {
+                                                class: "absolute left-1 top-2 w-2 h-2 rounded-full pointer-events-auto",
+                                                class: marker_class(level),
+                                                title: "{message}",
+                                            }
+                                        } else if error_lines.contains(&line_num) {
+                                            div {
+                                                class: "absolute left-1 top-2 w-2 h-2 rounded-full bg-red-500",
+                                            }
+                                        }
+                                        "{line_num}"
+                                    }
+                                    div {
+                                        class: "flex-1 min-w-0 px-3 whitespace-pre-wrap break-all",
+                                        aria_hidden: "true",
+                                        if segments.is_empty() {
+                                            // Keep empty lines one row tall
+                                            "\u{200b}"
+                                        }
+                                        for (i, segment) in segments.iter().enumerate() {
+                                            span {
+                                                key: "{i}",
+                                                class: token_class(segment.kind),
+                                                "data-offset": "{segment.offset}",
+                                                "{segment.text}"
+                                            }
+                                        }
+                                    }
+                                    if let Some((_, column, info)) = card {
+                                        div {
+                                            class: "absolute top-full z-10 max-w-md p-2 bg-white border border-gray-300 rounded shadow-lg text-xs font-sans select-text",
+                                            style: "left: calc(3.75rem + {column}ch);",
+                                            role: "tooltip",
+                                            onmouseenter: move |_| hover_generation += 1,
+                                            onmouseleave: move |_| hide_hover(),
+                                            div { class: "font-semibold text-gray-900 mb-1", "{info.title}" }
+                                            for (i, paragraph) in info.body.iter().enumerate() {
+                                                p { key: "{i}", class: "text-gray-700 mb-1", "{paragraph}" }
+                                            }
+                                            if let Some(url) = &info.url {
+                                                a {
+                                                    class: "text-sky-700 underline",
+                                                    href: "{url}",
+                                                    target: "_blank",
+                                                    "Graphviz documentation"
+                                                }
+                                            }
+                                        }
+                                    }
+                                    if let Some((_, column, items)) = suggestions {
+                                        ul {
+                                            class: "absolute top-full z-20 max-h-60 w-72 overflow-auto bg-white border border-gray-300 rounded shadow-lg pointer-events-auto text-xs",
+                                            style: "left: calc(3.75rem + {column}ch);",
+                                            role: "listbox",
+                                            for (i, item) in items.iter().enumerate() {
+                                                li {
+                                                    key: "{i}",
+                                                    class: if i == selected { "flex items-baseline gap-2 px-2 py-1 cursor-pointer bg-sky-100" } else { "flex items-baseline gap-2 px-2 py-1 cursor-pointer hover:bg-gray-100" },
+                                                    role: "option",
+                                                    aria_selected: i == selected,
+                                                    title: item.detail.clone().unwrap_or_default(),
+                                                    // mousedown rather than click so the textarea keeps focus
+                                                    onmousedown: {
+                                                        let current = value.clone();
+                                                        move |e: MouseEvent| {
+                                                            e.prevent_default();
+                                                            completion.selected.set(i);
+                                                            if let Some((text, offset)) = completion.accept(&current) {
+                                                                oninput.call(text.clone());
+                                                                set_caret(editor_id, &text, offset);
+                                                            }
+                                                        }
+                                                    },
+                                                    span { class: "w-4 shrink-0 text-gray-400", {kind_badge(item.kind)} }
+                                                    span { class: "text-gray-900", "{item.label}" }
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
                         }
                     }
                 }
-            }
 
-            // Textarea with line highlighting
-            textarea {
-                class: "w-full h-full font-mono text-sm p-4 pl-12 border-none outline-none resize-none overflow-auto",
-                style: "{background_style} line-height: 1.5em; tab-size: 4;",
-                value: "{value}",
-                placeholder: "{placeholder}",
-                spellcheck: false,
-
-                oninput: move |e| {
-                    oninput.call(e.value());
-                },
+                // The editable layer: same metrics as the code column, text transparent
+                textarea {
+                    id: "code-editor-{editor_id}",
+                    class: "absolute top-0 bottom-0 left-12 right-0 py-4 px-3 font-mono text-sm leading-6 whitespace-pre-wrap break-all bg-transparent text-transparent caret-gray-900 placeholder-gray-400 border-none outline-none resize-none overflow-hidden",
+                    style: "tab-size: 4;",
+                    value: "{value}",
+                    placeholder: "{placeholder}",
+                    spellcheck: false,
+                    autocomplete: "off",
+                    autocapitalize: "off",
+
+                    role: "combobox",
+                    aria_expanded: popup.is_some(),
+
+                    oninput: on_input,
+                    onkeydown: on_keydown,
+                    onkeyup: on_keyup,
+                    onclick: on_click,
+                    onmousemove: on_mousemove,
+                    onmouseleave: move |_| hide_hover(),
+                    onblur: move |_| completion.close(),
+                }
             }
         }
     }
 }
+
+/// Byte offset of the caret in editor `editor_id`, whose text is `text`.
+async fn caret(editor_id: usize, text: &str) -> Option<usize> {
+    let js = format!(
+        "return document.getElementById('code-editor-{editor_id}')?.selectionStart ?? null;"
+    );
+    let units: usize = document::eval(&js).join::<Option<usize>>().await.ok()??;
+    // The DOM counts UTF-16 code units
+    let mut seen = 0;
+    for (offset, c) in text.char_indices() {
+        if seen >= units {
+            return Some(offset);
+        }
+        seen += c.len_utf16();
+    }
+    Some(text.len())
+}
+
+/// Move the caret of editor `editor_id` to byte `offset` of `text` once the
+/// new text has been rendered.
+fn set_caret(editor_id: usize, text: &str, offset: usize) {
+    let units: usize = text[..offset].chars().map(char::len_utf16).sum();
+    document::eval(&format!(
+        "requestAnimationFrame(() => {{ const el = document.getElementById('code-editor-{editor_id}'); if (el) {{ el.focus(); el.setSelectionRange({units}, {units}); }} }});"
+    ));
+}
+
+/// Byte offset of the highlighted token under the viewport point `(x, y)`.
+///
+/// The textarea sits on top of the highlighted copy, so this looks through it
+/// with `elementsFromPoint` to the token span underneath.
+async fn offset_at(editor_id: usize, x: f64, y: f64) -> Option<usize> {
+    let js = format!(
+        "const el = document.elementsFromPoint({x}, {y}).find(e => e.dataset && e.dataset.offset !== undefined && e.closest('#code-editor-{editor_id}-lines')); return el ? Number(el.dataset.offset) : null;"
+    );
+    document::eval(&js).join::<Option<usize>>().await.ok()?
+}
+
+/// Scroll 1-based `line` of editor `editor_id` into view.
+fn reveal_line(editor_id: usize, line: u32) {
+    document::eval(&format!(
+        "requestAnimationFrame(() => document.getElementById('code-editor-{editor_id}-line-{line}')?.scrollIntoView({{ block: 'center' }}));"
+    ));
+}
+
+/// The most severe level on each line, and all messages for that line.
+fn line_markers(diagnostics: &[ErrorInfo]) -> HashMap<u32, (ErrorLevel, String)> {
+    let mut markers: HashMap<u32, (ErrorLevel, String)> = HashMap::new();
+    for info in diagnostics {
+        let Some(line) = info.line else { continue };
+        markers
+            .entry(line)
+            .and_modify(|(level, message)| {
+                if severity(&info.level) > severity(level) {
+                    *level = info.level.clone();
+                }
+                message.push('\n');
+                message.push_str(&info.message);
+            })
+            .or_insert_with(|| (info.level.clone(), info.message.clone()));
+    }
+    markers
+}
+
+fn severity(level: &ErrorLevel) -> u8 {
+    match level {
+        ErrorLevel::Info => 0,
+        ErrorLevel::Warning => 1,
+        ErrorLevel::Error => 2,
+    }
+}
+
+fn marker_class(level: &ErrorLevel) -> &'static str {
+    match level {
+        ErrorLevel::Error => "bg-red-500",
+        ErrorLevel::Warning => "bg-yellow-500",
+        ErrorLevel::Info => "bg-blue-500",
+    }
+}
+
+fn kind_badge(kind: CompletionKind) -> &'static str {
+    match kind {
+        CompletionKind::Keyword => "k",
+        CompletionKind::Attribute => "a",
+        CompletionKind::Value => "v",
+        CompletionKind::Node => "n",
+        CompletionKind::Subgraph => "s",
+        CompletionKind::Link => "↗",
+    }
+}
+
+/// Colors only: weight and slant would change glyph widths and break the
+/// alignment with the textarea on top.
+fn token_class(kind: HighlightKind) -> &'static str {
+    match kind {
+        HighlightKind::Plain => "",
+        HighlightKind::Keyword => "text-purple-700",
+        HighlightKind::Id => "text-gray-900",
+        HighlightKind::AttrName => "text-sky-700",
+        HighlightKind::AttrValue => "text-teal-700",
+        HighlightKind::Number => "text-orange-600",
+        HighlightKind::String => "text-green-700",
+        HighlightKind::Html => "text-amber-700",
+        HighlightKind::Comment => "text-gray-500",
+        HighlightKind::EdgeOp => "text-pink-600",
+        HighlightKind::Punctuation => "text-gray-500",
+        HighlightKind::Error => "text-red-600 underline decoration-wavy",
+    }
+}
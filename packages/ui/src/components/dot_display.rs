@@ -1,58 +1,82 @@
 //! Component to render DOT graphs using Graphvizm and Dioxus
+mod animate;
+
 pub mod fonts;
 
+mod pan_zoom;
+pub use pan_zoom::{PanZoomSvg, Viewport};
+
 mod render;
-pub use render::{GraphvizSvg, SvgBuildConfig};
+pub use render::{GraphvizSvg, LinkKind, SvgBuildConfig};
+
+mod rough;
+pub use rough::{roughen_svg, RoughFillStyle, RoughOptions, ROUGH_PRESETS};
 
 use dioxus::prelude::*;
 
 use crate::error::RenderError;
+use crate::theme::Theme;
 use crate::GVizProvider;
+use dot_lang::source_map::GraphElement;
 
 #[component]
-pub fn DotDisplay(dot: String, error_signal: Signal<Option<RenderError>>, rough: bool) -> Element {
+pub fn DotDisplay(
+    /// The DOT source. It is rendered again when it changes, and the previous
+    /// graph stays on screen until then.
+    dot: ReadSignal<String>,
+    error_signal: Signal<Option<RenderError>>,
+    rough: bool,
+    /// Elements to outline in the rendered graph.
+    #[props(default)]
+    selected: Vec<GraphElement>,
+    /// Extra classes for elements of the rendered graph.
+    #[props(default)]
+    classes: Vec<(GraphElement, String)>,
+    /// Called when a node, edge or cluster is clicked.
+    #[props(default)]
+    onselect: Option<EventHandler<GraphElement>>,
+    /// How rough shapes are drawn when `rough` is set.
+    #[props(default)]
+    rough_options: RoughOptions,
+    /// Colours for what Graphviz draws in its defaults.
+    #[props(default)]
+    theme: Theme,
+    /// Animate the graph from one render to the next.
+    #[props(default)]
+    animate: bool,
+) -> Element {
     let mut svg_signal = use_signal(|| None::<String>);
     let gviz_signal = use_context::<Signal<Option<GVizProvider>>>();
 
-    // Track last dot to only render when changed
-    let mut last_dot = use_signal(|| String::new());
-    let dot_changed = last_dot.read().as_str() != dot.as_str();
-
-    // Also re-render when GViz becomes available and we have a dot with no SVG yet.
-    // This covers the race where dot arrived before GViz finished loading.
-    let gviz_just_ready =
-        gviz_signal.read().is_some() && svg_signal.read().is_none() && !dot.is_empty();
-
-    if dot_changed || gviz_just_ready {
-        if dot_changed {
-            last_dot.set(dot.clone());
+    // Renders run in a task, restarted whenever `dot` changes or GViz becomes
+    // available. Restarting drops the render of a source that is out of date.
+    let _render = use_resource(move || async move {
+        let dot = dot();
+        let Some(gviz) = gviz_signal() else {
+            return;
+        };
+        if dot.is_empty() {
+            if error_signal.peek().is_some() {
+                error_signal.set(None);
+            }
+            svg_signal.set(None);
+            return;
         }
-        let gviz_signal_val = gviz_signal.read();
-
-        if let Some(gviz) = gviz_signal_val.as_ref() {
-            if dot.is_empty() {
+        match gviz.render_dot(&dot).await {
+            Ok(rendered_svg) => {
                 if error_signal.peek().is_some() {
                     error_signal.set(None);
                 }
-                svg_signal.set(None);
-            } else {
-                match gviz.render_dot(&dot) {
-                    Ok(rendered_svg) => {
-                        if error_signal.peek().is_some() {
-                            error_signal.set(None);
-                        }
-                        svg_signal.set(Some(rendered_svg));
-                    }
-                    Err(e) => match e {
-                        crate::Error::DotRenderError(render_error) => {
-                            error_signal.set(Some(render_error));
-                        }
-                        _ => {}
-                    },
-                }
+                svg_signal.set(Some(rendered_svg));
             }
+            Err(e) => match e {
+                crate::Error::DotRenderError(render_error) => {
+                    error_signal.set(Some(render_error));
+                }
+                _ => {}
+            },
         }
-    }
+    });
 
     let maybe_gviz = gviz_signal.read();
     match maybe_gviz.as_ref() {
@@ -68,7 +92,7 @@ pub fn DotDisplay(dot: String, error_signal: Signal<Option<RenderError>>, rough:
         // Cases 2-4: We have gviz
         Some(_gviz) => {
             // Case 2: Empty dot string
-            if dot.is_empty() {
+            if dot.read().is_empty() {
                 return rsx! {
                     div {
                         class: "text-grey-500 p-4 text-center",
@@ -81,15 +105,21 @@ pub fn DotDisplay(dot: String, error_signal: Signal<Option<RenderError>>, rough:
             if let Some(svg) = svg_signal.read().as_ref() {
                 let config = SvgBuildConfig {
                     rough_style: rough,
+                    rough_options: rough_options.clone(),
+                    theme,
+                    animate,
                     ..Default::default()
                 };
 
                 rsx! {
                     div {
-                        class: "w-full h-full overflow-auto",
-                        GraphvizSvg {
+                        class: "w-full h-full",
+                        PanZoomSvg {
                             svg_text: svg.clone(),
-                            config: config
+                            config: config,
+                            selected: selected.clone(),
+                            classes: classes.clone(),
+                            onselect: onselect,
                         }
                     }
                 }
@@ -0,0 +1,83 @@
+//! Transitions between successive renders of a graph.
+//!
+//! After each render the nodes, edges and clusters are matched to the previous
+//! render by their Graphviz `<title>` (and, for repeated edges, their order).
+//! Matched elements slide from their old position, new ones fade in, and
+//! copies of removed ones fade out. The browser's Web Animations API does the
+//! tweening, so the Dioxus tree is never touched mid-animation.
+use dioxus::prelude::*;
+
+const DURATION_MS: u32 = 300;
+
+/// Define `window.animateGraph` for [animate]. Run once, when the graph is
+/// mounted.
+pub(super) fn install() {
+    document::eval(ANIMATE_JS);
+}
+
+/// Animate the `<svg>` with id `svg_id` from its previous render. The first
+/// call for an id only records positions.
+pub(super) fn animate(svg_id: &str) {
+    let svg_id = svg_id.replace('\\', "\\\\").replace('\'', "\\'");
+    document::eval(&format!("window.animateGraph('{svg_id}', {DURATION_MS});"));
+}
+
+/// `animateGraph` keeps each graph's last positions and markup in
+/// `window.graphvizRenders`, keyed by `<svg>` id.
+const ANIMATE_JS: &str = r#"
+window.animateGraph = function (id, ms) {
+    const svg = document.getElementById(id);
+    if (!svg) return;
+    const renders = window.graphvizRenders || (window.graphvizRenders = new Map());
+    const previous = renders.get(id);
+    const still = window.matchMedia('(prefers-reduced-motion: reduce)').matches;
+    const current = new Map();
+    const counts = new Map();
+
+    for (const el of svg.querySelectorAll('g.node, g.edge, g.cluster')) {
+        if (el.closest('[data-minimap], [data-ghost]')) continue;
+        const title = el.querySelector(':scope > title');
+        if (!title) continue;
+        let key = el.classList[0] + ':' + title.textContent;
+        const n = counts.get(key) || 0;
+        counts.set(key, n + 1);
+        key += '#' + n;
+
+        const box = el.getBBox();
+        const x = box.x + box.width / 2;
+        const y = box.y + box.height / 2;
+        current.set(key, { x, y, html: el.outerHTML });
+        if (!previous || still) continue;
+
+        for (const running of el.getAnimations()) running.cancel();
+        const before = previous.get(key);
+        if (!before) {
+            el.animate([{ opacity: 0 }, { opacity: 1 }], { duration: ms, easing: 'ease-out' });
+        } else if (before.x !== x || before.y !== y) {
+            const from = `translate(${before.x - x}px, ${before.y - y}px)`;
+            el.animate([{ transform: from }, { transform: 'none' }], { duration: ms, easing: 'ease-in-out' });
+        }
+    }
+
+    if (previous && !still) {
+        const layer = svg.querySelector('g.graph') || svg;
+        for (const [key, before] of previous) {
+            if (current.has(key)) continue;
+            const holder = document.createElementNS('http://www.w3.org/2000/svg', 'g');
+            holder.innerHTML = before.html;
+            const ghost = holder.firstElementChild;
+            if (!ghost) continue;
+            for (const el of [ghost, ...ghost.querySelectorAll('*')]) {
+                el.removeAttribute('id');
+                el.removeAttribute('data-dioxus-id');
+            }
+            ghost.setAttribute('data-ghost', '');
+            ghost.style.pointerEvents = 'none';
+            layer.appendChild(ghost);
+            ghost.animate([{ opacity: 1 }, { opacity: 0 }], { duration: ms, easing: 'ease-in' })
+                .finished.then(() => ghost.remove(), () => ghost.remove());
+        }
+    }
+    renders.set(id, current);
+};
+"#;
@@ -1,7 +1,119 @@
 //! Shared font definitions for hand-drawn styling
-
-pub const ARCHITECTS_DAUGHTER_CSS: &str = r#"@import url('https://fonts.googleapis.com/css2?family=Architects+Daughter&display=swap');
-@import url('https://fonts.googleapis.com/css2?family=Noto+Sans+Symbols+2&display=swap');"#;
+//!
+//! Architects Daughter and Noto Sans Symbols 2 ship in `assets/fonts`. The page
+//! loads them as Dioxus assets, see [page_font_css], and rough SVGs embed them
+//! as `@font-face` data URIs, see [embedded_font_css], so rough mode works
+//! offline and exported SVGs keep their lettering. Apps can add their own
+//! handwriting fonts with [register_font].
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use dioxus::prelude::*;
+use std::sync::Mutex;
 
 pub const ARCHITECTS_DAUGHTER_FAMILY: &str =
     "'Architects Daughter','Noto Sans Symbols 2','Noto Sans',sans-serif";
+
+/// A font for the text of rough graphs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HandwritingFont {
+    /// The CSS font family name.
+    pub family: &'static str,
+    /// The font file, in WOFF2 format.
+    pub woff2: &'static [u8],
+}
+
+pub const ARCHITECTS_DAUGHTER: HandwritingFont = HandwritingFont {
+    family: "Architects Daughter",
+    woff2: include_bytes!("../../../assets/fonts/ArchitectsDaughter-Regular.woff2"),
+};
+
+/// Covers the arrows and other symbols Architects Daughter lacks.
+pub const NOTO_SANS_SYMBOLS_2: HandwritingFont = HandwritingFont {
+    family: "Noto Sans Symbols 2",
+    woff2: include_bytes!("../../../assets/fonts/NotoSansSymbols2-Regular.woff2"),
+};
+
+const ARCHITECTS_DAUGHTER_WOFF2: Asset = asset!("/assets/fonts/ArchitectsDaughter-Regular.woff2");
+const NOTO_SANS_SYMBOLS_2_WOFF2: Asset = asset!("/assets/fonts/NotoSansSymbols2-Regular.woff2");
+
+/// The built-in fonts, with the assets serving them.
+const BUILT_IN: [(HandwritingFont, Asset); 2] = [
+    (ARCHITECTS_DAUGHTER, ARCHITECTS_DAUGHTER_WOFF2),
+    (NOTO_SANS_SYMBOLS_2, NOTO_SANS_SYMBOLS_2_WOFF2),
+];
+
+/// Fonts added with [register_font], and the CSS embedding them and the
+/// built-in ones once it has been built.
+static REGISTRY: Mutex<Registry> = Mutex::new(Registry {
+    fonts: Vec::new(),
+    css: None,
+});
+
+struct Registry {
+    fonts: Vec<HandwritingFont>,
+    css: Option<String>,
+}
+
+/// Add a handwriting font for rough graphs. Registered fonts are tried before
+/// the built-in ones, latest first.
+///
+/// Register fonts at startup, before the first rough graph is drawn.
+pub fn register_font(font: HandwritingFont) {
+    let mut registry = REGISTRY.lock().unwrap_or_else(|e| e.into_inner());
+    if !registry.fonts.contains(&font) {
+        registry.fonts.insert(0, font);
+        registry.css = None;
+    }
+}
+
+/// `@font-face` rules embedding the registered and built-in fonts as data
+/// URIs, the default for
+/// [SvgBuildConfig::rough_embed_font_data](super::SvgBuildConfig).
+pub fn embedded_font_css() -> String {
+    let mut registry = REGISTRY.lock().unwrap_or_else(|e| e.into_inner());
+    let Registry { fonts, css } = &mut *registry;
+    css.get_or_insert_with(|| {
+        fonts
+            .iter()
+            .chain(BUILT_IN.iter().map(|(font, _)| font))
+            .map(|font| font_face(font.family, &data_uri(font)))
+            .collect()
+    })
+    .clone()
+}
+
+/// `@font-face` rules for the page: the built-in fonts from their assets, and
+/// the registered ones as data URIs.
+pub fn page_font_css() -> String {
+    let registry = REGISTRY.lock().unwrap_or_else(|e| e.into_inner());
+    let registered = registry
+        .fonts
+        .iter()
+        .map(|font| font_face(font.family, &data_uri(font)));
+    let built_in = BUILT_IN
+        .iter()
+        .map(|(font, asset)| font_face(font.family, &asset.to_string()));
+    registered.chain(built_in).collect()
+}
+
+/// The CSS `font-family` for rough text: registered fonts, then the built-in
+/// ones and their fallbacks.
+pub fn font_family() -> String {
+    let registry = REGISTRY.lock().unwrap_or_else(|e| e.into_inner());
+    registry
+        .fonts
+        .iter()
+        .map(|font| format!("'{}',", font.family))
+        .chain([ARCHITECTS_DAUGHTER_FAMILY.to_string()])
+        .collect()
+}
+
+fn data_uri(font: &HandwritingFont) -> String {
+    format!("data:font/woff2;base64,{}", STANDARD.encode(font.woff2))
+}
+
+fn font_face(family: &str, url: &str) -> String {
+    format!(
+        "@font-face {{ font-family: '{family}'; font-display: swap; \
+         src: url({url}) format('woff2'); }}\n"
+    )
+}
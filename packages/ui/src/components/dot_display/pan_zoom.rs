@@ -0,0 +1,555 @@
+//! Pan and zoom for rendered graphs.
+//!
+//! The view is a `viewBox` over the Graphviz output rather than a CSS
+//! transform, so the SVG stays crisp at every zoom level. The viewport always
+//! has the container's aspect ratio, which keeps the mapping between screen
+//! pixels and SVG units a plain scale and offset.
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use dioxus::html::geometry::{PixelsRect, WheelDelta};
+use dioxus::prelude::*;
+use dot_lang::source_map::GraphElement;
+
+use super::render::{GraphvizSvg, SvgBuildConfig};
+
+/// Zoom limits, relative to the graph's natural size.
+const MIN_SCALE: f64 = 0.05;
+const MAX_SCALE: f64 = 20.0;
+/// Zoom factor of the toolbar buttons.
+const ZOOM_STEP: f64 = 1.25;
+/// Space left around the graph by "Fit", as a fraction of its larger side.
+const FIT_MARGIN: f64 = 0.02;
+/// Space left around the selection by "Selection".
+const SELECTION_MARGIN: f64 = 0.1;
+
+static NEXT_VIEWER_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// The part of a graph that is shown, in SVG user units.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Viewport {
+    /// The smallest viewport with aspect ratio `aspect` (width / height) that
+    /// contains `rect`, centred on it.
+    pub fn around(rect: Viewport, aspect: f64) -> Self {
+        let mut width = rect.width.max(f64::EPSILON);
+        let mut height = rect.height.max(f64::EPSILON);
+        if width / height < aspect {
+            width = height * aspect;
+        } else {
+            height = width / aspect;
+        }
+        let (cx, cy) = rect.center();
+        Viewport::centered(cx, cy, width, height)
+    }
+
+    fn centered(cx: f64, cy: f64, width: f64, height: f64) -> Self {
+        Viewport {
+            x: cx - width / 2.0,
+            y: cy - height / 2.0,
+            width,
+            height,
+        }
+    }
+
+    pub fn center(&self) -> (f64, f64) {
+        (self.x + self.width / 2.0, self.y + self.height / 2.0)
+    }
+
+    /// Grown on every side by `fraction` of the larger dimension.
+    pub fn padded(&self, fraction: f64) -> Self {
+        let pad = self.width.max(self.height) * fraction;
+        Viewport {
+            x: self.x - pad,
+            y: self.y - pad,
+            width: self.width + 2.0 * pad,
+            height: self.height + 2.0 * pad,
+        }
+    }
+
+    /// Zoomed in by `factor` (below 1 zooms out), keeping the point
+    /// `(fx, fy)` where it is on screen.
+    pub fn zoom(&self, factor: f64, fx: f64, fy: f64) -> Self {
+        Viewport {
+            x: fx - (fx - self.x) / factor,
+            y: fy - (fy - self.y) / factor,
+            width: self.width / factor,
+            height: self.height / factor,
+        }
+    }
+
+    pub fn pan(&self, dx: f64, dy: f64) -> Self {
+        Viewport {
+            x: self.x + dx,
+            y: self.y + dy,
+            ..*self
+        }
+    }
+
+    /// Parse an SVG `viewBox` attribute. The size must be positive.
+    pub fn parse(view_box: &str) -> Option<Self> {
+        let numbers: Vec<f64> = view_box
+            .split([' ', ','])
+            .filter(|s| !s.is_empty())
+            .map(str::parse)
+            .collect::<Result<_, _>>()
+            .ok()?;
+        let [x, y, width, height] = numbers[..] else {
+            return None;
+        };
+        (width > 0.0 && height > 0.0).then_some(Viewport {
+            x,
+            y,
+            width,
+            height,
+        })
+    }
+
+    /// The value for the SVG `viewBox` attribute.
+    pub fn view_box(&self) -> String {
+        format!("{} {} {} {}", self.x, self.y, self.width, self.height)
+    }
+}
+
+/// [GraphvizSvg] in a viewport with zoom, pan and a small toolbar.
+///
+/// The mouse wheel zooms around the pointer (trackpad pinches arrive as
+/// Ctrl+wheel, so they zoom too) and dragging pans. The toolbar zooms in and
+/// out, fits the graph, shows it at its natural size, or zooms to the
+/// `selected` elements. `classes` are passed on to [GraphvizSvg].
+///
+/// The graph starts fitted to the container. After that the view is kept when
+/// `svg_text` changes, so it doesn't jump while the source is being edited.
+#[component]
+pub fn PanZoomSvg(
+    svg_text: String,
+    config: SvgBuildConfig,
+    #[props(default)] selected: Vec<GraphElement>,
+    #[props(default)] classes: Vec<(GraphElement, String)>,
+    #[props(default)] onselect: Option<EventHandler<GraphElement>>,
+) -> Element {
+    let viewer_id = use_hook(|| NEXT_VIEWER_ID.fetch_add(1, Ordering::Relaxed));
+    let mut mounted = use_signal(|| None::<Rc<MountedData>>);
+    // Screen rectangle of the container
+    let mut bounds = use_signal(|| None::<PixelsRect>);
+    let mut viewport = use_signal(|| None::<Viewport>);
+    // Pointer position and viewport when a drag started
+    let mut drag = use_signal(|| None::<(f64, f64, Viewport)>);
+    let mut minimap = use_signal(|| config.minimap);
+
+    // The graph's own bounds and its pixels per unit at 100%
+    let extent = svg_extent(&svg_text);
+    let natural_scale = extent.map_or(1.0, |(_, scale)| scale);
+
+    let fit = move |rect: PixelsRect| {
+        extent.map(|(content, _)| Viewport::around(content.padded(FIT_MARGIN), aspect(&rect)))
+    };
+
+    // Zoom by `factor` around a point in container pixels, within the limits
+    let mut zoom_by = move |factor: f64, px: f64, py: f64| {
+        let (Some(rect), Some(view)) = (*bounds.peek(), *viewport.peek()) else {
+            return;
+        };
+        let scale = rect.size.width / view.width;
+        let target = (scale * factor).clamp(natural_scale * MIN_SCALE, natural_scale * MAX_SCALE);
+        let fx = view.x + px / rect.size.width * view.width;
+        let fy = view.y + py / rect.size.height * view.height;
+        viewport.set(Some(view.zoom(target / scale, fx, fy)));
+    };
+
+    let on_resize = move |_: ResizeEvent| {
+        let Some(element) = mounted.peek().clone() else {
+            return;
+        };
+        spawn(async move {
+            let Ok(rect) = element.get_client_rect().await else {
+                return;
+            };
+            let previous = *bounds.peek();
+            bounds.set(Some(rect));
+            // Keep the centre and scale; reveal or hide what the resize adds or removes
+            let next = match (*viewport.peek(), previous) {
+                (Some(view), Some(old)) if old.size.width > 0.0 => {
+                    let scale = old.size.width / view.width;
+                    let (cx, cy) = view.center();
+                    Some(Viewport::centered(
+                        cx,
+                        cy,
+                        rect.size.width / scale,
+                        rect.size.height / scale,
+                    ))
+                }
+                _ => fit(rect),
+            };
+            viewport.set(next);
+        });
+    };
+
+    let on_wheel = move |e: WheelEvent| {
+        e.prevent_default();
+        let Some(rect) = *bounds.peek() else {
+            return;
+        };
+        let delta = match e.delta() {
+            WheelDelta::Pixels(v) => v.y,
+            WheelDelta::Lines(v) => v.y * 16.0,
+            WheelDelta::Pages(v) => v.y * rect.size.height,
+        };
+        let point = e.client_coordinates();
+        zoom_by(
+            (-delta * 0.002).exp(),
+            point.x - rect.origin.x,
+            point.y - rect.origin.y,
+        );
+    };
+
+    let on_mousedown = move |e: MouseEvent| {
+        if e.trigger_button() != Some(MouseButton::Primary) {
+            return;
+        }
+        if let Some(view) = *viewport.peek() {
+            let point = e.client_coordinates();
+            drag.set(Some((point.x, point.y, view)));
+        }
+    };
+
+    let on_mousemove = move |e: MouseEvent| {
+        let (Some((sx, sy, start)), Some(rect)) = (*drag.peek(), *bounds.peek()) else {
+            return;
+        };
+        let point = e.client_coordinates();
+        let units = start.width / rect.size.width;
+        viewport.set(Some(
+            start.pan((sx - point.x) * units, (sy - point.y) * units),
+        ));
+    };
+
+    let actual_size = move |_| {
+        let (Some(rect), Some(view)) = (*bounds.peek(), *viewport.peek()) else {
+            return;
+        };
+        let (cx, cy) = view.center();
+        viewport.set(Some(Viewport::centered(
+            cx,
+            cy,
+            rect.size.width / natural_scale,
+            rect.size.height / natural_scale,
+        )));
+    };
+
+    let zoom_to_selection = move |_| {
+        spawn(async move {
+            let (Some(rect), Some(view)) = (*bounds.peek(), *viewport.peek()) else {
+                return;
+            };
+            let Some([left, top, right, bottom]) = selection_bounds(viewer_id).await else {
+                return;
+            };
+            let units = view.width / rect.size.width;
+            let target = Viewport {
+                x: view.x + left * units,
+                y: view.y + top * units,
+                width: (right - left) * units,
+                height: (bottom - top) * units,
+            };
+            let mut next = Viewport::around(target.padded(SELECTION_MARGIN), aspect(&rect));
+            let max_width = rect.size.width / (natural_scale * MAX_SCALE);
+            if next.width < max_width {
+                let (cx, cy) = next.center();
+                next = Viewport::centered(cx, cy, max_width, max_width / aspect(&rect));
+            }
+            viewport.set(Some(next));
+        });
+    };
+
+    let config = SvgBuildConfig {
+        viewport: viewport(),
+        minimap: minimap(),
+        ..config
+    };
+    let cursor = if drag.read().is_some() {
+        "cursor-grabbing"
+    } else {
+        "cursor-grab"
+    };
+    let button = "px-2 py-1 text-xs bg-gray-200 hover:bg-gray-300 rounded disabled:opacity-50";
+
+    rsx! {
+        div {
+            id: "pan-zoom-{viewer_id}",
+            class: "relative w-full h-full overflow-hidden select-none {cursor}",
+            onmounted: move |e| mounted.set(Some(e.data())),
+            onresize: on_resize,
+            onwheel: on_wheel,
+            onmousedown: on_mousedown,
+            onmousemove: on_mousemove,
+            onmouseup: move |_| drag.set(None),
+            onmouseleave: move |_| drag.set(None),
+            GraphvizSvg {
+                svg_text: svg_text,
+                config: config,
+                selected: selected.clone(),
+                classes: classes.clone(),
+                onselect: onselect,
+                // Centre the view where the minimap was clicked
+                onnavigate: move |(x, y): (f64, f64)| {
+                    if let Some(view) = *viewport.peek() {
+                        viewport.set(Some(Viewport::centered(x, y, view.width, view.height)));
+                    }
+                },
+            }
+            div {
+                class: "absolute top-2 right-2 flex gap-1 cursor-default",
+                // Keep toolbar clicks from starting a drag
+                onmousedown: move |e| e.stop_propagation(),
+                button {
+                    class: button,
+                    title: "Zoom out",
+                    onclick: move |_| {
+                        if let Some(rect) = *bounds.peek() {
+                            zoom_by(1.0 / ZOOM_STEP, rect.size.width / 2.0, rect.size.height / 2.0);
+                        }
+                    },
+                    "−"
+                }
+                button {
+                    class: button,
+                    title: "Zoom in",
+                    onclick: move |_| {
+                        if let Some(rect) = *bounds.peek() {
+                            zoom_by(ZOOM_STEP, rect.size.width / 2.0, rect.size.height / 2.0);
+                        }
+                    },
+                    "+"
+                }
+                button {
+                    class: button,
+                    title: "Actual size",
+                    onclick: actual_size,
+                    "100%"
+                }
+                button {
+                    class: button,
+                    title: "Fit to view",
+                    onclick: move |_| {
+                        if let Some(rect) = *bounds.peek() {
+                            viewport.set(fit(rect));
+                        }
+                    },
+                    "Fit"
+                }
+                button {
+                    class: if minimap() { "{button} bg-gray-300" } else { button },
+                    title: "Toggle minimap",
+                    aria_pressed: minimap(),
+                    onclick: move |_| minimap.toggle(),
+                    "Map"
+                }
+                button {
+                    class: button,
+                    title: "Zoom to selection",
+                    disabled: selected.is_empty(),
+                    onclick: zoom_to_selection,
+                    "Selection"
+                }
+            }
+        }
+    }
+}
+
+fn aspect(rect: &PixelsRect) -> f64 {
+    aspect_ratio(rect.size.width, rect.size.height)
+}
+
+/// `width / height`, or 1 for a container that has no area yet, e.g. before
+/// it is laid out.
+fn aspect_ratio(width: f64, height: f64) -> f64 {
+    if width > 0.0 && height > 0.0 {
+        width / height
+    } else {
+        1.0
+    }
+}
+
+/// Screen bounds of the selected elements in viewer `viewer_id`, relative to
+/// the viewer, as `[left, top, right, bottom]` pixels.
+async fn selection_bounds(viewer_id: usize) -> Option<[f64; 4]> {
+    let js = format!(
+        r#"const root = document.getElementById('pan-zoom-{viewer_id}');
+        const selected = root ? [...root.querySelectorAll('g.selected')].filter(el => !el.closest('[data-minimap]')) : [];
+        if (!selected.length) return null;
+        const origin = root.getBoundingClientRect();
+        const rects = selected.map(el => el.getBoundingClientRect());
+        return [
+            Math.min(...rects.map(r => r.left)) - origin.left,
+            Math.min(...rects.map(r => r.top)) - origin.top,
+            Math.max(...rects.map(r => r.right)) - origin.left,
+            Math.max(...rects.map(r => r.bottom)) - origin.top,
+        ];"#
+    );
+    document::eval(&js).join::<Option<[f64; 4]>>().await.ok()?
+}
+
+/// The `viewBox` of Graphviz output and its CSS pixels per user unit at
+/// natural size, which comes from the `width` attribute (in points).
+fn svg_extent(svg_text: &str) -> Option<(Viewport, f64)> {
+    let start = svg_text.find("<svg")?;
+    let tag = &svg_text[start..start + svg_text[start..].find('>')?];
+    let attr = |name: &str| {
+        let pattern = format!("{name}=\"");
+        tag.match_indices(&pattern)
+            .find(|(i, _)| tag[..*i].ends_with(char::is_whitespace))
+            .and_then(|(i, _)| {
+                let value = &tag[i + pattern.len()..];
+                value.find('"').map(|end| &value[..end])
+            })
+    };
+    let extent = Viewport::parse(attr("viewBox")?)?;
+    let scale = attr("width")
+        .and_then(length_px)
+        .map_or(1.0, |px| px / extent.width);
+    Some((extent, scale))
+}
+
+/// An SVG length such as `62pt` or `100px` in CSS pixels.
+fn length_px(value: &str) -> Option<f64> {
+    match value.strip_suffix("pt") {
+        Some(points) => points.trim().parse::<f64>().ok().map(|pt| pt * 4.0 / 3.0),
+        None => value
+            .strip_suffix("px")
+            .unwrap_or(value)
+            .trim()
+            .parse()
+            .ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn zooming_keeps_the_focus_point_in_place() {
+        let view = Viewport {
+            x: 0.0,
+            y: 0.0,
+            width: 200.0,
+            height: 100.0,
+        };
+        let zoomed = view.zoom(2.0, 50.0, 25.0);
+        assert_eq!(
+            zoomed,
+            Viewport {
+                x: 25.0,
+                y: 12.5,
+                width: 100.0,
+                height: 50.0
+            }
+        );
+        // The focus sits at the same fraction of the view before and after
+        let fraction = |v: &Viewport| ((50.0 - v.x) / v.width, (25.0 - v.y) / v.height);
+        assert_eq!(fraction(&view), fraction(&zoomed));
+
+        let back = zoomed.zoom(0.5, 50.0, 25.0);
+        assert!(close(back.x, view.x) && close(back.y, view.y));
+        assert!(close(back.width, view.width) && close(back.height, view.height));
+    }
+
+    #[test]
+    fn around_widens_or_heightens_to_the_aspect_and_keeps_the_centre() {
+        let rect = Viewport {
+            x: 10.0,
+            y: 20.0,
+            width: 100.0,
+            height: 100.0,
+        };
+        let wide = Viewport::around(rect, 2.0);
+        assert_eq!((wide.width, wide.height), (200.0, 100.0));
+        assert_eq!(wide.center(), rect.center());
+
+        let tall = Viewport::around(rect, 0.5);
+        assert_eq!((tall.width, tall.height), (100.0, 200.0));
+        assert_eq!(tall.center(), rect.center());
+    }
+
+    #[test]
+    fn around_an_empty_rect_stays_finite() {
+        let point = Viewport {
+            x: 5.0,
+            y: 5.0,
+            width: 0.0,
+            height: 0.0,
+        };
+        let view = Viewport::around(point, aspect_ratio(300.0, 0.0));
+        assert!(view.width > 0.0 && view.height > 0.0);
+        assert!(view.width.is_finite() && view.height.is_finite());
+        assert_eq!(view.center(), (5.0, 5.0));
+    }
+
+    #[test]
+    fn a_container_without_area_has_a_square_aspect() {
+        assert_eq!(aspect_ratio(300.0, 150.0), 2.0);
+        assert_eq!(aspect_ratio(300.0, 0.0), 1.0);
+        assert_eq!(aspect_ratio(0.0, 150.0), 1.0);
+    }
+
+    #[test]
+    fn parses_view_boxes() {
+        assert_eq!(
+            Viewport::parse("0.00 -4, 62.5 116"),
+            Some(Viewport {
+                x: 0.0,
+                y: -4.0,
+                width: 62.5,
+                height: 116.0
+            })
+        );
+        let view = Viewport::parse("1 2 3 4").unwrap();
+        assert_eq!(Viewport::parse(&view.view_box()), Some(view));
+
+        for degenerate in [
+            "0 0 0 10",
+            "0 0 10 -1",
+            "0 0 10",
+            "0 0 10 10 10",
+            "a b c d",
+            "",
+        ] {
+            assert_eq!(Viewport::parse(degenerate), None, "{degenerate:?}");
+        }
+    }
+
+    #[test]
+    fn converts_lengths_to_css_pixels() {
+        assert_eq!(length_px("72pt"), Some(96.0));
+        assert_eq!(length_px("100px"), Some(100.0));
+        assert_eq!(length_px("50"), Some(50.0));
+        assert_eq!(length_px("2in"), None);
+    }
+
+    #[test]
+    fn reads_the_extent_and_scale_of_graphviz_output() {
+        let svg = r#"<?xml version="1.0"?>
+<svg width="62pt" height="116pt" viewBox="0.00 0.00 62.00 116.00" xmlns="http://www.w3.org/2000/svg"><g/></svg>"#;
+        let (extent, scale) = svg_extent(svg).unwrap();
+        assert_eq!(extent, Viewport::parse("0 0 62 116").unwrap());
+        assert!(close(scale, 4.0 / 3.0));
+
+        // No usable width: natural size is one pixel per unit
+        let (_, scale) = svg_extent(r#"<svg viewBox="0 0 10 10"></svg>"#).unwrap();
+        assert_eq!(scale, 1.0);
+        // `data-viewBox` is not the viewBox
+        assert!(svg_extent(r#"<svg data-viewBox="0 0 10 10"></svg>"#).is_none());
+        assert!(svg_extent(r#"<svg viewBox="0 0 0 0"></svg>"#).is_none());
+    }
+}
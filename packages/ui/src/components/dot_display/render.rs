@@ -1,21 +1,22 @@
 //! Graphviz SVG → Dioxus renderer with optional "rough" hand-drawn styling.
 //!
-//! When `rough` feature is enabled, shapes and paths can be converted to hand-drawn versions using `roughr`.
-use super::fonts::{ARCHITECTS_DAUGHTER_CSS, ARCHITECTS_DAUGHTER_FAMILY};
+//! With `rough_style`, the SVG is first redrawn hand-drawn by [super::rough],
+//! then rendered like any other.
+use super::animate::{animate, install as install_animation};
+use super::fonts::{embedded_font_css, font_family};
+use super::pan_zoom::Viewport;
+use super::rough::{has_rough_classes, roughen_root, RoughOptions};
+use crate::theme::Theme;
+use dioxus::html::geometry::ClientPoint;
 use dioxus::logger::tracing;
 use dioxus::prelude::*;
 use dioxus_router::Navigator;
-use roxmltree::{Document, Node};
+use dot_lang::source_map::GraphElement;
+use roxmltree::{Document, Node, NodeId};
 use std::borrow::Cow;
-
-#[cfg(feature = "rough")]
-use std::fmt::Display;
-
-#[cfg(feature = "rough")]
-use roughr::{
-    core::{FillStyle, OpSetType, Options, OptionsBuilder},
-    generator::Generator,
-};
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum LinkKind {
@@ -25,11 +26,13 @@ pub enum LinkKind {
     None,
 }
 
-const XLINK_NS: &str = "http://www.w3.org/1999/xlink";
-const XML_NS: &str = "http://www.w3.org/XML/1998/namespace";
+pub(super) const XLINK_NS: &str = "http://www.w3.org/1999/xlink";
+pub(super) const XML_NS: &str = "http://www.w3.org/XML/1998/namespace";
 
-// Default opacity for hachure strokes when source color has no alpha
-const HATCH_DEFAULT_OPACITY: f32 = 0.95;
+// Outline for elements selected from the editor. Covers the plain shapes and
+// the groups of paths that rough shapes are drawn with.
+const SELECTION_STYLE: &str = r#"
+    g.selected path, g.selected polygon, g.selected ellipse, g.selected polyline { stroke: #2563eb !important; stroke-width: 2.5px; }"#;
 
 // ------------------------- Config -------------------------
 
@@ -42,37 +45,27 @@ pub struct SvgBuildConfig {
     pub strip_doctype: bool,
     pub rough_style: bool,
     pub scale_to_fit: bool,
+    /// Show this part of the graph, scaled to fill the container. Overrides
+    /// `scale_to_fit`.
+    pub viewport: Option<Viewport>,
+    /// Overlay a thumbnail of the whole graph marking the `viewport`. Has no
+    /// effect without one.
+    pub minimap: bool,
+    /// Colours for what Graphviz draws in its defaults.
+    pub theme: Theme,
     pub rough_options: RoughOptions,
     pub rough_use_custom_font: bool,
-    pub rough_embed_font_data: Option<&'static str>,
-}
-
-#[derive(Clone, PartialEq)]
-pub struct RoughOptions {
-    pub roughness: f32,
-    pub bowing: f32,
-    pub fill_style: RoughFillStyle,
-}
-
-#[derive(Clone, PartialEq)]
-pub enum RoughFillStyle {
-    Solid,
-    Hachure,
-    ZigZag,
-    CrossHatch,
-    Dots,
-    Dashed,
-    ZigZagLine,
-}
-
-impl Default for RoughOptions {
-    fn default() -> Self {
-        Self {
-            roughness: 1.0,
-            bowing: 1.0,
-            fill_style: RoughFillStyle::Hachure,
-        }
-    }
+    /// CSS providing the handwriting fonts, e.g. `@font-face` rules. Defaults
+    /// to the built-in and registered fonts, see [super::fonts]; `None` leaves
+    /// them to the page.
+    pub rough_embed_font_data: Option<String>,
+    /// Tween nodes, edges and clusters from where they were in the previous
+    /// render, see [super::animate]. Off by default, as every element is
+    /// measured after each render.
+    pub animate: bool,
+    /// Id for the root `<svg>`, replacing Graphviz's. When animating without
+    /// one, [GraphvizSvg] picks its own.
+    pub svg_id: Option<String>,
 }
 
 impl Default for SvgBuildConfig {
@@ -100,9 +93,14 @@ impl Default for SvgBuildConfig {
             strip_doctype: true,
             rough_style: true,
             scale_to_fit: false,
+            viewport: None,
+            minimap: false,
+            theme: Theme::default(),
             rough_options: RoughOptions::default(),
             rough_use_custom_font: true,
-            rough_embed_font_data: None,
+            rough_embed_font_data: Some(embedded_font_css()),
+            animate: false,
+            svg_id: None,
         }
     }
 }
@@ -229,325 +227,9 @@ fn collect_attrs(node: Node) -> SvgAttrs {
     sa
 }
 
-// ------------------------- Color helpers for opacity -------------------------
-
-fn format_opacity(v: f32) -> String {
-    // Trim to at most 3 decimals and strip trailing zeros/dot.
-    let s = format!("{:.3}", v.clamp(0.0, 1.0));
-    let s = s.trim_end_matches('0').trim_end_matches('.');
-    if s.is_empty() {
-        "0".into()
-    } else {
-        s.into()
-    }
-}
-
-/// Normalize a color into (#RRGGBB, Some(opacity)) if it is #RRGGBBAA; pass through otherwise.
-/// Returns (normalized_color, optional_opacity_string).
-fn normalize_hex_color_with_opacity(input: &Option<String>) -> (Option<String>, Option<String>) {
-    let Some(raw) = input.as_ref() else {
-        return (None, None);
-    };
-    let s = raw.trim();
-    if !s.starts_with('#') {
-        return (Some(raw.clone()), None);
-    }
-    let hex = &s[1..];
-    match hex.len() {
-        6 => (Some(raw.clone()), None),
-        8 => {
-            if let (Ok(a), Ok(_)) = (
-                u8::from_str_radix(&hex[6..8], 16),
-                u32::from_str_radix(&hex[0..6], 16),
-            ) {
-                let rgb = format!("#{}", &hex[0..6]);
-                let op = (a as f32) / 255.0;
-                (Some(rgb), Some(format_opacity(op)))
-            } else {
-                (Some(raw.clone()), None)
-            }
-        }
-        _ => (Some(raw.clone()), None),
-    }
-}
-
-// ------------------------- Rough conversion core -------------------------
-
-#[cfg(feature = "rough")]
-fn map_fill_style(fs: &RoughFillStyle) -> FillStyle {
-    match fs {
-        RoughFillStyle::Solid => FillStyle::Solid,
-        RoughFillStyle::Hachure => FillStyle::Hachure,
-        RoughFillStyle::ZigZag => FillStyle::ZigZag,
-        RoughFillStyle::CrossHatch => FillStyle::CrossHatch,
-        RoughFillStyle::Dots => FillStyle::Dots,
-        RoughFillStyle::Dashed => FillStyle::Dashed,
-        RoughFillStyle::ZigZagLine => FillStyle::ZigZagLine,
-    }
-}
-
-#[cfg(feature = "rough")]
-fn parse_color_to_srgba(s: &str) -> Option<roughr::Srgba> {
-    // Accept #RRGGBB or #RRGGBBAA
-    let hex = s.trim();
-    let hex = hex.strip_prefix('#')?;
-    let (r, g, b, a) = match hex.len() {
-        6 => {
-            let r = u8::from_str_radix(&hex[0..2], 16).ok()?;
-            let g = u8::from_str_radix(&hex[2..4], 16).ok()?;
-            let b = u8::from_str_radix(&hex[4..6], 16).ok()?;
-            (r, g, b, 255)
-        }
-        8 => {
-            let r = u8::from_str_radix(&hex[0..2], 16).ok()?;
-            let g = u8::from_str_radix(&hex[2..4], 16).ok()?;
-            let b = u8::from_str_radix(&hex[4..6], 16).ok()?;
-            let a = u8::from_str_radix(&hex[6..8], 16).ok()?;
-            (r, g, b, a)
-        }
-        _ => return None,
-    };
-    Some(roughr::Srgba::from_components((
-        r as f32 / 255.0,
-        g as f32 / 255.0,
-        b as f32 / 255.0,
-        a as f32 / 255.0,
-    )))
-}
-
-/// Attenuate the alpha channel of a color by a factor (0.0 to 1.0)
-#[cfg(feature = "rough")]
-fn attenuate_alpha(c: roughr::Srgba, factor: f32) -> roughr::Srgba {
-    let (r, g, b, a) = c.into_components();
-    roughr::Srgba::from_components((r, g, b, a * factor))
-}
-
-/// Build roughr::Options from SVG attributes and config
-#[cfg(feature = "rough")]
-fn build_rough_options_from_attrs(attrs: &SvgAttrs, cfg: &SvgBuildConfig) -> Option<Options> {
-    let mut builder = OptionsBuilder::default();
-    builder.roughness(cfg.rough_options.roughness);
-    builder.bowing(cfg.rough_options.bowing);
-    builder.fill_style(map_fill_style(&cfg.rough_options.fill_style));
-
-    if let Some(stroke) = attrs.stroke.as_ref().and_then(|s| parse_color_to_srgba(s)) {
-        builder.stroke(stroke);
-    }
-    if let Some(mut fill) = attrs.fill.as_ref().and_then(|s| parse_color_to_srgba(s)) {
-        if cfg.rough_style {
-            // Slight attenuation to reduce visual density under text
-            fill = attenuate_alpha(fill, 1.0);
-        }
-        builder.fill(fill);
-    }
-    if let Some(sw) = attrs
-        .stroke_width
-        .as_ref()
-        .and_then(|s| s.parse::<f32>().ok())
-    {
-        builder.stroke_width(sw);
-    }
-
-    builder.build().ok()
-}
-
-#[cfg(feature = "rough")]
-fn drawable_to_path_elements<F: num_traits::Float + euclid::Trig + Display>(
-    drawable: &roughr::core::Drawable<F>,
-    original_attrs: &SvgAttrs,
-) -> Vec<Element> {
-    // Precompute normalized colors and opacities
-    let (fill_color_norm, fill_opacity_attr) =
-        normalize_hex_color_with_opacity(&original_attrs.fill);
-    let (stroke_color_norm, stroke_opacity_attr) =
-        normalize_hex_color_with_opacity(&original_attrs.stroke);
-
-    // Choose hatch color: prefer fill color (represents fill pattern); fallback to stroke; then black
-    let hatch_color = fill_color_norm
-        .clone()
-        .or_else(|| stroke_color_norm.clone())
-        .unwrap_or_else(|| "#000".to_string());
-
-    // Hatch opacity: prefer fill's alpha; fallback to default; allow stroke alpha if no fill alpha
-    let hatch_opacity = fill_opacity_attr
-        .clone()
-        .or_else(|| stroke_opacity_attr.clone())
-        .unwrap_or_else(|| format_opacity(HATCH_DEFAULT_OPACITY));
-
-    let mut out = Vec::new();
-    for set in &drawable.sets {
-        // Build path 'd'
-        let mut d_buf = String::new();
-        for op in &set.ops {
-            use roughr::core::OpType;
-            match op.op {
-                OpType::Move => {
-                    if op.data.len() >= 2 {
-                        d_buf.push_str(&format!("M{},{} ", op.data[0], op.data[1]));
-                    }
-                }
-                OpType::LineTo => {
-                    if op.data.len() >= 2 {
-                        d_buf.push_str(&format!("L{},{} ", op.data[0], op.data[1]));
-                    }
-                }
-                OpType::BCurveTo => {
-                    if op.data.len() >= 6 {
-                        d_buf.push_str(&format!(
-                            "C{},{} {},{} {},{} ",
-                            op.data[0], op.data[1], op.data[2], op.data[3], op.data[4], op.data[5]
-                        ));
-                    }
-                }
-            }
-        }
-        let d_str = d_buf.trim().to_string();
-
-        match set.op_set_type {
-            OpSetType::Path => {
-                // Outline stroke path
-                out.push(rsx! {
-                    path {
-                        id: original_attrs.id.clone(),
-                        class: original_attrs.class.clone(),
-                        d: d_str,
-                        stroke: stroke_color_norm.clone(),
-                        "stroke-width": original_attrs.stroke_width.clone(),
-                        fill: "none",
-                        // apply stroke-opacity if stroke color had alpha
-                        "stroke-opacity": stroke_opacity_attr.clone(),
-                        style: original_attrs.style.clone(),
-                        "data-rough-segment": "stroke"
-                    }
-                });
-            }
-            OpSetType::FillPath => {
-                // Base fill area
-                out.push(rsx! {
-                    path {
-                        id: original_attrs.id.clone(),
-                        class: original_attrs.class.clone(),
-                        d: d_str,
-                        fill: fill_color_norm.clone().or_else(|| Some("none".into())),
-                        stroke: "none",
-                        // apply fill-opacity if fill color had alpha
-                        "fill-opacity": fill_opacity_attr.clone(),
-                        style: original_attrs.style.clone(),
-                        "data-rough-segment": "fill"
-                    }
-                });
-            }
-            OpSetType::FillSketch => {
-                // Hatching strokes — use stroke-opacity for legibility
-                out.push(rsx! {
-                    path {
-                        id: original_attrs.id.clone(),
-                        class: original_attrs.class.clone(),
-                        d: d_str,
-                        stroke: hatch_color.clone(),
-                        "stroke-width": original_attrs.stroke_width.clone(),
-                        "stroke-opacity": hatch_opacity.clone(),
-                        fill: "none",
-                        style: original_attrs.style.clone(),
-                        "data-rough-segment": "hatch"
-                    }
-                });
-            }
-        }
-    }
-    out
-}
-
-#[cfg(feature = "rough")]
-fn rough_path(attrs: &SvgAttrs, cfg: &SvgBuildConfig) -> Option<Vec<Element>> {
-    let d = attrs.d.as_ref()?;
-    let options = build_rough_options_from_attrs(attrs, cfg)?;
-    let gen = Generator::default();
-    let drawable = gen.path::<f32>(d.clone(), &Some(options));
-    Some(drawable_to_path_elements(&drawable, attrs))
-}
-
-#[cfg(feature = "rough")]
-fn rough_rect(attrs: &SvgAttrs, cfg: &SvgBuildConfig) -> Option<Vec<Element>> {
-    let x = attrs.x.as_ref()?.parse::<f32>().ok()?;
-    let y = attrs.y.as_ref()?.parse::<f32>().ok()?;
-    let w = attrs.width.as_ref()?.parse::<f32>().ok()?;
-    let h = attrs.height.as_ref()?.parse::<f32>().ok()?;
-    let options = build_rough_options_from_attrs(attrs, cfg)?;
-    let gen = Generator::default();
-    let drawable = gen.rectangle::<f32>(x, y, w, h, &Some(options));
-    Some(drawable_to_path_elements(&drawable, attrs))
-}
-
-#[cfg(feature = "rough")]
-fn rough_circle(attrs: &SvgAttrs, cfg: &SvgBuildConfig) -> Option<Vec<Element>> {
-    let cx = attrs.cx.as_ref()?.parse::<f32>().ok()?;
-    let cy = attrs.cy.as_ref()?.parse::<f32>().ok()?;
-    let r = attrs.r.as_ref()?.parse::<f32>().ok()?;
-    let diameter = r * 2.0;
-    let options = build_rough_options_from_attrs(attrs, cfg)?;
-    let gen = Generator::default();
-    let drawable = gen.circle::<f32>(cx, cy, diameter, &Some(options));
-    Some(drawable_to_path_elements(&drawable, attrs))
-}
-
-#[cfg(feature = "rough")]
-fn rough_ellipse(attrs: &SvgAttrs, cfg: &SvgBuildConfig) -> Option<Vec<Element>> {
-    let cx = attrs.cx.as_ref()?.parse::<f32>().ok()?;
-    let cy = attrs.cy.as_ref()?.parse::<f32>().ok()?;
-    let rx = attrs.rx.as_ref()?.parse::<f32>().ok()?;
-    let ry = attrs.ry.as_ref()?.parse::<f32>().ok()?;
-    let options = build_rough_options_from_attrs(attrs, cfg)?;
-    let gen = Generator::default();
-    let drawable = gen.ellipse::<f32>(cx, cy, rx * 2.0, ry * 2.0, &Some(options));
-    Some(drawable_to_path_elements(&drawable, attrs))
-}
-
-#[cfg(feature = "rough")]
-fn rough_polygon(attrs: &SvgAttrs, cfg: &SvgBuildConfig) -> Option<Vec<Element>> {
-    let pts_str = attrs.points.as_ref()?;
-    let mut points = Vec::new();
-    for pair in pts_str.split_whitespace() {
-        let (x, y) = pair.split_once(',')?;
-        let px = x.parse::<f32>().ok()?;
-        let py = y.parse::<f32>().ok()?;
-        points.push(roughr::Point2D::new(px, py));
-    }
-    if points.len() < 3 {
-        return None;
-    }
-    let options = build_rough_options_from_attrs(attrs, cfg)?;
-    let gen = Generator::default();
-    let drawable = gen.polygon::<f32>(&points, &Some(options));
-    Some(drawable_to_path_elements(&drawable, attrs))
-}
-
-// ------------------------- Non-rough fallback for path processing -------------------------
-
-#[cfg(not(feature = "rough"))]
-fn rough_path(_attrs: &SvgAttrs, _cfg: &SvgBuildConfig) -> Option<Vec<Element>> {
-    None
-}
-#[cfg(not(feature = "rough"))]
-fn rough_rect(_attrs: &SvgAttrs, _cfg: &SvgBuildConfig) -> Option<Vec<Element>> {
-    None
-}
-#[cfg(not(feature = "rough"))]
-fn rough_circle(_attrs: &SvgAttrs, _cfg: &SvgBuildConfig) -> Option<Vec<Element>> {
-    None
-}
-#[cfg(not(feature = "rough"))]
-fn rough_ellipse(_attrs: &SvgAttrs, _cfg: &SvgBuildConfig) -> Option<Vec<Element>> {
-    None
-}
-#[cfg(not(feature = "rough"))]
-fn rough_polygon(_attrs: &SvgAttrs, _cfg: &SvgBuildConfig) -> Option<Vec<Element>> {
-    None
-}
-
 // ------------------------- DTD strip -------------------------
 
-fn strip_doctype(raw: &str) -> Cow<'_, str> {
+pub(super) fn strip_doctype(raw: &str) -> Cow<'_, str> {
     if !raw.contains("<!DOCTYPE") {
         return Cow::Borrowed(raw);
     }
@@ -574,10 +256,61 @@ fn strip_doctype(raw: &str) -> Cow<'_, str> {
 
 // ------------------------- Component -------------------------
 
+/// Selection state threaded through the build.
+struct Selection<'a> {
+    /// The node, edge or cluster each Graphviz `g` element draws.
+    elements: &'a HashMap<NodeId, GraphElement>,
+    selected: &'a [GraphElement],
+    /// Extra classes for some elements.
+    classes: &'a [(GraphElement, String)],
+    onselect: Option<EventHandler<GraphElement>>,
+}
+
+static NEXT_SVG_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Renders Graphviz SVG output as Dioxus elements.
+///
+/// Nodes, edges and clusters listed in `selected` are outlined, and clicking
+/// one calls `onselect` with it. Those in `classes` get the class given.
+/// Clicking or dragging in the minimap calls `onnavigate` with the point in
+/// SVG units. With `config.animate`, changes between renders are animated.
 #[component]
-pub fn GraphvizSvg(svg_text: String, config: SvgBuildConfig) -> Element {
+pub fn GraphvizSvg(
+    svg_text: String,
+    config: SvgBuildConfig,
+    #[props(default)] selected: Vec<GraphElement>,
+    #[props(default)] classes: Vec<(GraphElement, String)>,
+    #[props(default)] onselect: Option<EventHandler<GraphElement>>,
+    #[props(default)] onnavigate: Option<EventHandler<(f64, f64)>>,
+) -> Element {
     let navigator = use_navigator();
 
+    // Animations need the rendered <svg>, so they start in an effect once the
+    // new SVG is in the page
+    let own_id = use_hook(|| {
+        format!(
+            "graphviz-svg-{}",
+            NEXT_SVG_ID.fetch_add(1, Ordering::Relaxed)
+        )
+    });
+    let config = match (config.animate, &config.svg_id) {
+        (true, None) => SvgBuildConfig {
+            svg_id: Some(own_id),
+            ..config
+        },
+        _ => config,
+    };
+    use_hook(install_animation);
+    let animated_id = config.svg_id.clone().filter(|_| config.animate);
+    use_effect(use_reactive(
+        (&animated_id, &svg_text),
+        move |(animated_id, _)| {
+            if let Some(id) = animated_id {
+                animate(&id);
+            }
+        },
+    ));
+
     let mut cow: Cow<'_, str> = if config.strip_doctype {
         strip_doctype(&svg_text)
     } else {
@@ -603,7 +336,163 @@ pub fn GraphvizSvg(svg_text: String, config: SvgBuildConfig) -> Element {
         return rsx! { svg { class: "graphviz-svg error", "No <svg> root found." } };
     };
 
-    build_node(root, &config, navigator, 0).unwrap_or(rsx! {})
+    // Elements can opt in to rough shapes with a class, see `rough`
+    let rough_text = (config.rough_style || has_rough_classes(root))
+        .then(|| roughen_root(root, &config.rough_options, config.rough_style));
+    let rough_doc = match rough_text.as_deref().map(Document::parse) {
+        Some(Ok(d)) => Some(d),
+        Some(Err(e)) => return render_parse_error(e, true),
+        None => None,
+    };
+    let root = rough_doc.as_ref().map_or(root, |d| d.root_element());
+
+    let elements = graph_elements(root);
+    let selection = Selection {
+        elements: &elements,
+        selected: &selected,
+        classes: &classes,
+        onselect,
+    };
+    let graph = build_node(root, &config, navigator, &selection, 0).unwrap_or(rsx! {});
+
+    let extent = root.attribute("viewBox").and_then(Viewport::parse);
+    let (Some(viewport), Some(extent), true) = (config.viewport, extent, config.minimap) else {
+        return graph;
+    };
+    // The thumbnail is built from the same tree, showing the selection but
+    // without links or click handlers. The graph's own style already sets up
+    // the fonts.
+    let thumbnail_config = SvgBuildConfig {
+        viewport: None,
+        scale_to_fit: true,
+        minimap: false,
+        rough_use_custom_font: false,
+        svg_id: None,
+        ..config.clone()
+    };
+    let thumbnail_selection = Selection {
+        onselect: None,
+        ..selection
+    };
+    let thumbnail =
+        build_node(root, &thumbnail_config, navigator, &thumbnail_selection, 0).unwrap_or(rsx! {});
+
+    rsx! {
+        {graph}
+        Minimap {
+            extent,
+            viewport,
+            onnavigate,
+            {thumbnail}
+        }
+    }
+}
+
+/// A thumbnail of the whole graph with the visible part outlined. Clicking or
+/// dragging reports the point under the pointer in SVG units.
+#[component]
+fn Minimap(
+    extent: Viewport,
+    viewport: Viewport,
+    onnavigate: Option<EventHandler<(f64, f64)>>,
+    children: Element,
+) -> Element {
+    let mut mounted = use_signal(|| None::<Rc<MountedData>>);
+    let mut dragging = use_signal(|| false);
+
+    let navigate = move |point: ClientPoint| {
+        let (Some(handler), Some(element)) = (onnavigate, mounted.peek().clone()) else {
+            return;
+        };
+        spawn(async move {
+            let Ok(rect) = element.get_client_rect().await else {
+                return;
+            };
+            let x = extent.x + (point.x - rect.origin.x) / rect.size.width * extent.width;
+            let y = extent.y + (point.y - rect.origin.y) / rect.size.height * extent.height;
+            handler.call((x, y));
+        });
+    };
+
+    // Fixed width for wide graphs, fixed height for tall ones
+    let size = if extent.width * 3.0 >= extent.height * 4.0 {
+        "width: 12rem;"
+    } else {
+        "height: 9rem;"
+    };
+
+    rsx! {
+        div {
+            class: "absolute bottom-2 right-2 bg-white/90 border border-gray-300 rounded shadow overflow-hidden cursor-pointer",
+            style: "{size} aspect-ratio: {extent.width} / {extent.height};",
+            "data-minimap": "true",
+            onmounted: move |e| mounted.set(Some(e.data())),
+            // Keep the viewer underneath from starting a drag of its own
+            onmousedown: move |e| {
+                e.stop_propagation();
+                dragging.set(true);
+                navigate(e.client_coordinates());
+            },
+            onmousemove: move |e| {
+                if *dragging.peek() {
+                    navigate(e.client_coordinates());
+                }
+            },
+            onmouseup: move |_| dragging.set(false),
+            onmouseleave: move |_| dragging.set(false),
+            div {
+                class: "w-full h-full pointer-events-none",
+                {children}
+            }
+            svg {
+                class: "absolute inset-0 w-full h-full pointer-events-none",
+                view_box: extent.view_box(),
+                "preserveAspectRatio": "none",
+                rect {
+                    x: "{viewport.x}",
+                    y: "{viewport.y}",
+                    width: "{viewport.width}",
+                    height: "{viewport.height}",
+                    fill: "rgba(37, 99, 235, 0.1)",
+                    stroke: "#2563eb",
+                    "stroke-width": "2",
+                    "vector-effect": "non-scaling-stroke",
+                }
+            }
+        }
+    }
+}
+
+/// Identify the `g.node`, `g.edge` and `g.cluster` groups by their `<title>`.
+///
+/// Edges with the same title are told apart by the order they appear in,
+/// which follows the order they were declared in.
+fn graph_elements(root: Node) -> HashMap<NodeId, GraphElement> {
+    let mut edge_counts: HashMap<String, usize> = HashMap::new();
+    root.descendants()
+        .filter(|n| n.has_tag_name("g"))
+        .filter_map(|n| {
+            // Graphviz appends any user `class` after its own
+            let kind = n.attribute("class")?.split_whitespace().next()?;
+            let title = n
+                .children()
+                .find(|c| c.has_tag_name("title"))?
+                .text()?
+                .to_string();
+            let element = match kind {
+                "node" => GraphElement::Node(title),
+                "edge" => {
+                    let count = edge_counts.entry(title.clone()).or_default();
+                    let index = *count;
+                    *count += 1;
+                    GraphElement::Edge { title, index }
+                }
+                "cluster" => GraphElement::Cluster(title),
+                _ => return None,
+            };
+            Some((n.id(), element))
+        })
+        .collect()
 }
 
 fn render_parse_error(err: roxmltree::Error, did_strip: bool) -> Element {
@@ -624,6 +513,7 @@ fn build_node(
     node: Node,
     cfg: &SvgBuildConfig,
     navigator: Navigator,
+    selection: &Selection,
     depth: usize,
 ) -> Option<Element> {
     if depth > MAX_RECURSION_DEPTH {
@@ -651,59 +541,99 @@ fn build_node(
     let attrs = collect_attrs(node);
     let children: Vec<Element> = node
         .children()
-        .filter_map(|c| build_node(c, cfg, navigator, depth + 1))
+        .filter_map(|c| build_node(c, cfg, navigator, selection, depth + 1))
         .collect();
 
-    let link_style = r#"
-    g[data-link-type="external"] text { fill: #1e88e5 !important; }
-    g[data-link-type="internal"] text { fill: #43a047 !important; }
-    g[data-link-type="fragment"] text { fill: #fb8c00 !important; }
-    g[data-link-type] text { text-decoration: underline !important; }
-    g[data-link-type]:hover text { opacity: 0.7; }"#;
-
-    let custom_style = if cfg.rough_style && cfg.rough_use_custom_font {
-        if let Some(css) = cfg.rough_embed_font_data {
-            format!("{css}\nsvg, text, tspan {{ font-family: {ARCHITECTS_DAUGHTER_FAMILY}; }}")
-        } else {
-            format!(
-                "{ARCHITECTS_DAUGHTER_CSS}\nsvg, text, tspan {{ font-family: {ARCHITECTS_DAUGHTER_FAMILY}; }}"
-            )
-        }
-    } else {
-        link_style.to_string()
-    };
-
     let el = match tag {
         "svg" => {
-            let (width, height) = if cfg.scale_to_fit {
+            let font_style = if cfg.rough_style && cfg.rough_use_custom_font {
+                let fonts = cfg.rough_embed_font_data.as_deref().unwrap_or_default();
+                format!(
+                    "{fonts}\nsvg, text, tspan {{ font-family: {}; }}",
+                    font_family()
+                )
+            } else {
+                String::new()
+            };
+            let theme_style = cfg.theme.css();
+
+            let (width, height) = if cfg.scale_to_fit || cfg.viewport.is_some() {
                 (Some("100%".to_string()), Some("100%".to_string()))
             } else {
                 (attrs.width, attrs.height)
             };
+            // When zoomed, keep the natural size around for exports
+            let (view_box, natural) = match &cfg.viewport {
+                Some(viewport) => (
+                    Some(viewport.view_box()),
+                    Some((attrs.view_box, attrs.width, attrs.height)),
+                ),
+                None => (attrs.view_box, None),
+            };
+            let (natural_view_box, natural_width, natural_height) = natural.unwrap_or_default();
 
             rsx! {
                 svg {
-                    id: attrs.id,
+                    id: cfg.svg_id.clone().or(attrs.id),
                     class: attrs.class,
                     width: width,
                     height: height,
-                    view_box: attrs.view_box,
+                    view_box: view_box,
+                    "data-view-box": natural_view_box,
+                    "data-width": natural_width,
+                    "data-height": natural_height,
+                    "data-theme": cfg.theme.name,
                     style: attrs.style,
                     "xmlns": "http://www.w3.org/2000/svg",
                     "xmlns:xlink": XLINK_NS,
-                    style { {custom_style} }
+                    style { {font_style} {theme_style} {SELECTION_STYLE} }
                     for child in children { {child} }
                 }
             }
         }
-        "g" => rsx! {
-            g {
-                id: attrs.id,
-                class: attrs.class,
-                transform: attrs.transform,
-                style: attrs.style,
-                for child in children { {child} }
+        "g" => match selection.elements.get(&node.id()) {
+            Some(element) => {
+                let extra = selection
+                    .classes
+                    .iter()
+                    .filter(|(e, _)| e == element)
+                    .map(|(_, class)| class.as_str())
+                    .chain(selection.selected.contains(element).then_some("selected"));
+                let class = attrs
+                    .class
+                    .iter()
+                    .map(String::as_str)
+                    .chain(extra)
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                let class = (!class.is_empty()).then_some(class);
+                let element = element.clone();
+                let onselect = selection.onselect;
+                rsx! {
+                    g {
+                        id: attrs.id,
+                        class: class,
+                        transform: attrs.transform,
+                        style: attrs.style,
+                        cursor: onselect.is_some().then_some("pointer"),
+                        onclick: move |_| {
+                            if let Some(handler) = onselect {
+                                handler.call(element.clone());
+                            }
+                        },
+                        for child in children { {child} }
+                    }
+                }
             }
+            None => rsx! {
+                g {
+                    id: attrs.id,
+                    class: attrs.class,
+                    transform: attrs.transform,
+                    style: attrs.style,
+                    for child in children { {child} }
+                }
+            },
         },
         "text" => rsx! {
             text {
@@ -735,61 +665,11 @@ fn build_node(
                 rsx! { title { for child in children { {child} } } }
             }
         }
-        "path" => {
-            if cfg.rough_style {
-                if let Some(segments) = rough_path(&attrs, cfg) {
-                    rsx! { g { for seg in segments { {seg} } } }
-                } else {
-                    default_path(&attrs)
-                }
-            } else {
-                default_path(&attrs)
-            }
-        }
-        "rect" => {
-            if cfg.rough_style {
-                if let Some(segments) = rough_rect(&attrs, cfg) {
-                    rsx! { g { for seg in segments { {seg} } for child in children { {child} } } }
-                } else {
-                    default_rect(&attrs, &children)
-                }
-            } else {
-                default_rect(&attrs, &children)
-            }
-        }
-        "circle" => {
-            if cfg.rough_style {
-                if let Some(segments) = rough_circle(&attrs, cfg) {
-                    rsx! { g { for seg in segments { {seg} } } }
-                } else {
-                    default_circle(&attrs)
-                }
-            } else {
-                default_circle(&attrs)
-            }
-        }
-        "ellipse" => {
-            if cfg.rough_style {
-                if let Some(segments) = rough_ellipse(&attrs, cfg) {
-                    rsx! { g { for seg in segments { {seg} } } }
-                } else {
-                    default_ellipse(&attrs)
-                }
-            } else {
-                default_ellipse(&attrs)
-            }
-        }
-        "polygon" => {
-            if cfg.rough_style {
-                if let Some(segments) = rough_polygon(&attrs, cfg) {
-                    rsx! { g { for seg in segments { {seg} } } }
-                } else {
-                    default_polygon(&attrs)
-                }
-            } else {
-                default_polygon(&attrs)
-            }
-        }
+        "path" => default_path(&attrs),
+        "rect" => default_rect(&attrs, &children),
+        "circle" => default_circle(&attrs),
+        "ellipse" => default_ellipse(&attrs),
+        "polygon" => default_polygon(&attrs),
         "polyline" => rsx! {
             polyline {
                 id: attrs.id,
@@ -0,0 +1,654 @@
+//! Graphviz SVG → "rough" hand-drawn SVG, as text.
+//!
+//! [roughen_svg] redraws the paths, rectangles, circles, ellipses and polygons
+//! of a Graphviz SVG with `roughr` and leaves everything else as it was. It has
+//! no Dioxus dependency, so the output can be saved, exported or compared as is;
+//! [super::GraphvizSvg] renders the same output.
+//!
+//! Graphviz copies the DOT `class` attribute of nodes, edges and clusters onto
+//! their `<g>`, so these classes change how one element is drawn:
+//! - `rough` draws it hand-drawn, even when the rest of the graph is not;
+//! - `no-rough` draws it as Graphviz does;
+//! - `rough:fill=<style>`, `rough:roughness=<n>` and `rough:bowing=<n>` draw
+//!   it hand-drawn with that setting, e.g. `class="rough:fill=cross-hatch"`.
+//!
+//! A cluster's class applies to its outline and label, not to its nodes.
+//!
+//! Without the `rough` feature the SVG is only re-serialized.
+use super::render::{strip_doctype, XLINK_NS, XML_NS};
+use roxmltree::{Document, Node};
+
+#[cfg(feature = "rough")]
+use roughr::{
+    core::{Drawable, FillStyle, OpSetType, OpType, Options, OptionsBuilder},
+    generator::Generator,
+};
+
+// Default opacity for hachure strokes when source color has no alpha
+#[cfg(feature = "rough")]
+const HATCH_DEFAULT_OPACITY: f32 = 0.95;
+
+const SVG_NS: &str = "http://www.w3.org/2000/svg";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoughOptions {
+    pub roughness: f32,
+    pub bowing: f32,
+    pub fill_style: RoughFillStyle,
+    /// Combined with each shape's element title to seed its jitter, so a shape
+    /// is drawn the same way every time. Change it for a different drawing.
+    pub seed: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RoughFillStyle {
+    Solid,
+    Hachure,
+    ZigZag,
+    CrossHatch,
+    Dots,
+    Dashed,
+    ZigZagLine,
+}
+
+impl Default for RoughOptions {
+    fn default() -> Self {
+        Self {
+            roughness: 1.0,
+            bowing: 1.0,
+            fill_style: RoughFillStyle::Hachure,
+            seed: 1,
+        }
+    }
+}
+
+/// Named styles offered in the style settings. The default is "whiteboard".
+pub const ROUGH_PRESETS: [(&str, RoughOptions); 3] = [
+    (
+        "whiteboard",
+        RoughOptions {
+            roughness: 1.0,
+            bowing: 1.0,
+            fill_style: RoughFillStyle::Hachure,
+            seed: 1,
+        },
+    ),
+    (
+        "blueprint",
+        RoughOptions {
+            roughness: 0.5,
+            bowing: 0.5,
+            fill_style: RoughFillStyle::CrossHatch,
+            seed: 1,
+        },
+    ),
+    (
+        "sketchy",
+        RoughOptions {
+            roughness: 2.5,
+            bowing: 3.0,
+            fill_style: RoughFillStyle::ZigZag,
+            seed: 1,
+        },
+    ),
+];
+
+impl RoughOptions {
+    /// The preset these options match, whatever their seed.
+    pub fn preset(&self) -> Option<&'static str> {
+        ROUGH_PRESETS
+            .iter()
+            .find(|(_, p)| {
+                p.roughness == self.roughness
+                    && p.bowing == self.bowing
+                    && p.fill_style == self.fill_style
+            })
+            .map(|(name, _)| *name)
+    }
+
+    /// These options as `name = value` lines, for storing next to a file.
+    pub fn to_settings(&self) -> String {
+        format!(
+            "roughness = {}\nbowing = {}\nfill = {}\nseed = {}\n",
+            self.roughness,
+            self.bowing,
+            self.fill_style.name(),
+            self.seed
+        )
+    }
+
+    /// Read back [RoughOptions::to_settings] output. Missing or unreadable
+    /// values keep their defaults.
+    pub fn from_settings(text: &str) -> Self {
+        let mut options = Self::default();
+        for (name, value) in text.lines().filter_map(|line| line.split_once('=')) {
+            let value = value.trim();
+            match name.trim() {
+                "roughness" => options.roughness = value.parse().unwrap_or(options.roughness),
+                "bowing" => options.bowing = value.parse().unwrap_or(options.bowing),
+                "fill" => {
+                    options.fill_style =
+                        RoughFillStyle::from_name(value).unwrap_or(options.fill_style)
+                }
+                "seed" => options.seed = value.parse().unwrap_or(options.seed),
+                _ => {}
+            }
+        }
+        options
+    }
+}
+
+impl RoughFillStyle {
+    pub const ALL: [RoughFillStyle; 7] = [
+        RoughFillStyle::Solid,
+        RoughFillStyle::Hachure,
+        RoughFillStyle::ZigZag,
+        RoughFillStyle::CrossHatch,
+        RoughFillStyle::Dots,
+        RoughFillStyle::Dashed,
+        RoughFillStyle::ZigZagLine,
+    ];
+
+    /// The name used in stored settings.
+    pub fn name(&self) -> &'static str {
+        match self {
+            RoughFillStyle::Solid => "solid",
+            RoughFillStyle::Hachure => "hachure",
+            RoughFillStyle::ZigZag => "zigzag",
+            RoughFillStyle::CrossHatch => "cross-hatch",
+            RoughFillStyle::Dots => "dots",
+            RoughFillStyle::Dashed => "dashed",
+            RoughFillStyle::ZigZagLine => "zigzag-line",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|style| style.name() == name)
+    }
+}
+
+/// Redraw the shapes in Graphviz `svg_text` hand-drawn, returning the new SVG.
+///
+/// Any `<!DOCTYPE>` is dropped. Each rough shape becomes a `<g>` carrying the
+/// shape's `id` and `class`, holding one `<path>` per stroke, fill and hatch
+/// segment, told apart by `data-rough-segment`. Elements with one of the
+/// rough classes from the [module docs](self) get their own style.
+///
+/// The output depends only on the input and `options`: each shape is seeded
+/// from [RoughOptions::seed] and the title of the node, edge or cluster it
+/// belongs to, so editing one part of a graph leaves the rest as drawn.
+pub fn roughen_svg(svg_text: &str, options: &RoughOptions) -> Result<String, roxmltree::Error> {
+    let text = strip_doctype(svg_text);
+    let doc = Document::parse(&text)?;
+    Ok(roughen_root(doc.root_element(), options, true))
+}
+
+/// [roughen_svg] for an already parsed `<svg>` element. With `all` unset,
+/// only elements that opt in with a class are drawn rough.
+pub(super) fn roughen_root(root: Node, options: &RoughOptions, all: bool) -> String {
+    let style = Style {
+        rough: all,
+        options: options.clone(),
+    };
+    let mut out = String::with_capacity(4096);
+    write_node(root, &style, &mut out);
+    out
+}
+
+/// Whether any element under `root` opts in to rough shapes with a class.
+pub(super) fn has_rough_classes(root: Node) -> bool {
+    root.descendants()
+        .filter_map(|n| n.attribute("class"))
+        .flat_map(str::split_whitespace)
+        .any(|token| token == "rough" || token.starts_with("rough:"))
+}
+
+/// How the shapes of an element and its contents are drawn.
+#[derive(Clone)]
+struct Style {
+    rough: bool,
+    options: RoughOptions,
+}
+
+/// The style `node`'s rough classes give it, if it has any.
+fn class_style(node: Node, inherited: &Style) -> Option<Style> {
+    let class = node.attribute("class")?;
+    let mut style: Option<Style> = None;
+    for token in class.split_whitespace() {
+        match token {
+            "rough" => style.get_or_insert_with(|| inherited.clone()).rough = true,
+            "no-rough" => style.get_or_insert_with(|| inherited.clone()).rough = false,
+            _ => {
+                let Some((name, value)) = token
+                    .strip_prefix("rough:")
+                    .and_then(|setting| setting.split_once('='))
+                else {
+                    continue;
+                };
+                let style = style.get_or_insert_with(|| inherited.clone());
+                style.rough = true;
+                let options = &mut style.options;
+                match name {
+                    "fill" => {
+                        options.fill_style =
+                            RoughFillStyle::from_name(value).unwrap_or(options.fill_style)
+                    }
+                    "roughness" => options.roughness = value.parse().unwrap_or(options.roughness),
+                    "bowing" => options.bowing = value.parse().unwrap_or(options.bowing),
+                    _ => {}
+                }
+            }
+        }
+    }
+    style
+}
+
+fn write_node(node: Node, inherited: &Style, out: &mut String) {
+    if node.is_text() {
+        push_escaped(out, node.text().unwrap_or_default(), false);
+        return;
+    }
+    if !node.is_element() {
+        return;
+    }
+    let own = class_style(node, inherited);
+    let style = own.as_ref().unwrap_or(inherited);
+    if style.rough && write_rough_shape(node, style, out) {
+        return;
+    }
+
+    let name = node.tag_name().name();
+    out.push('<');
+    out.push_str(name);
+    if node.parent_element().is_none() {
+        push_attr(out, "xmlns", SVG_NS);
+        push_attr(out, "xmlns:xlink", XLINK_NS);
+    }
+    for a in node.attributes() {
+        let prefix = match a.namespace() {
+            None => None,
+            Some(XLINK_NS) => Some("xlink"),
+            Some(XML_NS) => Some("xml"),
+            Some(uri) => node.lookup_prefix(uri),
+        };
+        match prefix {
+            Some(prefix) => push_attr(out, &format!("{prefix}:{}", a.name()), a.value()),
+            None => push_attr(out, a.name(), a.value()),
+        }
+    }
+    if !node.has_children() {
+        out.push_str("/>");
+        return;
+    }
+    out.push('>');
+    for child in node.children() {
+        write_node(child, style, out);
+    }
+    out.push_str("</");
+    out.push_str(name);
+    out.push('>');
+}
+
+fn push_attr(out: &mut String, name: &str, value: &str) {
+    out.push(' ');
+    out.push_str(name);
+    out.push_str("=\"");
+    push_escaped(out, value, true);
+    out.push('"');
+}
+
+fn push_escaped(out: &mut String, text: &str, in_attr: bool) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' if in_attr => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+}
+
+// ------------------------- Rough conversion core -------------------------
+
+/// Write `node` hand-drawn if it is a shape `roughr` can draw. Returns whether
+/// anything was written.
+#[cfg(feature = "rough")]
+fn write_rough_shape(node: Node, style: &Style, out: &mut String) -> bool {
+    let options = &style.options;
+    let drawable = match node.tag_name().name() {
+        "path" => rough_path(node, options),
+        "rect" => rough_rect(node, options),
+        "circle" => rough_circle(node, options),
+        "ellipse" => rough_ellipse(node, options),
+        "polygon" => rough_polygon(node, options),
+        _ => None,
+    };
+    let Some(drawable) = drawable else {
+        return false;
+    };
+
+    out.push_str("<g");
+    for name in ["id", "class"] {
+        if let Some(value) = node.attribute(name) {
+            push_attr(out, name, value);
+        }
+    }
+    out.push('>');
+    write_drawable(&drawable, node, out);
+    for child in node.children() {
+        write_node(child, style, out);
+    }
+    out.push_str("</g>");
+    true
+}
+
+#[cfg(not(feature = "rough"))]
+fn write_rough_shape(_node: Node, _style: &Style, _out: &mut String) -> bool {
+    false
+}
+
+#[cfg(feature = "rough")]
+fn map_fill_style(fs: &RoughFillStyle) -> FillStyle {
+    match fs {
+        RoughFillStyle::Solid => FillStyle::Solid,
+        RoughFillStyle::Hachure => FillStyle::Hachure,
+        RoughFillStyle::ZigZag => FillStyle::ZigZag,
+        RoughFillStyle::CrossHatch => FillStyle::CrossHatch,
+        RoughFillStyle::Dots => FillStyle::Dots,
+        RoughFillStyle::Dashed => FillStyle::Dashed,
+        RoughFillStyle::ZigZagLine => FillStyle::ZigZagLine,
+    }
+}
+
+#[cfg(feature = "rough")]
+fn parse_color_to_srgba(s: &str) -> Option<roughr::Srgba> {
+    // Accept #RRGGBB or #RRGGBBAA
+    let hex = s.trim();
+    let hex = hex.strip_prefix('#')?;
+    let (r, g, b, a) = match hex.len() {
+        6 => {
+            let r = u8::from_str_radix(&hex[0..2], 16).ok()?;
+            let g = u8::from_str_radix(&hex[2..4], 16).ok()?;
+            let b = u8::from_str_radix(&hex[4..6], 16).ok()?;
+            (r, g, b, 255)
+        }
+        8 => {
+            let r = u8::from_str_radix(&hex[0..2], 16).ok()?;
+            let g = u8::from_str_radix(&hex[2..4], 16).ok()?;
+            let b = u8::from_str_radix(&hex[4..6], 16).ok()?;
+            let a = u8::from_str_radix(&hex[6..8], 16).ok()?;
+            (r, g, b, a)
+        }
+        _ => return None,
+    };
+    Some(roughr::Srgba::from_components((
+        r as f32 / 255.0,
+        g as f32 / 255.0,
+        b as f32 / 255.0,
+        a as f32 / 255.0,
+    )))
+}
+
+/// Attenuate the alpha channel of a color by a factor (0.0 to 1.0)
+#[cfg(feature = "rough")]
+fn attenuate_alpha(c: roughr::Srgba, factor: f32) -> roughr::Srgba {
+    let (r, g, b, a) = c.into_components();
+    roughr::Srgba::from_components((r, g, b, a * factor))
+}
+
+/// Build roughr::Options from a shape's attributes and the rough options
+#[cfg(feature = "rough")]
+fn build_rough_options(node: Node, options: &RoughOptions) -> Option<Options> {
+    let mut builder = OptionsBuilder::default();
+    builder.roughness(options.roughness);
+    builder.bowing(options.bowing);
+    builder.fill_style(map_fill_style(&options.fill_style));
+    builder.seed(shape_seed(node, options.seed));
+
+    if let Some(stroke) = node.attribute("stroke").and_then(parse_color_to_srgba) {
+        builder.stroke(stroke);
+    }
+    if let Some(fill) = node.attribute("fill").and_then(parse_color_to_srgba) {
+        // Slight attenuation to reduce visual density under text
+        builder.fill(attenuate_alpha(fill, 1.0));
+    }
+    if let Some(sw) = node
+        .attribute("stroke-width")
+        .and_then(|s| s.parse::<f32>().ok())
+    {
+        builder.stroke_width(sw);
+    }
+
+    builder.build().ok()
+}
+
+/// A seed for `node` that survives edits elsewhere in the graph: Graphviz
+/// numbers its `id`s by position, so the seed comes from the `<title>` of the
+/// enclosing node, edge or cluster group and the shape's place in it.
+#[cfg(feature = "rough")]
+fn shape_seed(node: Node, seed: u64) -> u64 {
+    let group = node
+        .ancestors()
+        .skip(1)
+        .find(|n| n.has_tag_name("g") && n.children().any(|c| c.has_tag_name("title")));
+    let Some(group) = group else {
+        return seed;
+    };
+    let title = group
+        .children()
+        .find(|c| c.has_tag_name("title"))
+        .and_then(|t| t.text())
+        .unwrap_or_default();
+    let position = group
+        .descendants()
+        .filter(|n| n.is_element())
+        .position(|n| n == node)
+        .unwrap_or_default();
+
+    // FNV-1a, which unlike std's hasher is stable across Rust releases
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325 ^ seed;
+    for byte in title.bytes().chain((position as u64).to_le_bytes()) {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+#[cfg(feature = "rough")]
+fn format_opacity(v: f32) -> String {
+    // Trim to at most 3 decimals and strip trailing zeros/dot.
+    let s = format!("{:.3}", v.clamp(0.0, 1.0));
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    if s.is_empty() {
+        "0".into()
+    } else {
+        s.into()
+    }
+}
+
+/// Normalize a color into (#RRGGBB, Some(opacity)) if it is #RRGGBBAA; pass through otherwise.
+/// Returns (normalized_color, optional_opacity_string).
+#[cfg(feature = "rough")]
+fn normalize_hex_color_with_opacity(input: Option<&str>) -> (Option<String>, Option<String>) {
+    let Some(raw) = input else {
+        return (None, None);
+    };
+    let s = raw.trim();
+    if !s.starts_with('#') {
+        return (Some(raw.to_string()), None);
+    }
+    let hex = &s[1..];
+    match hex.len() {
+        6 => (Some(raw.to_string()), None),
+        8 => {
+            if let (Ok(a), Ok(_)) = (
+                u8::from_str_radix(&hex[6..8], 16),
+                u32::from_str_radix(&hex[0..6], 16),
+            ) {
+                let rgb = format!("#{}", &hex[0..6]);
+                let op = (a as f32) / 255.0;
+                (Some(rgb), Some(format_opacity(op)))
+            } else {
+                (Some(raw.to_string()), None)
+            }
+        }
+        _ => (Some(raw.to_string()), None),
+    }
+}
+
+#[cfg(feature = "rough")]
+fn write_drawable(drawable: &Drawable<f32>, original: Node, out: &mut String) {
+    // Precompute normalized colors and opacities
+    let (fill_color_norm, fill_opacity_attr) =
+        normalize_hex_color_with_opacity(original.attribute("fill"));
+    let (stroke_color_norm, stroke_opacity_attr) =
+        normalize_hex_color_with_opacity(original.attribute("stroke"));
+    let stroke_width = original.attribute("stroke-width");
+    let style = original.attribute("style");
+
+    // Choose hatch color: prefer fill color (represents fill pattern); fallback to stroke; then black
+    let hatch_color = fill_color_norm
+        .clone()
+        .or_else(|| stroke_color_norm.clone())
+        .unwrap_or_else(|| "#000".to_string());
+
+    // Hatch opacity: prefer fill's alpha; fallback to default; allow stroke alpha if no fill alpha
+    let hatch_opacity = fill_opacity_attr
+        .clone()
+        .or_else(|| stroke_opacity_attr.clone())
+        .unwrap_or_else(|| format_opacity(HATCH_DEFAULT_OPACITY));
+
+    for set in &drawable.sets {
+        // Build path 'd'
+        let mut d_buf = String::new();
+        for op in &set.ops {
+            match op.op {
+                OpType::Move => {
+                    if op.data.len() >= 2 {
+                        d_buf.push_str(&format!("M{},{} ", op.data[0], op.data[1]));
+                    }
+                }
+                OpType::LineTo => {
+                    if op.data.len() >= 2 {
+                        d_buf.push_str(&format!("L{},{} ", op.data[0], op.data[1]));
+                    }
+                }
+                OpType::BCurveTo => {
+                    if op.data.len() >= 6 {
+                        d_buf.push_str(&format!(
+                            "C{},{} {},{} {},{} ",
+                            op.data[0], op.data[1], op.data[2], op.data[3], op.data[4], op.data[5]
+                        ));
+                    }
+                }
+            }
+        }
+
+        let attrs: [(&str, Option<&str>); 6] = match set.op_set_type {
+            // Outline stroke path; stroke-opacity applies if the stroke color had alpha
+            OpSetType::Path => [
+                ("stroke", stroke_color_norm.as_deref()),
+                ("stroke-width", stroke_width),
+                ("stroke-opacity", stroke_opacity_attr.as_deref()),
+                ("fill", Some("none")),
+                ("fill-opacity", None),
+                ("data-rough-segment", Some("stroke")),
+            ],
+            // Base fill area; fill-opacity applies if the fill color had alpha
+            OpSetType::FillPath => [
+                ("stroke", Some("none")),
+                ("stroke-width", None),
+                ("stroke-opacity", None),
+                ("fill", Some(fill_color_norm.as_deref().unwrap_or("none"))),
+                ("fill-opacity", fill_opacity_attr.as_deref()),
+                ("data-rough-segment", Some("fill")),
+            ],
+            // Hatching strokes — use stroke-opacity for legibility
+            OpSetType::FillSketch => [
+                ("stroke", Some(hatch_color.as_str())),
+                ("stroke-width", stroke_width),
+                ("stroke-opacity", Some(hatch_opacity.as_str())),
+                ("fill", Some("none")),
+                ("fill-opacity", None),
+                ("data-rough-segment", Some("hatch")),
+            ],
+        };
+
+        out.push_str("<path");
+        push_attr(out, "d", d_buf.trim());
+        for (name, value) in attrs {
+            if let Some(value) = value {
+                push_attr(out, name, value);
+            }
+        }
+        if let Some(style) = style {
+            push_attr(out, "style", style);
+        }
+        out.push_str("/>");
+    }
+}
+
+#[cfg(feature = "rough")]
+fn number(node: Node, name: &str) -> Option<f32> {
+    node.attribute(name)?.parse::<f32>().ok()
+}
+
+#[cfg(feature = "rough")]
+fn rough_path(node: Node, options: &RoughOptions) -> Option<Drawable<f32>> {
+    let d = node.attribute("d")?;
+    let options = build_rough_options(node, options)?;
+    let gen = Generator::default();
+    Some(gen.path::<f32>(d.to_string(), &Some(options)))
+}
+
+#[cfg(feature = "rough")]
+fn rough_rect(node: Node, options: &RoughOptions) -> Option<Drawable<f32>> {
+    let x = number(node, "x")?;
+    let y = number(node, "y")?;
+    let w = number(node, "width")?;
+    let h = number(node, "height")?;
+    let options = build_rough_options(node, options)?;
+    let gen = Generator::default();
+    Some(gen.rectangle::<f32>(x, y, w, h, &Some(options)))
+}
+
+#[cfg(feature = "rough")]
+fn rough_circle(node: Node, options: &RoughOptions) -> Option<Drawable<f32>> {
+    let cx = number(node, "cx")?;
+    let cy = number(node, "cy")?;
+    let r = number(node, "r")?;
+    let diameter = r * 2.0;
+    let options = build_rough_options(node, options)?;
+    let gen = Generator::default();
+    Some(gen.circle::<f32>(cx, cy, diameter, &Some(options)))
+}
+
+#[cfg(feature = "rough")]
+fn rough_ellipse(node: Node, options: &RoughOptions) -> Option<Drawable<f32>> {
+    let cx = number(node, "cx")?;
+    let cy = number(node, "cy")?;
+    let rx = number(node, "rx")?;
+    let ry = number(node, "ry")?;
+    let options = build_rough_options(node, options)?;
+    let gen = Generator::default();
+    Some(gen.ellipse::<f32>(cx, cy, rx * 2.0, ry * 2.0, &Some(options)))
+}
+
+#[cfg(feature = "rough")]
+fn rough_polygon(node: Node, options: &RoughOptions) -> Option<Drawable<f32>> {
+    let pts_str = node.attribute("points")?;
+    let mut points = Vec::new();
+    for pair in pts_str.split_whitespace() {
+        let (x, y) = pair.split_once(',')?;
+        let px = x.parse::<f32>().ok()?;
+        let py = y.parse::<f32>().ok()?;
+        points.push(roughr::Point2D::new(px, py));
+    }
+    if points.len() < 3 {
+        return None;
+    }
+    let options = build_rough_options(node, options)?;
+    let gen = Generator::default();
+    Some(gen.polygon::<f32>(&points, &Some(options)))
+}
@@ -0,0 +1,96 @@
+//! Menu for exporting the rendered graph.
+use dioxus::prelude::*;
+
+use crate::export::{export_key, export_svg, save, ExportFormat};
+use crate::StorageProvider;
+
+const FORMATS: [(&str, ExportFormat); 5] = [
+    ("SVG", ExportFormat::Svg),
+    ("PNG 1×", ExportFormat::Png { scale: 1.0 }),
+    ("PNG 2×", ExportFormat::Png { scale: 2.0 }),
+    ("PNG 4×", ExportFormat::Png { scale: 4.0 }),
+    ("PDF (raster)", ExportFormat::Pdf),
+];
+
+/// A dropdown that exports the `<svg>` matched by `target` as SVG, PNG or PDF.
+/// PDFs hold a JPEG image of the graph rather than vector shapes, which the
+/// menu says.
+///
+/// Files are named after `file_key`, the storage key of the DOT file. On the web
+/// they are downloaded; on desktop they are saved next to the DOT file.
+#[component]
+pub fn ExportMenu(
+    /// CSS selector of the rendered `<svg>`.
+    target: String,
+    /// Storage key of the DOT file being shown.
+    file_key: String,
+) -> Element {
+    let storage = use_context::<StorageProvider>();
+    let mut open = use_signal(|| false);
+    let mut busy = use_signal(|| false);
+    let mut status = use_signal(|| None::<Result<String, String>>);
+
+    rsx! {
+        div {
+            class: "relative text-xs",
+            button {
+                class: "px-2 py-1 bg-gray-200 hover:bg-gray-300 rounded disabled:opacity-50",
+                aria_haspopup: "menu",
+                aria_expanded: open(),
+                disabled: busy(),
+                onclick: move |_| open.toggle(),
+                if busy() { "Exporting…" } else { "Export ▾" }
+            }
+            if open() {
+                ul {
+                    class: "absolute left-0 mt-1 w-28 bg-white border border-gray-300 rounded shadow-lg",
+                    role: "menu",
+                    for (label, format) in FORMATS {
+                        li {
+                            key: "{label}",
+                            class: "px-2 py-1 cursor-pointer hover:bg-gray-100",
+                            role: "menuitem",
+                            onclick: {
+                                let storage = storage.clone();
+                                let target = target.clone();
+                                let key = export_key(&file_key, format);
+                                move |_| {
+                                    open.set(false);
+                                    busy.set(true);
+                                    let storage = storage.clone();
+                                    let target = target.clone();
+                                    let key = key.clone();
+                                    spawn(async move {
+                                        let result = export_svg(&target, format)
+                                            .await
+                                            .and_then(|data| save(&storage, &key, format, &data));
+                                        busy.set(false);
+                                        status.set(Some(result));
+                                    });
+                                }
+                            },
+                            "{label}"
+                        }
+                    }
+                }
+            }
+            {match status() {
+                Some(Ok(message)) => rsx! {
+                    div {
+                        class: "mt-1 px-2 py-1 bg-green-50 text-green-700 rounded shadow cursor-pointer",
+                        onclick: move |_| status.set(None),
+                        "{message}"
+                    }
+                },
+                Some(Err(message)) => rsx! {
+                    div {
+                        class: "mt-1 px-2 py-1 bg-red-50 text-red-700 rounded shadow cursor-pointer",
+                        onclick: move |_| status.set(None),
+                        "{message}"
+                    }
+                },
+                None => rsx! {},
+            }}
+        }
+    }
+}
@@ -0,0 +1,403 @@
+//! Sidebar listing the DOT documents in storage.
+use std::collections::HashSet;
+
+use dioxus::prelude::*;
+
+use crate::components::StandaloneDotDisplay;
+use crate::documents::{
+    delete_document, document_key, document_path, duplicate_document, entries, rename_document,
+    Entry,
+};
+use crate::platform;
+use crate::{PreloadComplete, StorageProvider};
+
+/// A collapsible sidebar listing every DOT document in storage, in folders
+/// for `/`-separated keys, with search and thumbnails.
+///
+/// Documents are opened at `/:key_path`, the path both the web and desktop
+/// routers show them at; `route` is the current route, used to highlight the
+/// open document and to follow it when it's renamed or deleted.
+#[component]
+pub fn FileBrowser<R: Routable + Clone + PartialEq>(route: R) -> Element {
+    let storage = use_context::<StorageProvider>();
+    let navigator = use_navigator();
+    let preload_complete = try_use_context::<PreloadComplete>();
+
+    let mut collapsed = use_signal(|| false);
+    let mut thumbnails = use_signal(|| true);
+    let mut query = use_signal(String::new);
+    let mut closed = use_signal(HashSet::<String>::new);
+    // Bumped to list storage again
+    let mut refresh = use_signal(|| 0u64);
+    let mut creating = use_signal(|| None::<String>);
+    let mut renaming = use_signal(|| None::<(String, String)>);
+    let mut deleting = use_signal(|| None::<String>);
+    let mut status = use_signal(|| None::<String>);
+
+    let path = route.to_string();
+    let current = url_escape::decode(path.trim_start_matches('/')).to_string();
+
+    // The page just opened may create its document once it has loaded, so
+    // look again shortly after each navigation
+    use_effect(use_reactive((&path,), move |_| {
+        spawn(async move {
+            platform::sleep(std::time::Duration::from_millis(300)).await;
+            refresh += 1;
+        });
+    }));
+
+    let list_storage = storage.clone();
+    let listing = use_resource(move || {
+        let storage = list_storage.clone();
+        let _ = refresh();
+        if let Some(preloaded) = preload_complete {
+            let _ = preloaded();
+        }
+        async move {
+            storage.asynchronous().list("").await.unwrap_or_else(|err| {
+                error!("Failed to list documents: {err}");
+                Vec::new()
+            })
+        }
+    });
+    let keys = listing.read().clone().unwrap_or_default();
+    let rows = entries(&keys, &query(), &closed.read());
+    let count = keys.iter().filter(|key| key.ends_with(".dot")).count();
+
+    if collapsed() {
+        return rsx! {
+            div {
+                class: "w-8 flex flex-col items-center border-r border-gray-200 bg-gray-50",
+                button {
+                    class: "mt-2 px-1 text-gray-500 hover:text-gray-800",
+                    title: "Show documents",
+                    aria_expanded: false,
+                    onclick: move |_| collapsed.set(false),
+                    "»"
+                }
+            }
+        };
+    }
+
+    let create_storage = storage.clone();
+    let mut create = move |name: String| {
+        let Some(key) = document_key(&name) else {
+            status.set(Some("Enter a name for the document".to_string()));
+            return;
+        };
+        if create_storage.exists(&key) {
+            status.set(Some(format!("{key} already exists")));
+            return;
+        }
+        creating.set(None);
+        status.set(None);
+        // The graph view creates the document when it finds nothing stored
+        navigator.push(document_path(&key).as_str());
+    };
+
+    let rename_storage = storage.clone();
+    let rename_current = current.clone();
+    let rename = move |from: String, name: String| {
+        let Some(to) = document_key(&name) else {
+            status.set(Some("Enter a name for the document".to_string()));
+            return;
+        };
+        renaming.set(None);
+        if to == from {
+            return;
+        }
+        match rename_document(&rename_storage, &from, &to) {
+            Ok(()) => {
+                status.set(None);
+                if from == rename_current {
+                    navigator.replace(document_path(&to).as_str());
+                }
+            }
+            Err(err) => status.set(Some(err.to_string())),
+        }
+        refresh += 1;
+    };
+
+    rsx! {
+        aside {
+            class: "w-64 shrink-0 flex flex-col border-r border-gray-200 bg-gray-50 text-sm",
+            aria_label: "Documents",
+            div {
+                class: "flex items-center gap-1 p-2 border-b border-gray-200",
+                span { class: "flex-1 font-semibold text-gray-700", "Documents ({count})" }
+                button {
+                    class: "px-2 py-1 text-xs bg-gray-200 hover:bg-gray-300 rounded",
+                    title: "New document",
+                    onclick: move |_| creating.set(Some(String::new())),
+                    "+"
+                }
+                button {
+                    class: "px-2 py-1 text-xs bg-gray-200 hover:bg-gray-300 rounded",
+                    title: if thumbnails() { "Hide thumbnails" } else { "Show thumbnails" },
+                    aria_pressed: thumbnails(),
+                    onclick: move |_| thumbnails.toggle(),
+                    "▦"
+                }
+                button {
+                    class: "px-2 py-1 text-xs text-gray-500 hover:text-gray-800",
+                    title: "Hide documents",
+                    aria_expanded: true,
+                    onclick: move |_| collapsed.set(true),
+                    "«"
+                }
+            }
+            div {
+                class: "p-2 flex flex-col gap-2 border-b border-gray-200",
+                input {
+                    class: "w-full px-2 py-1 text-xs border border-gray-300 rounded",
+                    r#type: "search",
+                    placeholder: "Search documents…",
+                    value: query(),
+                    oninput: move |e| query.set(e.value()),
+                }
+                if let Some(name) = creating() {
+                    form {
+                        class: "flex gap-1",
+                        onsubmit: move |e| {
+                            e.prevent_default();
+                            create(creating.peek().clone().unwrap_or_default());
+                        },
+                        input {
+                            class: "flex-1 min-w-0 px-2 py-1 text-xs border border-gray-300 rounded",
+                            placeholder: "folder/name.dot",
+                            autofocus: true,
+                            value: name,
+                            oninput: move |e| creating.set(Some(e.value())),
+                            onkeydown: move |e| {
+                                if e.key() == Key::Escape {
+                                    creating.set(None);
+                                }
+                            },
+                        }
+                        button {
+                            class: "px-2 py-1 text-xs bg-blue-600 hover:bg-blue-700 text-white rounded",
+                            r#type: "submit",
+                            "Create"
+                        }
+                    }
+                }
+                if let Some(message) = status() {
+                    p {
+                        class: "px-2 py-1 text-xs bg-red-50 text-red-700 rounded cursor-pointer",
+                        onclick: move |_| status.set(None),
+                        "{message}"
+                    }
+                }
+            }
+            ul {
+                class: "flex-1 overflow-auto p-1",
+                if rows.is_empty() {
+                    li {
+                        class: "p-2 text-xs text-gray-500",
+                        if query().is_empty() { "No documents yet" } else { "No matching documents" }
+                    }
+                }
+                for row in rows {
+                    {match row {
+                        Entry::Folder { path, name, depth } => rsx! {
+                            li {
+                                key: "folder:{path}",
+                                class: "group flex items-center gap-1 px-1 py-0.5 rounded hover:bg-gray-200",
+                                style: "padding-left: {depth * 12 + 4}px",
+                                button {
+                                    class: "flex-1 text-left text-gray-700 truncate",
+                                    aria_expanded: !closed.read().contains(&path),
+                                    onclick: {
+                                        let path = path.clone();
+                                        move |_| {
+                                            let mut closed = closed.write();
+                                            if !closed.remove(&path) {
+                                                closed.insert(path.clone());
+                                            }
+                                        }
+                                    },
+                                    if closed.read().contains(&path) { "▸ " } else { "▾ " }
+                                    "{name}"
+                                }
+                                button {
+                                    class: "hidden group-hover:block px-1 text-xs text-gray-500 hover:text-gray-800",
+                                    title: "New document in {path}",
+                                    onclick: {
+                                        let path = path.clone();
+                                        move |_| creating.set(Some(format!("{path}/")))
+                                    },
+                                    "+"
+                                }
+                            }
+                        },
+                        Entry::Document { key, name, depth } => {
+                            let is_current = key == current;
+                            let renamed = renaming().filter(|(from, _)| *from == key);
+                            let confirm_delete = deleting().as_ref() == Some(&key);
+                            let duplicate_storage = storage.clone();
+                            let delete_storage = storage.clone();
+                            let delete_current = current.clone();
+                            let mut rename = rename.clone();
+                            rsx! {
+                                li {
+                                    key: "doc:{key}",
+                                    class: if is_current {
+                                        "group flex flex-col gap-1 px-1 py-1 rounded bg-blue-100"
+                                    } else {
+                                        "group flex flex-col gap-1 px-1 py-1 rounded hover:bg-gray-200"
+                                    },
+                                    style: "padding-left: {depth * 12 + 4}px",
+                                    if let Some((from, value)) = renamed {
+                                        form {
+                                            class: "flex gap-1",
+                                            onsubmit: move |e| {
+                                                e.prevent_default();
+                                                let name = renaming.peek().clone().map(|(_, name)| name).unwrap_or_default();
+                                                rename(from.clone(), name);
+                                            },
+                                            input {
+                                                class: "flex-1 min-w-0 px-2 py-1 text-xs border border-gray-300 rounded",
+                                                autofocus: true,
+                                                value: value,
+                                                oninput: {
+                                                    let key = key.clone();
+                                                    move |e: FormEvent| renaming.set(Some((key.clone(), e.value())))
+                                                },
+                                                onkeydown: move |e| {
+                                                    if e.key() == Key::Escape {
+                                                        renaming.set(None);
+                                                    }
+                                                },
+                                            }
+                                            button {
+                                                class: "px-2 py-1 text-xs bg-blue-600 hover:bg-blue-700 text-white rounded",
+                                                r#type: "submit",
+                                                "Rename"
+                                            }
+                                        }
+                                    } else {
+                                        div {
+                                            class: "flex items-center gap-1",
+                                            button {
+                                                class: "flex-1 text-left truncate text-gray-800",
+                                                title: "{key}",
+                                                aria_current: if is_current { "page" } else { "false" },
+                                                onclick: {
+                                                    let key = key.clone();
+                                                    move |_| {
+                                                        navigator.push(document_path(&key).as_str());
+                                                    }
+                                                },
+                                                "{name}"
+                                            }
+                                            div {
+                                                class: "hidden group-hover:flex gap-1 text-xs text-gray-500",
+                                                button {
+                                                    class: "hover:text-gray-800",
+                                                    title: "Rename",
+                                                    onclick: {
+                                                        let key = key.clone();
+                                                        move |_| {
+                                                            deleting.set(None);
+                                                            renaming.set(Some((key.clone(), key.clone())));
+                                                        }
+                                                    },
+                                                    "✎"
+                                                }
+                                                button {
+                                                    class: "hover:text-gray-800",
+                                                    title: "Duplicate",
+                                                    onclick: {
+                                                        let key = key.clone();
+                                                        move |_| {
+                                                            match duplicate_document(&duplicate_storage, &key) {
+                                                                Ok(_) => status.set(None),
+                                                                Err(err) => status.set(Some(err.to_string())),
+                                                            }
+                                                            refresh += 1;
+                                                        }
+                                                    },
+                                                    "⧉"
+                                                }
+                                                button {
+                                                    class: "hover:text-red-700",
+                                                    title: "Delete",
+                                                    onclick: {
+                                                        let key = key.clone();
+                                                        move |_| {
+                                                            renaming.set(None);
+                                                            deleting.set(Some(key.clone()));
+                                                        }
+                                                    },
+                                                    "🗑"
+                                                }
+                                            }
+                                        }
+                                    }
+                                    if confirm_delete {
+                                        div {
+                                            class: "flex items-center gap-1 text-xs",
+                                            span { class: "flex-1 text-red-700", "Delete {name}?" }
+                                            button {
+                                                class: "px-2 py-0.5 bg-red-600 hover:bg-red-700 text-white rounded",
+                                                onclick: {
+                                                    let key = key.clone();
+                                                    move |_| {
+                                                        deleting.set(None);
+                                                        match delete_document(&delete_storage, &key) {
+                                                            Ok(()) => {
+                                                                status.set(None);
+                                                                if key == delete_current {
+                                                                    navigator.replace("/");
+                                                                }
+                                                            }
+                                                            Err(err) => status.set(Some(err.to_string())),
+                                                        }
+                                                        refresh += 1;
+                                                    }
+                                                },
+                                                "Delete"
+                                            }
+                                            button {
+                                                class: "px-2 py-0.5 bg-gray-200 hover:bg-gray-300 rounded",
+                                                onclick: move |_| deleting.set(None),
+                                                "Cancel"
+                                            }
+                                        }
+                                    }
+                                    if thumbnails() {
+                                        Thumbnail { key: key.clone(), version: refresh() }
+                                    }
+                                }
+                            }
+                        }
+                    }}
+                }
+            }
+        }
+    }
+}
+
+/// The graph stored under `key`, drawn small. Loaded once it is shown, and
+/// again when `version` changes.
+#[component]
+fn Thumbnail(key: String, version: u64) -> Element {
+    let storage = use_context::<StorageProvider>();
+    let dot = use_resource(use_reactive((&key, &version), move |(key, _)| {
+        let storage = storage.clone();
+        async move {
+            let data = storage.asynchronous().load(&key).await.ok()?;
+            Some(String::from_utf8_lossy(&data).to_string())
+        }
+    }));
+    let Some(Some(dot)) = dot() else {
+        return rsx! { div { class: "w-full h-20 bg-white border border-gray-200 rounded" } };
+    };
+    rsx! {
+        StandaloneDotDisplay {
+            dot,
+            scale_to_fit: true,
+            class: "w-full h-20 bg-white border border-gray-200 rounded overflow-hidden pointer-events-none",
+        }
+    }
+}
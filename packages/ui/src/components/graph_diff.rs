@@ -0,0 +1,90 @@
+//! Visual diff of two DOT sources.
+use dioxus::prelude::*;
+use dot_lang::diff::{diff, ChangeKind};
+
+use crate::components::DotDisplay;
+use crate::error::RenderError;
+use crate::theme::Theme;
+
+// Covers the plain shapes and the groups of paths rough shapes are drawn with,
+// like the selection outline.
+const DIFF_STYLE: &str = r#"
+    g.diff-added path, g.diff-added polygon, g.diff-added ellipse, g.diff-added polyline { stroke: #16a34a !important; stroke-width: 2.5px; }
+    g.diff-added text { fill: #16a34a !important; }
+    g.diff-removed path, g.diff-removed polygon, g.diff-removed ellipse, g.diff-removed polyline { stroke: #dc2626 !important; stroke-width: 2.5px; stroke-dasharray: 6 3; }
+    g.diff-removed text { fill: #dc2626 !important; text-decoration: line-through; }
+    g.diff-removed { opacity: 0.7; }
+    g.diff-changed path, g.diff-changed polygon, g.diff-changed ellipse, g.diff-changed polyline { stroke: #d97706 !important; stroke-width: 2.5px; }"#;
+
+fn class_of(kind: ChangeKind) -> &'static str {
+    match kind {
+        ChangeKind::Added => "diff-added",
+        ChangeKind::Removed => "diff-removed",
+        ChangeKind::Changed => "diff-changed",
+    }
+}
+
+/// Renders `new` with the nodes, edges and clusters removed since `old` put
+/// back, marking additions in green, removals in red and changes in amber,
+/// above a list of the changes.
+#[component]
+pub fn GraphDiff(
+    old: String,
+    new: String,
+    #[props(default)] rough: bool,
+    #[props(default)] theme: Theme,
+) -> Element {
+    let error_signal = use_signal(|| None::<RenderError>);
+
+    let old_parse = dot_lang::parse(&old);
+    let new_parse = dot_lang::parse(&new);
+    let diff = diff(&old_parse.document, &new_parse.document);
+    let preview = diff.preview(&new);
+    let classes: Vec<_> = preview
+        .marks
+        .into_iter()
+        .map(|(element, kind)| (element, class_of(kind).to_string()))
+        .collect();
+    let incomplete = !old_parse.is_ok() || !new_parse.is_ok();
+
+    rsx! {
+        style { {DIFF_STYLE} }
+        div {
+            class: "flex flex-col h-full",
+            div {
+                class: "max-h-40 overflow-auto border-b border-gray-200 bg-gray-50 p-2 pt-10 font-mono text-xs",
+                if incomplete {
+                    p {
+                        class: "text-gray-500 mb-1",
+                        "Syntax errors: the diff may be incomplete."
+                    }
+                }
+                if diff.is_empty() {
+                    p { class: "text-gray-500", "No changes" }
+                }
+                for (i, change) in diff.changes.iter().enumerate() {
+                    p {
+                        key: "{i}",
+                        class: match change.kind {
+                            ChangeKind::Added => "text-green-700",
+                            ChangeKind::Removed => "text-red-700",
+                            ChangeKind::Changed => "text-amber-700",
+                        },
+                        "{change}"
+                    }
+                }
+            }
+            div {
+                class: "flex-1 min-h-0",
+                style: "background: {theme.background};",
+                DotDisplay {
+                    dot: preview.source,
+                    error_signal,
+                    rough,
+                    theme,
+                    classes,
+                }
+            }
+        }
+    }
+}
@@ -1,5 +1,26 @@
 mod dot_display;
-pub use dot_display::{fonts, DotDisplay, GraphvizSvg, SvgBuildConfig};
+pub use dot_display::{
+    fonts, roughen_svg, DotDisplay, GraphvizSvg, LinkKind, PanZoomSvg, RoughFillStyle,
+    RoughOptions, SvgBuildConfig, Viewport, ROUGH_PRESETS,
+};
+
+mod rough_settings;
+pub use rough_settings::RoughSettings;
+
+mod theme_picker;
+pub use theme_picker::ThemePicker;
+
+mod graph_diff;
+pub use graph_diff::GraphDiff;
+
+mod file_browser;
+pub use file_browser::FileBrowser;
+
+mod revision_timeline;
+pub use revision_timeline::RevisionTimeline;
+
+mod export_menu;
+pub use export_menu::ExportMenu;
 
 mod error_overlay;
 pub use error_overlay::ErrorOverlay;
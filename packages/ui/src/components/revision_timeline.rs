@@ -0,0 +1,113 @@
+//! Browser for the saved revisions of a graph.
+use dioxus::prelude::*;
+
+use crate::components::StandaloneDotDisplay;
+use crate::platform;
+use crate::revisions::{load_revisions, Revision};
+use crate::StorageProvider;
+
+/// How long before `now_ms` `timestamp_ms` was, e.g. "5 min ago".
+fn ago(timestamp_ms: u64, now_ms: u64) -> String {
+    let secs = now_ms.saturating_sub(timestamp_ms) / 1000;
+    if secs < 60 {
+        "just now".to_string()
+    } else if secs < 60 * 60 {
+        format!("{} min ago", secs / 60)
+    } else if secs < 24 * 60 * 60 {
+        format!("{} h ago", secs / (60 * 60))
+    } else {
+        format!("{} days ago", secs / (24 * 60 * 60))
+    }
+}
+
+/// A dropdown listing the saved revisions of the graph stored under
+/// `file_key`, newest first. Choosing one previews it; restoring it calls
+/// `onrestore` with its source.
+///
+/// Revisions are read from storage each time the dropdown opens.
+#[component]
+pub fn RevisionTimeline(
+    /// Storage key of the DOT file being shown.
+    file_key: String,
+    onrestore: EventHandler<String>,
+) -> Element {
+    let storage = use_context::<StorageProvider>();
+    let mut open = use_signal(|| false);
+    // Newest first
+    let mut revisions = use_signal(Vec::<Revision>::new);
+    let mut chosen = use_signal(|| None::<usize>);
+    let now = platform::now_millis();
+
+    let toggle = move |_| {
+        if !open() {
+            let (storage, file_key) = (storage.clone(), file_key.clone());
+            spawn(async move {
+                let log = load_revisions(&storage, &file_key).await;
+                revisions.set(log.revisions().iter().rev().cloned().collect());
+            });
+            chosen.set(None);
+        }
+        open.toggle();
+    };
+    let preview = chosen().and_then(|i| revisions.read().get(i).cloned());
+
+    rsx! {
+        div {
+            class: "relative text-xs",
+            button {
+                class: "px-2 py-1 bg-gray-200 hover:bg-gray-300 rounded",
+                aria_haspopup: "dialog",
+                aria_expanded: open(),
+                onclick: toggle,
+                "History ▾"
+            }
+            if open() {
+                div {
+                    class: "absolute left-0 mt-1 w-96 p-2 flex flex-col gap-2 bg-white border border-gray-300 rounded shadow-lg",
+                    role: "dialog",
+                    aria_label: "Revision history",
+                    if revisions.read().is_empty() {
+                        p { class: "text-gray-500", "No saved revisions yet" }
+                    }
+                    ol {
+                        class: "max-h-48 overflow-auto",
+                        for (i, revision) in revisions.read().iter().enumerate() {
+                            li {
+                                key: "{revision.timestamp_ms}",
+                                button {
+                                    class: if chosen() == Some(i) {
+                                        "w-full px-2 py-1 flex justify-between bg-blue-600 text-white rounded"
+                                    } else {
+                                        "w-full px-2 py-1 flex justify-between hover:bg-gray-100 rounded"
+                                    },
+                                    aria_pressed: chosen() == Some(i),
+                                    onclick: move |_| chosen.set(Some(i)),
+                                    span {
+                                        if i == 0 { "Latest, " }
+                                        "{ago(revision.timestamp_ms, now)}"
+                                    }
+                                    span { "{revision.content.len()} bytes" }
+                                }
+                            }
+                        }
+                    }
+                    if let Some(revision) = preview {
+                        StandaloneDotDisplay {
+                            dot: revision.content.clone(),
+                            scale_to_fit: true,
+                            class: "w-full h-48 border border-gray-200 rounded overflow-hidden",
+                        }
+                        button {
+                            class: "px-2 py-1 bg-blue-600 hover:bg-blue-700 text-white rounded",
+                            onclick: move |_| {
+                                onrestore.call(revision.content.clone());
+                                open.set(false);
+                            },
+                            "Restore this revision"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
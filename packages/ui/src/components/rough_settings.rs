@@ -0,0 +1,118 @@
+//! Panel for tuning the rough hand-drawn style.
+use dioxus::prelude::*;
+
+use crate::components::{RoughFillStyle, RoughOptions, ROUGH_PRESETS};
+
+/// A dropdown panel editing `options`: presets, roughness and bowing sliders,
+/// the fill style and a button to draw the graph again with another seed.
+///
+/// Changes are written straight to `options`, so a preview reading it updates
+/// as the sliders move.
+#[component]
+pub fn RoughSettings(mut options: Signal<RoughOptions>) -> Element {
+    let mut open = use_signal(|| false);
+    let current = options();
+    let preset = current.preset();
+    let label = preset.unwrap_or("custom");
+
+    rsx! {
+        div {
+            class: "relative text-xs",
+            button {
+                class: "px-2 py-1 bg-gray-200 hover:bg-gray-300 rounded",
+                aria_haspopup: "dialog",
+                aria_expanded: open(),
+                onclick: move |_| open.toggle(),
+                "Style: {label} ▾"
+            }
+            if open() {
+                div {
+                    class: "absolute left-0 mt-1 w-56 p-2 flex flex-col gap-2 bg-white border border-gray-300 rounded shadow-lg",
+                    role: "dialog",
+                    aria_label: "Rough style",
+                    div {
+                        class: "flex gap-1",
+                        for (name, preset_options) in ROUGH_PRESETS {
+                            button {
+                                key: "{name}",
+                                class: if preset == Some(name) {
+                                    "flex-1 px-1 py-1 bg-blue-600 text-white rounded"
+                                } else {
+                                    "flex-1 px-1 py-1 bg-gray-100 hover:bg-gray-200 rounded"
+                                },
+                                aria_pressed: preset == Some(name),
+                                // Keep the seed, so the preset only changes the style
+                                onclick: move |_| {
+                                    let seed = options.peek().seed;
+                                    options.set(RoughOptions {
+                                        seed,
+                                        ..preset_options.clone()
+                                    });
+                                },
+                                "{name}"
+                            }
+                        }
+                    }
+                    label {
+                        class: "flex flex-col",
+                        "Roughness: {current.roughness}"
+                        input {
+                            r#type: "range",
+                            min: "0",
+                            max: "5",
+                            step: "0.1",
+                            value: "{current.roughness}",
+                            oninput: move |e| {
+                                if let Ok(value) = e.value().parse() {
+                                    options.write().roughness = value;
+                                }
+                            },
+                        }
+                    }
+                    label {
+                        class: "flex flex-col",
+                        "Bowing: {current.bowing}"
+                        input {
+                            r#type: "range",
+                            min: "0",
+                            max: "10",
+                            step: "0.5",
+                            value: "{current.bowing}",
+                            oninput: move |e| {
+                                if let Ok(value) = e.value().parse() {
+                                    options.write().bowing = value;
+                                }
+                            },
+                        }
+                    }
+                    label {
+                        class: "flex flex-col",
+                        "Fill"
+                        select {
+                            class: "border border-gray-300 rounded",
+                            onchange: move |e| {
+                                if let Some(style) = RoughFillStyle::from_name(&e.value()) {
+                                    options.write().fill_style = style;
+                                }
+                            },
+                            for style in RoughFillStyle::ALL {
+                                option {
+                                    key: "{style.name()}",
+                                    value: style.name(),
+                                    selected: style == current.fill_style,
+                                    "{style.name()}"
+                                }
+                            }
+                        }
+                    }
+                    button {
+                        class: "px-2 py-1 bg-gray-100 hover:bg-gray-200 rounded",
+                        title: "Draw the graph again with different jitter",
+                        onclick: move |_| options.write().seed += 1,
+                        "Redraw"
+                    }
+                }
+            }
+        }
+    }
+}
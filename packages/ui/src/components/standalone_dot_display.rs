@@ -1,8 +1,9 @@
 //! A simplified version of `DotDisplay` for standalone, non-editable graph rendering.
 use dioxus::prelude::*;
 
-use crate::components::dot_display::{GraphvizSvg, SvgBuildConfig};
+use crate::components::dot_display::{GraphvizSvg, PanZoomSvg, SvgBuildConfig};
 use crate::error::Error as UiError;
+use crate::hooks::use_theme;
 use crate::GVizProvider;
 
 /// Renders a DOT string into a self-contained, interactive SVG.
@@ -12,14 +13,18 @@ use crate::GVizProvider;
 /// It reuses `GraphvizSvg` to ensure interactivity like clickable nodes is preserved.
 #[component]
 pub fn StandaloneDotDisplay(
-    /// The DOT graph string to render.
-    dot: String,
+    /// The DOT graph string to render. The graph is rendered again when it
+    /// changes.
+    dot: ReadSignal<String>,
     /// Apply a "hand-drawn" aesthetic to the SVG. Defaults to `false`.
     #[props(default = false)]
     rough_style: bool,
     /// Make the SVG scale to fit its container. Defaults to `false`.
     #[props(default = false)]
     scale_to_fit: bool,
+    /// Show zoom and pan controls instead of a static image. Defaults to `false`.
+    #[props(default = false)]
+    pan_zoom: bool,
     /// Optional CSS classes to apply to the container `div`.
     #[props(default)]
     class: String,
@@ -31,37 +36,44 @@ pub fn StandaloneDotDisplay(
     };
 
     let gviz_signal = use_context::<Signal<Option<GVizProvider>>>();
+    let theme = use_theme();
 
-    // Memoize the expensive rendering process. The closure captures the `gviz_signal`
-    // and `dot` string, both of which have a `'static` lifetime, satisfying
-    // the hook's requirements. The memo will re-run if `gviz_signal` changes.
-    let svg_result = use_memo(move || {
-        if let Some(gviz) = gviz_signal.read().as_ref() {
-            gviz.render_dot(&dot)
-        } else {
-            Err(UiError::GvizNotInitialized)
-        }
+    // Render in a task, restarted if `gviz_signal` or `dot` changes, which
+    // drops the render of the previous source.
+    let svg_result = use_resource(move || async move {
+        let Some(gviz) = gviz_signal() else {
+            return Err(UiError::GvizNotInitialized);
+        };
+        gviz.render_dot(&dot()).await
     });
 
-    match svg_result() {
+    match svg_result().unwrap_or(Err(UiError::GvizNotInitialized)) {
         Ok(svg) => {
             let config = SvgBuildConfig {
                 rough_style,
                 scale_to_fit,
+                theme,
                 ..Default::default()
             };
             rsx! {
                 div {
                     class: "{container_class}",
-                    GraphvizSvg {
-                        svg_text: svg,
-                        config: config
+                    if pan_zoom {
+                        PanZoomSvg {
+                            svg_text: svg,
+                            config: config
+                        }
+                    } else {
+                        GraphvizSvg {
+                            svg_text: svg,
+                            config: config
+                        }
                     }
                 }
             }
         }
         Err(UiError::GvizNotInitialized) => {
-            // Graphviz engine is not yet available.
+            // Graphviz engine is not yet available, or still rendering.
             rsx! {
                 div {
                     class: "{container_class} flex items-center justify-center",
@@ -0,0 +1,38 @@
+//! Navbar control for choosing the graph theme.
+use dioxus::prelude::*;
+
+use crate::theme::{Theme, THEMES};
+
+/// A select for the theme provided by
+/// [use_theme_provider](crate::hooks::use_theme_provider). Renders nothing
+/// when there is none.
+#[component]
+pub fn ThemePicker() -> Element {
+    let Some(mut theme) = try_use_context::<Signal<Theme>>() else {
+        return rsx! {};
+    };
+    let current = theme();
+
+    rsx! {
+        label {
+            class: "flex items-center gap-2 text-sm text-neutral-200",
+            "Theme"
+            select {
+                class: "px-1 rounded text-neutral-800 bg-neutral-100",
+                onchange: move |e| {
+                    if let Some(chosen) = Theme::by_name(&e.value()) {
+                        theme.set(chosen);
+                    }
+                },
+                for choice in THEMES {
+                    option {
+                        key: "{choice.name}",
+                        value: choice.name,
+                        selected: choice == current,
+                        "{choice.name}"
+                    }
+                }
+            }
+        }
+    }
+}
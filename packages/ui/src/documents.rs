@@ -0,0 +1,159 @@
+//! The DOT documents in storage, as the file browser shows and changes them.
+//!
+//! Documents are the `.dot` keys; `/` in a key puts the document in a folder.
+//! Each document's rough settings and revisions are kept under other keys,
+//! see [sidecar_keys], which follow it when it is renamed or deleted.
+use std::collections::HashSet;
+
+use crate::hooks::rough_settings_key;
+use crate::revisions::revisions_key;
+use crate::{StorageError, StorageProvider};
+
+/// One row of the file browser, indented by `depth`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Entry {
+    /// A folder, i.e. a `/`-separated prefix shared by document keys.
+    Folder {
+        path: String,
+        name: String,
+        depth: usize,
+    },
+    Document {
+        key: String,
+        name: String,
+        depth: usize,
+    },
+}
+
+/// The rows for the `.dot` documents among `keys`, folders before what they
+/// contain. Documents whose key doesn't contain `query` are left out, as are
+/// the contents of `closed` folders unless searching.
+pub fn entries(keys: &[String], query: &str, closed: &HashSet<String>) -> Vec<Entry> {
+    let query = query.trim().to_lowercase();
+    let mut keys: Vec<&String> = keys
+        .iter()
+        .filter(|key| key.ends_with(".dot"))
+        .filter(|key| key.to_lowercase().contains(&query))
+        .collect();
+    // Comparing segments keeps each folder's contents together
+    keys.sort_by(|a, b| a.split('/').cmp(b.split('/')));
+
+    let mut rows = Vec::new();
+    let mut previous: Vec<&str> = Vec::new();
+    for key in keys {
+        let segments: Vec<&str> = key.split('/').collect();
+        let Some((name, folders)) = segments.split_last() else {
+            continue;
+        };
+        let hidden = |depth: usize| {
+            query.is_empty() && (1..=depth).any(|i| closed.contains(&folders[..i].join("/")))
+        };
+        let shared = previous
+            .iter()
+            .zip(folders)
+            .take_while(|(a, b)| a == b)
+            .count();
+        for depth in shared..folders.len() {
+            if !hidden(depth) {
+                rows.push(Entry::Folder {
+                    path: folders[..=depth].join("/"),
+                    name: folders[depth].to_string(),
+                    depth,
+                });
+            }
+        }
+        if !hidden(folders.len()) {
+            rows.push(Entry::Document {
+                key: key.clone(),
+                name: name.to_string(),
+                depth: folders.len(),
+            });
+        }
+        previous = folders.to_vec();
+    }
+    rows
+}
+
+/// The key for a document named `name`, e.g. `diagrams/flow` becomes
+/// `diagrams/flow.dot`. `None` for names with a blank segment, or one starting
+/// with `.`: `.` and `..` would leave the folder, and hidden folders hold
+/// settings and history.
+pub fn document_key(name: &str) -> Option<String> {
+    let name = name.trim().trim_matches('/');
+    let valid = |segment: &str| !segment.trim().is_empty() && !segment.starts_with('.');
+    if !name.split('/').all(valid) {
+        return None;
+    }
+    Some(if name.ends_with(".dot") {
+        name.to_string()
+    } else {
+        format!("{name}.dot")
+    })
+}
+
+/// The router path showing the document stored under `key`. Keys are one
+/// `/:key_path` segment, so their slashes are escaped.
+pub fn document_path(key: &str) -> String {
+    format!("/{}", url_escape::encode_component(key))
+}
+
+/// The storage keys kept alongside a document, which follow it around.
+pub fn sidecar_keys(key: &str) -> [String; 2] {
+    [rough_settings_key(key), revisions_key(key)]
+}
+
+/// Rename the document stored under `from`, with its rough settings and
+/// revisions. Nothing is moved if any of their new keys is taken.
+pub fn rename_document(
+    storage: &StorageProvider,
+    from: &str,
+    to: &str,
+) -> Result<(), StorageError> {
+    let sidecars = sidecar_keys(from)
+        .into_iter()
+        .zip(sidecar_keys(to))
+        .filter(|(old, _)| storage.exists(old));
+    let moves: Vec<(String, String)> = [(from.to_string(), to.to_string())]
+        .into_iter()
+        .chain(sidecars)
+        .collect();
+    if let Some((_, taken)) = moves.iter().find(|(_, new)| storage.exists(new)) {
+        return Err(StorageError::AlreadyExists(taken.clone()));
+    }
+    for (old, new) in &moves {
+        storage.rename(old, new)?;
+    }
+    Ok(())
+}
+
+/// Copy the document stored under `key`, with its rough settings, to the first
+/// free "<name> copy.dot" next to it. Returns the key of the copy.
+pub fn duplicate_document(storage: &StorageProvider, key: &str) -> Result<String, StorageError> {
+    let stem = key.strip_suffix(".dot").unwrap_or(key);
+    let copy = (1..)
+        .map(|n| match n {
+            1 => format!("{stem} copy.dot"),
+            n => format!("{stem} copy {n}.dot"),
+        })
+        .find(|candidate| !storage.exists(candidate))
+        .expect("a free name");
+    storage.save(&copy, &storage.load(key)?)?;
+    if let Ok(settings) = storage.load(&rough_settings_key(key)) {
+        storage.save(&rough_settings_key(&copy), &settings)?;
+    }
+    Ok(copy)
+}
+
+/// Delete the document stored under `key`, with its rough settings and
+/// revisions.
+pub fn delete_document(storage: &StorageProvider, key: &str) -> Result<(), StorageError> {
+    storage.delete(key)?;
+    for sidecar in sidecar_keys(key) {
+        match storage.delete(&sidecar) {
+            // Never saved
+            Err(StorageError::NotFound(_)) => {}
+            result => result?,
+        }
+    }
+    Ok(())
+}
@@ -9,6 +9,10 @@ pub enum Error {
     #[error("Storage failure: {0}")]
     StorageFailure(&'static str),
 
+    /// A failed storage operation
+    #[error(transparent)]
+    Storage(#[from] crate::StorageError),
+
     /// Generic I/O error
     #[error("I/O error: {0}")]
     Io(String),
@@ -41,6 +45,39 @@ pub enum ErrorLevel {
     Error,
 }
 
+impl From<&dot_lang::ParseError> for ErrorInfo {
+    fn from(err: &dot_lang::ParseError) -> Self {
+        ErrorInfo {
+            level: ErrorLevel::Error,
+            message: err.message.clone(),
+            line: Some(err.line()),
+        }
+    }
+}
+
+impl From<&dot_lang::lint::Diagnostic> for ErrorInfo {
+    fn from(diagnostic: &dot_lang::lint::Diagnostic) -> Self {
+        use dot_lang::lint::Severity;
+        ErrorInfo {
+            level: match diagnostic.severity {
+                Severity::Error => ErrorLevel::Error,
+                Severity::Warning => ErrorLevel::Warning,
+                Severity::Info => ErrorLevel::Info,
+            },
+            message: diagnostic.message.clone(),
+            line: Some(diagnostic.line()),
+        }
+    }
+}
+
+impl From<Vec<dot_lang::ParseError>> for RenderError {
+    fn from(errors: Vec<dot_lang::ParseError>) -> Self {
+        RenderError {
+            errors: errors.iter().map(ErrorInfo::from).collect(),
+        }
+    }
+}
+
 impl std::error::Error for RenderError {}
 
 impl fmt::Display for RenderError {
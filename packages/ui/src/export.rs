@@ -0,0 +1,295 @@
+//! Exporting rendered graphs as SVG, PNG and PDF files.
+//!
+//! The export starts from the `<svg>` in the page rather than from the
+//! Graphviz output, so it includes the rough styling. The browser does the
+//! rasterizing; PDFs wrap a JPEG rendering in a single page.
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use dioxus::prelude::*;
+
+use crate::StorageProvider;
+
+/// Scale of the raster image embedded in PDFs.
+const PDF_SCALE: f64 = 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Svg,
+    /// A PNG at `scale` times the graph's natural size.
+    Png {
+        scale: f64,
+    },
+    Pdf,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Svg => "svg",
+            ExportFormat::Png { .. } => "png",
+            ExportFormat::Pdf => "pdf",
+        }
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Svg => "image/svg+xml",
+            ExportFormat::Png { .. } => "image/png",
+            ExportFormat::Pdf => "application/pdf",
+        }
+    }
+}
+
+/// The storage key for exporting the graph stored under `dot_key`:
+/// `diagrams/flow.dot` becomes `diagrams/flow.png`.
+pub fn export_key(dot_key: &str, format: ExportFormat) -> String {
+    let stem = dot_key.strip_suffix(".dot").unwrap_or(dot_key);
+    format!("{stem}.{}", format.extension())
+}
+
+/// Export the `<svg>` matched by the CSS `selector` as `format`.
+pub async fn export_svg(selector: &str, format: ExportFormat) -> Result<Vec<u8>, String> {
+    let selector = selector.replace('\\', "\\\\").replace('\'', "\\'");
+    match format {
+        ExportFormat::Svg => {
+            let text = document::eval(&script(&format!("return await svgText('{selector}');")))
+                .join::<Option<String>>()
+                .await
+                .map_err(failed)?
+                .ok_or(NOTHING)?;
+            Ok(text.into_bytes())
+        }
+        ExportFormat::Png { scale } => {
+            let (data, _, _) = rasterize(&selector, scale, "image/png").await?;
+            decode(&data)
+        }
+        ExportFormat::Pdf => {
+            let (data, width, height) = rasterize(&selector, PDF_SCALE, "image/jpeg").await?;
+            let jpeg = decode(&data)?;
+            // The page has the graph's natural size; 1 CSS pixel is 0.75pt
+            let points = |px: u32| px as f64 / PDF_SCALE * 0.75;
+            Ok(jpeg_pdf(
+                &jpeg,
+                width,
+                height,
+                points(width),
+                points(height),
+            ))
+        }
+    }
+}
+
+const NOTHING: &str = "Nothing to export";
+
+fn script(call: &str) -> String {
+    format!("{EXPORT_JS}\n{call}")
+}
+
+fn failed(err: document::EvalError) -> String {
+    format!("Export failed: {err:?}")
+}
+
+/// Rasterize the `<svg>` matched by `selector`; returns the base64 image data
+/// and its size in pixels.
+async fn rasterize(
+    selector: &str,
+    scale: f64,
+    mime_type: &str,
+) -> Result<(String, u32, u32), String> {
+    document::eval(&script(&format!(
+        "return await rasterize(await svgText('{selector}'), {scale}, '{mime_type}');"
+    )))
+    .join::<Option<(String, u32, u32)>>()
+    .await
+    .map_err(failed)?
+    .ok_or_else(|| NOTHING.to_string())
+}
+
+fn decode(data: &str) -> Result<Vec<u8>, String> {
+    STANDARD
+        .decode(data)
+        .map_err(|err| format!("Failed to decode export: {err:?}"))
+}
+
+/// Save an export: as a download on the web, and next to the DOT file in
+/// `storage` elsewhere. Returns a message saying where it went.
+pub fn save(
+    storage: &StorageProvider,
+    key: &str,
+    format: ExportFormat,
+    data: &[u8],
+) -> Result<String, String> {
+    #[cfg(target_arch = "wasm32")]
+    {
+        let _ = storage;
+        download(key, format.mime_type(), data);
+        Ok(format!("Downloaded {key}"))
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let _ = format;
+        storage.save(key, data).map_err(|e| e.to_string())?;
+        Ok(format!("Saved {key}"))
+    }
+}
+
+/// Offer `data` as a download named after the last segment of `key`.
+#[cfg(target_arch = "wasm32")]
+fn download(key: &str, mime_type: &str, data: &[u8]) {
+    let name = key.rsplit('/').next().unwrap_or(key).replace('\'', "\\'");
+    let encoded = STANDARD.encode(data);
+    document::eval(&format!(
+        r#"const bytes = Uint8Array.from(atob('{encoded}'), c => c.charCodeAt(0));
+        const url = URL.createObjectURL(new Blob([bytes], {{ type: '{mime_type}' }}));
+        const link = document.createElement('a');
+        link.href = url;
+        link.download = '{name}';
+        link.click();
+        setTimeout(() => URL.revokeObjectURL(url), 1000);"#
+    ));
+}
+
+/// A single-page PDF showing a JPEG image of `width` x `height` pixels on a
+/// page of `page_width` x `page_height` points.
+pub fn jpeg_pdf(
+    jpeg: &[u8],
+    width: u32,
+    height: u32,
+    page_width: f64,
+    page_height: f64,
+) -> Vec<u8> {
+    let contents = format!("q {page_width:.2} 0 0 {page_height:.2} 0 0 cm /Im0 Do Q");
+    let objects: Vec<Vec<u8>> = vec![
+        b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+        b"<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_vec(),
+        format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {page_width:.2} {page_height:.2}] \
+             /Resources << /XObject << /Im0 4 0 R >> >> /Contents 5 0 R >>"
+        )
+        .into_bytes(),
+        stream(
+            &format!(
+                "/Type /XObject /Subtype /Image /Width {width} /Height {height} \
+                 /ColorSpace /DeviceRGB /BitsPerComponent 8 /Filter /DCTDecode"
+            ),
+            jpeg,
+        ),
+        stream("", contents.as_bytes()),
+    ];
+
+    let mut pdf = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n", i + 1).as_bytes());
+        pdf.extend_from_slice(object);
+        pdf.extend_from_slice(b"\nendobj\n");
+    }
+    let xref = pdf.len();
+    pdf.extend_from_slice(
+        format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes(),
+    );
+    for offset in offsets {
+        pdf.extend_from_slice(format!("{offset:010} 00000 n \n").as_bytes());
+    }
+    pdf.extend_from_slice(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+            objects.len() + 1
+        )
+        .as_bytes(),
+    );
+    pdf
+}
+
+fn stream(dict: &str, data: &[u8]) -> Vec<u8> {
+    let mut out = format!("<< {dict} /Length {} >>\nstream\n", data.len()).into_bytes();
+    out.extend_from_slice(data);
+    out.extend_from_slice(b"\nendstream");
+    out
+}
+
+/// Browser side of the export.
+///
+/// `svgText` serializes a copy of the rendered SVG at its full size, without
+/// editor state such as the selection, and with web fonts inlined so the file
+/// renders the same anywhere. `rasterize` draws SVG text onto a canvas and
+/// returns `[base64, width, height]`.
+const EXPORT_JS: &str = r#"
+async function dataUrl(url) {
+    return await blobDataUrl(await (await fetch(url)).blob());
+}
+
+async function blobDataUrl(blob) {
+    return await new Promise((resolve, reject) => {
+        const reader = new FileReader();
+        reader.onload = () => resolve(reader.result);
+        reader.onerror = reject;
+        reader.readAsDataURL(blob);
+    });
+}
+
+async function inlineFonts(css) {
+    const imports = [...css.matchAll(/@import url\(['"]?([^'")]+)['"]?\);?/g)];
+    for (const [rule, href] of imports) {
+        try {
+            let fontCss = await (await fetch(href)).text();
+            for (const [, font] of [...fontCss.matchAll(/url\(([^)]+)\)/g)]) {
+                fontCss = fontCss.replace(font, await dataUrl(font.replace(/['"]/g, '')));
+            }
+            css = css.replace(rule, fontCss);
+        } catch (err) {
+            console.warn('Keeping font import, could not inline', href, err);
+        }
+    }
+    return css;
+}
+
+async function svgText(selector) {
+    const svg = document.querySelector(selector);
+    if (!svg) return null;
+    const copy = svg.cloneNode(true);
+    // While zoomed, the natural size is kept in data attributes
+    const natural = svg.dataset;
+    if (natural.viewBox) copy.setAttribute('viewBox', natural.viewBox);
+    if (natural.width) copy.setAttribute('width', natural.width);
+    if (natural.height) copy.setAttribute('height', natural.height);
+    for (const el of [copy, ...copy.querySelectorAll('*')]) {
+        for (const name of ['data-dioxus-id', 'data-view-box', 'data-width', 'data-height']) {
+            el.removeAttribute(name);
+        }
+        el.classList.remove('selected');
+        if (el.getAttribute('class') === '') el.removeAttribute('class');
+    }
+    for (const style of copy.querySelectorAll('style')) {
+        style.textContent = await inlineFonts(style.textContent);
+    }
+    return '<?xml version="1.0" encoding="UTF-8"?>\n' + new XMLSerializer().serializeToString(copy);
+}
+
+async function rasterize(svg, scale, type) {
+    if (!svg) return null;
+    const url = URL.createObjectURL(new Blob([svg], { type: 'image/svg+xml' }));
+    try {
+        const img = new Image();
+        await new Promise((resolve, reject) => {
+            img.onload = resolve;
+            img.onerror = reject;
+            img.src = url;
+        });
+        const canvas = document.createElement('canvas');
+        canvas.width = Math.ceil(img.width * scale);
+        canvas.height = Math.ceil(img.height * scale);
+        const ctx = canvas.getContext('2d');
+        if (type === 'image/jpeg') {
+            ctx.fillStyle = '#fff';
+            ctx.fillRect(0, 0, canvas.width, canvas.height);
+        }
+        ctx.drawImage(img, 0, 0, canvas.width, canvas.height);
+        const blob = await new Promise(resolve => canvas.toBlob(resolve, type, 0.92));
+        const data = await blobDataUrl(blob);
+        return [data.slice(data.indexOf(',') + 1), canvas.width, canvas.height];
+    } finally {
+        URL.revokeObjectURL(url);
+    }
+}
+"#;
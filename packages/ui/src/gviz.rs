@@ -1,25 +1,43 @@
 //! This module defines the trait details for managing data.
 use std::sync::Arc;
 
+use crate::LocalBoxFuture;
+
 pub trait GraphVizable {
     type Error;
     fn render_dot(&self, dot: &str) -> Result<String, Self::Error>;
 }
 
+/// [GraphVizable] for renderers that answer asynchronously, e.g. from a
+/// worker. Every [GraphVizable] is one as well, rendering when first polled.
+pub trait AsyncGraphVizable {
+    type Error;
+    fn render_dot<'a>(&'a self, dot: &'a str) -> LocalBoxFuture<'a, Result<String, Self::Error>>;
+}
+
+impl<G: GraphVizable + ?Sized> AsyncGraphVizable for G {
+    type Error = G::Error;
+    fn render_dot<'a>(&'a self, dot: &'a str) -> LocalBoxFuture<'a, Result<String, Self::Error>> {
+        Box::pin(async move { GraphVizable::render_dot(self, dot) })
+    }
+}
+
 // A storage provider context that wraps any storage implementation
 #[derive(Clone)]
 pub struct GVizProvider {
-    inner: Arc<dyn GraphVizable<Error = crate::Error>>,
+    inner: Arc<dyn AsyncGraphVizable<Error = crate::Error>>,
 }
 
 impl GVizProvider {
-    pub fn new<G: GraphVizable<Error = crate::Error> + 'static>(graphviz: G) -> Self {
+    pub fn new<G: AsyncGraphVizable<Error = crate::Error> + 'static>(graphviz: G) -> Self {
         Self {
             inner: Arc::new(graphviz),
         }
     }
 
-    pub fn render_dot(&self, dot: &str) -> Result<String, crate::Error> {
-        self.inner.render_dot(dot)
+    /// Render `dot` to SVG. Dropping the future gives up on the render,
+    /// where the renderer allows it.
+    pub async fn render_dot(&self, dot: &str) -> Result<String, crate::Error> {
+        self.inner.render_dot(dot).await
     }
 }
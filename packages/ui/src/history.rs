@@ -0,0 +1,110 @@
+//! Undo and redo for the editor.
+//!
+//! [History] keeps whole sources rather than diffs, and groups keystrokes
+//! into one step until the user pauses. It holds no clock: callers pass the
+//! time of each keystroke.
+use std::collections::VecDeque;
+
+/// How many edits can be undone.
+pub const HISTORY_LIMIT: usize = 200;
+
+/// Typing after a pause this long, in milliseconds, starts a new undo step.
+pub const TYPING_PAUSE_MS: u64 = 1000;
+
+/// One undo or redo step.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryEntry {
+    /// What the step did, e.g. "Typing" or "Format".
+    pub label: String,
+    /// The source on the other side of the step: before it on the undo
+    /// stack, after it on the redo stack.
+    pub source: String,
+}
+
+/// Bounded undo and redo stacks of whole sources.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct History {
+    undo: VecDeque<HistoryEntry>,
+    redo: Vec<HistoryEntry>,
+    /// When the last keystroke of the typing burst on top of the undo stack
+    /// was, if that burst can still grow.
+    typed_at: Option<u64>,
+}
+
+impl History {
+    const TYPING: &'static str = "Typing";
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// The label of the step [History::undo] would undo.
+    pub fn undo_label(&self) -> Option<&str> {
+        self.undo.back().map(|entry| entry.label.as_str())
+    }
+
+    /// The label of the step [History::redo] would redo.
+    pub fn redo_label(&self) -> Option<&str> {
+        self.redo.last().map(|entry| entry.label.as_str())
+    }
+
+    /// Note a keystroke at `now_ms` that changed `before`. It joins the typing
+    /// burst in progress unless [TYPING_PAUSE_MS] or more have passed since
+    /// the last one.
+    pub fn record_typing(&mut self, before: &str, now_ms: u64) {
+        let joins = self
+            .typed_at
+            .is_some_and(|at| now_ms.saturating_sub(at) < TYPING_PAUSE_MS);
+        if joins {
+            self.redo.clear();
+        } else {
+            self.push(before, Self::TYPING);
+        }
+        self.typed_at = Some(now_ms);
+    }
+
+    /// Note an edit of `before` named `label`, e.g. "Format". It is always a
+    /// step of its own and ends any typing burst.
+    pub fn record(&mut self, before: &str, label: &str) {
+        self.push(before, label);
+    }
+
+    fn push(&mut self, before: &str, label: &str) {
+        self.redo.clear();
+        self.typed_at = None;
+        self.undo.push_back(HistoryEntry {
+            label: label.to_string(),
+            source: before.to_string(),
+        });
+        if self.undo.len() > HISTORY_LIMIT {
+            self.undo.pop_front();
+        }
+    }
+
+    /// Undo the newest step from `current`, returning the source before it.
+    pub fn undo(&mut self, current: String) -> Option<String> {
+        let entry = self.undo.pop_back()?;
+        self.typed_at = None;
+        self.redo.push(HistoryEntry {
+            label: entry.label,
+            source: current,
+        });
+        Some(entry.source)
+    }
+
+    /// Redo the newest undone step from `current`, returning the source after
+    /// it.
+    pub fn redo(&mut self, current: String) -> Option<String> {
+        let entry = self.redo.pop()?;
+        self.typed_at = None;
+        self.undo.push_back(HistoryEntry {
+            label: entry.label,
+            source: current,
+        });
+        Some(entry.source)
+    }
+}
@@ -4,4 +4,13 @@ pub mod use_graph_editor;
 pub use use_graph_editor::{use_graph_editor_logic, GraphEditorLogic};
 
 pub mod use_graph_view;
-pub use use_graph_view::use_graph_view_logic;
+pub use use_graph_view::{use_graph_view_logic, GraphViewLogic};
+
+pub mod use_code_completion;
+pub use use_code_completion::{use_code_completion, CodeCompletion};
+
+pub mod use_rough_style;
+pub use use_rough_style::{rough_settings_key, use_rough_style};
+
+pub mod use_theme;
+pub use use_theme::{use_theme, use_theme_provider};
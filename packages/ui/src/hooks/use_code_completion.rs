@@ -0,0 +1,85 @@
+//! A "headless" hook containing the autocompletion logic for the CodeEditor.
+use dioxus::prelude::*;
+use dot_lang::complete::{complete, Completions};
+
+/// A headless hook holding the suggestion list for DOT autocompletion.
+///
+/// The consumer tells it where the cursor is via [CodeCompletion::update] and
+/// decides how to render and navigate the suggestions.
+#[must_use]
+pub fn use_code_completion() -> CodeCompletion {
+    let completions = use_signal(|| None::<Completions>);
+    let selected = use_signal(|| 0usize);
+
+    CodeCompletion {
+        completions,
+        selected,
+    }
+}
+
+/// The state and signals returned by the `use_code_completion` hook.
+#[derive(Clone, Copy)]
+pub struct CodeCompletion {
+    /// The open suggestion list, if any.
+    pub completions: Signal<Option<Completions>>,
+    /// Index of the highlighted suggestion.
+    pub selected: Signal<usize>,
+}
+
+impl CodeCompletion {
+    pub fn is_open(&self) -> bool {
+        self.completions.read().is_some()
+    }
+
+    /// Recompute suggestions for the cursor at byte `offset` in `src`.
+    ///
+    /// The list closes when there is nothing to offer, or when the only
+    /// suggestion is exactly what has already been typed.
+    pub fn update(&mut self, src: &str, offset: usize, links: &[String]) {
+        let next = complete(src, offset, links).filter(|c| match c.items.as_slice() {
+            [] => false,
+            [only] => only.label != c.prefix,
+            _ => true,
+        });
+        self.selected.set(0);
+        self.completions.set(next);
+    }
+
+    pub fn close(&mut self) {
+        if self.is_open() {
+            self.completions.set(None);
+        }
+    }
+
+    /// Move the highlight by `delta`, wrapping around at either end.
+    pub fn move_selection(&mut self, delta: isize) {
+        let len = self
+            .completions
+            .read()
+            .as_ref()
+            .map_or(0, |c| c.items.len());
+        if len == 0 {
+            return;
+        }
+        let current = *self.selected.peek() as isize;
+        self.selected
+            .set((current + delta).rem_euclid(len as isize) as usize);
+    }
+
+    /// Apply the highlighted suggestion to `src` and close the list.
+    ///
+    /// Returns the new text and the byte offset the cursor should move to.
+    pub fn accept(&mut self, src: &str) -> Option<(String, usize)> {
+        let completions = self.completions.take()?;
+        let item = completions.items.get(*self.selected.peek())?;
+        let range = completions.range;
+        if range.end > src.len() {
+            return None;
+        }
+        let mut next = String::with_capacity(src.len() + item.insert.len());
+        next.push_str(&src[..range.start]);
+        next.push_str(&item.insert);
+        next.push_str(&src[range.end..]);
+        Some((next, range.start + item.insert.len()))
+    }
+}
@@ -1,6 +1,11 @@
 //! A "headless" hook containing the logic for the GraphEditor component.
-use crate::error::RenderError;
+use crate::error::{ErrorInfo, RenderError};
+use crate::history::History;
+use crate::platform;
 use dioxus::prelude::*;
+use dot_lang::lint::{lint_source, LintOptions};
+use dot_lang::source_map::{GraphElement, SourceMap};
+use dot_lang::span::Span;
 
 /// A headless hook containing the logic for the GraphEditor component.
 ///
@@ -10,10 +15,25 @@ use dioxus::prelude::*;
 pub fn use_graph_editor_logic() -> GraphEditorLogic {
     let collapsed = use_signal(|| false);
     let render_errors = use_signal(|| None::<crate::error::RenderError>);
+    let lint_warnings = use_signal(Vec::<ErrorInfo>::new);
+    let selected = use_signal(Vec::<GraphElement>::new);
+    let highlighted = use_signal(|| None::<Span>);
+    let history = use_signal(History::default);
+
+    // Renderer errors first, then lint findings, each group in line order.
+    // The renderer owns `render_errors` and clears it on every successful
+    // render, so lint results are kept apart and merged here.
+    let diagnostics = use_memo(move || {
+        let mut errors = render_errors().map(|err| err.errors).unwrap_or_default();
+        let mut warnings = lint_warnings();
+        warnings.sort_by_key(|w| w.line);
+        errors.append(&mut warnings);
+        (!errors.is_empty()).then_some(RenderError { errors })
+    });
 
     // Calculate which lines have errors
     let error_lines = use_memo(move || {
-        render_errors()
+        diagnostics()
             .map(|err| {
                 err.errors
                     .iter()
@@ -26,16 +46,120 @@ pub fn use_graph_editor_logic() -> GraphEditorLogic {
     GraphEditorLogic {
         collapsed,
         render_errors,
+        lint_warnings,
+        diagnostics,
         error_lines,
+        selected,
+        highlighted,
+        history,
     }
 }
 
 /// The state and signals returned by the `use_graph_editor_logic` hook.
+#[derive(Clone, Copy)]
 pub struct GraphEditorLogic {
     /// A signal that determines whether the editor panel is collapsed.
     pub collapsed: Signal<bool>,
     /// A signal that holds any rendering errors.
     pub render_errors: Signal<Option<RenderError>>,
-    /// A memoized list of lines that have errors.
+    /// Findings from the pre-render lint pass over the current source.
+    pub lint_warnings: Signal<Vec<ErrorInfo>>,
+    /// Rendering errors and lint warnings together, for display.
+    pub diagnostics: Memo<Option<RenderError>>,
+    /// A memoized list of lines that have errors or warnings.
     pub error_lines: Memo<Vec<u32>>,
+    /// Elements outlined in the preview.
+    pub selected: Signal<Vec<GraphElement>>,
+    /// The statement highlighted in the editor after a click in the preview.
+    pub highlighted: Signal<Option<Span>>,
+    /// Undo and redo stacks for the source.
+    pub history: Signal<History>,
+}
+
+impl GraphEditorLogic {
+    /// Lint `source` and replace the current lint warnings with the result.
+    ///
+    /// `link_exists` resolves internal `.dot` link targets, e.g. against the
+    /// [StorageProvider](crate::StorageProvider).
+    pub fn lint(&mut self, source: &str, link_exists: &dyn Fn(&str) -> bool) {
+        let options = LintOptions {
+            link_exists: Some(link_exists),
+        };
+        let warnings: Vec<ErrorInfo> = lint_source(source, &options)
+            .iter()
+            .map(ErrorInfo::from)
+            .collect();
+        if *self.lint_warnings.peek() != warnings {
+            self.lint_warnings.set(warnings);
+        }
+    }
+
+    /// Select the preview elements for the statement at byte `offset` of
+    /// `source`, e.g. when the editor cursor moves.
+    pub fn select_at(&mut self, source: &str, offset: usize) {
+        let elements = SourceMap::new(&dot_lang::parse(source).document).elements_at(offset);
+        if *self.selected.peek() != elements {
+            self.selected.set(elements);
+        }
+        if self.highlighted.peek().is_some() {
+            self.highlighted.set(None);
+        }
+    }
+
+    /// Select `element`, e.g. when it is clicked in the preview, and highlight
+    /// the statement that defines it in `source`.
+    pub fn select_element(&mut self, source: &str, element: GraphElement) {
+        let span = SourceMap::new(&dot_lang::parse(source).document).span_of(&element);
+        self.highlighted.set(span);
+        self.selected.set(vec![element]);
+    }
+}
+
+// ------------------------- History -------------------------
+
+impl GraphEditorLogic {
+    /// Set `source` to `text` typed by the user. Keystrokes until a pause, or
+    /// until another kind of edit, are undone together.
+    pub fn type_text(&mut self, mut source: Signal<String>, text: String) {
+        if *source.peek() == text {
+            return;
+        }
+        self.history
+            .write()
+            .record_typing(&source.peek(), platform::now_millis());
+        source.set(text);
+    }
+
+    /// Set `source` to `text` as one undo step named `label`, e.g. "Format" or
+    /// "Delete node a".
+    pub fn apply(&mut self, mut source: Signal<String>, label: &str, text: String) {
+        if *source.peek() == text {
+            return;
+        }
+        self.history.write().record(&source.peek(), label);
+        source.set(text);
+    }
+
+    /// Put `source` back to before the last undo step, if there is one.
+    pub fn undo(&mut self, mut source: Signal<String>) {
+        let current = source.peek().clone();
+        if let Some(text) = self.history.write().undo(current) {
+            source.set(text);
+        }
+    }
+
+    /// Redo the last undone step on `source`, if there is one.
+    pub fn redo(&mut self, mut source: Signal<String>) {
+        let current = source.peek().clone();
+        if let Some(text) = self.history.write().redo(current) {
+            source.set(text);
+        }
+    }
+
+    /// Forget the history, e.g. when another file is opened.
+    pub fn clear_history(&mut self) {
+        if *self.history.peek() != History::default() {
+            self.history.set(History::default());
+        }
+    }
 }
@@ -1,77 +1,120 @@
 //! A "headless" hook containing the logic for the GraphView component.
-use crate::{platform, StorageProvider};
-use dioxus::prelude::*;
+use std::collections::HashMap;
 
-static KITCHEN_SINK: &str = include_str!("../../assets/dot/kitchen_sink.dot");
+use crate::revisions::{record_revision, Retention};
+use crate::{platform, PreloadComplete, StorageProvider};
+use dioxus::prelude::*;
 
 /// A headless hook containing the logic for the GraphView component.
 ///
-/// This hook encapsulates the data loading and saving logic for a graph,
-/// allowing the consumer to build a custom UI around it.
+/// This hook loads the graph stored under `key_path` and saves every edit of
+/// it, allowing the consumer to build a custom UI around it. A graph that
+/// isn't stored yet is created with `default`, called with its key; while
+/// assets are still being preloaded it is left alone, as they may bring it.
+/// Each save is also kept as a revision, as much as `retention` allows.
+/// `retention` is read once.
 #[must_use]
-pub fn use_graph_view_logic(key_path: String) -> Signal<String> {
+pub fn use_graph_view_logic(
+    key_path: String,
+    default: impl FnMut(String) -> String + 'static,
+    retention: Retention,
+) -> GraphViewLogic {
     let storage = use_context::<StorageProvider>();
+    let preload_complete = try_use_context::<PreloadComplete>();
+    let default = use_callback(default);
 
-    // Decode the key_path
-    let decoded = url_escape::decode(&key_path).to_string();
+    let key = use_memo(use_reactive((&key_path,), |(key_path,)| {
+        url_escape::decode(&key_path).to_string()
+    }));
+    let mut dot_input = use_signal(String::new);
+    let mut opened = use_signal(String::new);
+    // The key `dot_input` was loaded from, which `key` runs ahead of while
+    // the next graph loads
+    let mut loaded_key = use_signal(String::new);
 
-    // Load the file content - this re-runs whenever key_path prop changes
-    let initial_content = {
-        info!(
-            "use_graph_view_logic: Loading file for key_path: {}",
-            key_path
-        );
-        let storage_clone = storage.clone();
-        let decoded_clone = decoded.clone();
-        storage_clone
-            .load(&decoded_clone)
-            .map(|data| String::from_utf8_lossy(&data).to_string())
-            .unwrap_or_else(|_| {
-                let d = if decoded_clone == "kitchen_sink.dot" {
-                    KITCHEN_SINK.to_string()
-                } else {
-                    "digraph { creating -> new_file; }".to_string()
-                };
-                if let Err(e) = storage_clone.save(&decoded_clone, d.as_bytes()) {
-                    error!("Failed to save new file to storage: {}", e);
+    // Load the file whenever the key changes. Switching again before it has
+    // loaded restarts the task, so a stale load never lands.
+    let load_storage = storage.clone();
+    let _load = use_resource(move || {
+        let storage = load_storage.clone();
+        async move {
+            let key = key();
+            let preloaded = preload_complete.is_none_or(|preloaded| preloaded());
+            info!("use_graph_view_logic: Loading file for key: {}", key);
+            let content = match storage.asynchronous().load(&key).await {
+                Ok(data) => String::from_utf8_lossy(&data).to_string(),
+                // Creating it now would clobber the file once preloading
+                // brings it; this runs again when preloading is done
+                Err(_) if !preloaded => return,
+                Err(_) => {
+                    let d = default.call(key.clone());
+                    if let Err(e) = storage.asynchronous().save(&key, d.as_bytes()).await {
+                        error!("Failed to save new file to storage: {}", e);
+                    }
+                    d
                 }
-                d
-            })
-    };
-
-    // Create signal with the loaded content
-    // IMPORTANT: use_signal is called every render, but it only initializes once
-    // We need to UPDATE the signal when key_path changes
-    let mut dot_input = use_signal(|| initial_content.clone());
-
-    // Update the signal content when key_path changes
-    use_effect(move || {
-        info!(
-            "use_graph_view_logic effect: Updating dot_input signal with new content (len: {})",
-            initial_content.len()
-        );
-        dot_input.set(initial_content.clone());
+            };
+            loaded_key.set(key);
+            opened.set(content.clone());
+            dot_input.set(content);
+        }
     });
 
-    // Effect for auto-saving with debouncing
+    // Effect for auto-saving with debouncing: each edit cancels the save of
+    // the one before. A save for the file just switched away from is let
+    // finish.
+    let retention = use_hook(|| retention);
+    let mut pending_saves = use_signal(HashMap::<String, Task>::new);
     use_effect(move || {
         let current_dot = dot_input();
-        if current_dot.is_empty() {
+        let key = loaded_key.peek().clone();
+        if let Some(task) = pending_saves.write().remove(&key) {
+            task.cancel();
+        }
+        if key.is_empty() || current_dot.is_empty() {
             return;
         }
 
-        let decoded_clone = decoded.clone();
-        let storage_clone = storage.clone();
-        spawn(async move {
+        let storage = storage.clone();
+        let saved_key = key.clone();
+        let task = spawn(async move {
             platform::sleep(std::time::Duration::from_millis(500)).await;
 
-            if let Err(e) = storage_clone.save(&decoded_clone, current_dot.as_bytes()) {
+            let result = storage
+                .asynchronous()
+                .save(&saved_key, current_dot.as_bytes())
+                .await;
+            pending_saves.write().remove(&saved_key);
+            if let Err(e) = result {
                 error!("Failed to auto-save changes: {}", e);
-            } else {
-                info!("Auto-saved changes to {}", decoded_clone);
+                return;
+            }
+            info!("Auto-saved changes to {}", saved_key);
+            let now = platform::now_millis();
+            if let Err(e) =
+                record_revision(&storage, &saved_key, &current_dot, now, &retention).await
+            {
+                error!("Failed to save revision: {}", e);
             }
         });
+        pending_saves.write().insert(key, task);
     });
 
-    dot_input
+    GraphViewLogic {
+        key,
+        dot_input,
+        opened,
+    }
+}
+
+/// The state and signals returned by the `use_graph_view_logic` hook.
+#[derive(Clone, Copy)]
+pub struct GraphViewLogic {
+    /// The storage key of the graph, decoded from the route.
+    pub key: Memo<String>,
+    /// The source being edited. Changes are saved half a second after the
+    /// last one.
+    pub dot_input: Signal<String>,
+    /// The source as it was loaded, set each time a graph is opened.
+    pub opened: Signal<String>,
 }
@@ -0,0 +1,72 @@
+//! A "headless" hook holding the rough style settings of a file.
+use crate::components::RoughOptions;
+use crate::{platform, StorageProvider, SETTINGS_PREFIX};
+use dioxus::prelude::*;
+
+/// The storage key for the rough settings of the graph stored under
+/// `dot_key`, among the other settings: `diagrams/flow.dot` keeps them in
+/// `.settings/diagrams/flow.rough`.
+pub fn rough_settings_key(dot_key: &str) -> String {
+    let stem = dot_key.strip_suffix(".dot").unwrap_or(dot_key);
+    format!("{SETTINGS_PREFIX}{stem}.rough")
+}
+
+/// The rough style settings for the file stored under `file_key`.
+///
+/// Settings are loaded in a task when the key changes and saved shortly after
+/// each change. Files without settings get the defaults, which are only written
+/// once changed.
+#[must_use]
+pub fn use_rough_style(file_key: String) -> Signal<RoughOptions> {
+    let storage = use_context::<StorageProvider>();
+    let mut options = use_signal(RoughOptions::default);
+    // The settings key and what is stored under it, so only changes are saved
+    let mut stored = use_signal(|| None::<(String, RoughOptions)>);
+    // Bumped on every change so a slider drag saves once
+    let mut generation = use_signal(|| 0u64);
+
+    let key = rough_settings_key(&file_key);
+    let load_storage = storage.clone();
+    let _load = use_resource(use_reactive((&key,), move |(key,)| {
+        let storage = load_storage.clone();
+        async move {
+            let loaded = storage
+                .asynchronous()
+                .load(&key)
+                .await
+                .map(|data| RoughOptions::from_settings(&String::from_utf8_lossy(&data)))
+                .unwrap_or_default();
+            options.set(loaded.clone());
+            stored.set(Some((key, loaded)));
+        }
+    }));
+
+    use_effect(move || {
+        let current = options();
+        let Some((key, saved)) = stored.peek().clone() else {
+            return;
+        };
+        if current == saved {
+            return;
+        }
+        let this = *generation.peek() + 1;
+        generation.set(this);
+        let storage = storage.clone();
+        spawn(async move {
+            platform::sleep(std::time::Duration::from_millis(500)).await;
+            if *generation.peek() != this {
+                return;
+            }
+            if let Err(e) = storage.save(&key, current.to_settings().as_bytes()) {
+                error!("Failed to save rough settings: {}", e);
+                return;
+            }
+            // Unless another file has been opened meanwhile
+            if stored.peek().as_ref().is_some_and(|(k, _)| *k == key) {
+                stored.set(Some((key, current)));
+            }
+        });
+    });
+
+    options
+}
@@ -0,0 +1,37 @@
+//! Hooks sharing the colour theme for rendered graphs.
+use crate::theme::{Theme, THEME_STORAGE_KEY};
+use crate::StorageProvider;
+use dioxus::prelude::*;
+
+/// Provide the chosen theme as a `Signal<Theme>` context, read from `storage`
+/// and saved back on every change. Call it where the storage is provided.
+pub fn use_theme_provider(storage: StorageProvider) -> Signal<Theme> {
+    let theme = use_context_provider(|| {
+        let saved = storage
+            .load(THEME_STORAGE_KEY)
+            .ok()
+            .and_then(|data| Theme::by_name(String::from_utf8_lossy(&data).trim()));
+        Signal::new(saved.unwrap_or_default())
+    });
+
+    let mut saved = use_signal(|| theme.peek().name);
+    use_effect(move || {
+        let name = theme().name;
+        if name == *saved.peek() {
+            return;
+        }
+        match storage.save(THEME_STORAGE_KEY, name.as_bytes()) {
+            Ok(()) => saved.set(name),
+            Err(e) => error!("Failed to save theme: {}", e),
+        }
+    });
+
+    theme
+}
+
+/// The theme provided by [use_theme_provider], or the default if there is none.
+pub fn use_theme() -> Theme {
+    try_use_context::<Signal<Theme>>()
+        .map(|theme| theme())
+        .unwrap_or_default()
+}
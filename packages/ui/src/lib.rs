@@ -14,14 +14,31 @@ pub mod views;
 
 pub mod components;
 
+pub mod export;
+
+pub mod theme;
+pub use theme::Theme;
+
 mod storage;
-pub use storage::{PlatformStorage, StorageProvider};
+pub use storage::asynchronous::{AsyncPlatformStorage, LocalBoxFuture};
+pub use storage::{
+    content_hash, Metadata, PlatformStorage, StorageError, StorageProvider, SETTINGS_PREFIX,
+};
+
+pub mod documents;
+
+pub mod history;
+
+pub mod revisions;
 
 pub mod error;
 pub use error::Error;
 
+/// The DOT parser, re-exported so platform crates share the same AST types.
+pub use dot_lang;
+
 mod gviz;
-pub use gviz::{GVizProvider, GraphVizable};
+pub use gviz::{AsyncGraphVizable, GVizProvider, GraphVizable};
 
 /// Platform specific utilities
 mod platform;
@@ -16,3 +16,17 @@ pub async fn sleep(millis: std::time::Duration) {
 
     TimeoutFuture::new(millis.as_millis() as u32).await;
 }
+
+/// Milliseconds since the Unix epoch
+#[cfg(not(target_arch = "wasm32"))]
+pub fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64)
+}
+
+/// Milliseconds since the Unix epoch, from the browser's clock
+#[cfg(target_arch = "wasm32")]
+pub fn now_millis() -> u64 {
+    web_sys::js_sys::Date::now() as u64
+}
@@ -0,0 +1,242 @@
+//! Saved revisions of stored graphs.
+//!
+//! Each auto-save of a graph adds a revision to its log, kept under the hidden
+//! [HISTORY_PREFIX] so it stays out of the document listing:
+//! `diagrams/flow.dot` keeps its history in `.history/diagrams/flow.history`.
+//! The oldest revision is stored whole and each later
+//! one as the text that replaced the middle of the one before it, which is
+//! small for edits made while typing.
+//!
+//! Saves within the same [Retention::interval_ms] are merged into one revision,
+//! and the oldest revisions are dropped to stay within the other limits.
+use crate::{StorageError, StorageProvider};
+
+/// Revision logs are stored under this prefix, like settings under
+/// [SETTINGS_PREFIX](crate::SETTINGS_PREFIX).
+pub const HISTORY_PREFIX: &str = ".history/";
+
+/// The storage key for the revisions of the graph stored under `dot_key`.
+pub fn revisions_key(dot_key: &str) -> String {
+    let stem = dot_key.strip_suffix(".dot").unwrap_or(dot_key);
+    format!("{HISTORY_PREFIX}{stem}.history")
+}
+
+/// How much history to keep per graph. Pass one to `GraphView`, or provide
+/// one in context, to change the defaults.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Retention {
+    /// Most revisions kept.
+    pub max_revisions: usize,
+    /// Most bytes the encoded log may take. Browser storage is a few MB for
+    /// the whole app, and stores text as base64.
+    pub max_bytes: usize,
+    /// Saves in the same interval of this many milliseconds replace the newest
+    /// revision rather than adding one.
+    pub interval_ms: u64,
+}
+
+impl Default for Retention {
+    fn default() -> Self {
+        Retention {
+            max_revisions: 100,
+            max_bytes: 64 * 1024,
+            interval_ms: 60_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Revision {
+    /// When the revision was saved, in milliseconds since the Unix epoch.
+    pub timestamp_ms: u64,
+    pub content: String,
+}
+
+/// The revisions of a graph, oldest first. Always keeps at least one once one
+/// has been added.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RevisionLog {
+    revisions: Vec<Revision>,
+}
+
+impl RevisionLog {
+    pub fn revisions(&self) -> &[Revision] {
+        &self.revisions
+    }
+
+    pub fn latest(&self) -> Option<&Revision> {
+        self.revisions.last()
+    }
+
+    /// Add `content`, saved at `timestamp_ms`. Returns whether the log changed,
+    /// which it doesn't when `content` is the latest revision already.
+    pub fn record(&mut self, timestamp_ms: u64, content: &str, retention: &Retention) -> bool {
+        if self.latest().is_some_and(|r| r.content == content) {
+            return false;
+        }
+        let revision = Revision {
+            timestamp_ms,
+            content: content.to_string(),
+        };
+        // A lone revision is never replaced, so a file's state when it was
+        // first saved survives the edits that follow
+        let interval = retention.interval_ms.max(1);
+        match self.revisions.as_mut_slice() {
+            [_, .., latest] if latest.timestamp_ms / interval == timestamp_ms / interval => {
+                *latest = revision;
+            }
+            _ => self.revisions.push(revision),
+        }
+        self.apply(retention);
+        true
+    }
+
+    /// Drop the oldest revisions until the log is within `retention`.
+    pub fn apply(&mut self, retention: &Retention) {
+        let max = retention.max_revisions.max(1);
+        if self.revisions.len() > max {
+            self.revisions.drain(..self.revisions.len() - max);
+        }
+        while self.revisions.len() > 1 && self.encode().len() > retention.max_bytes {
+            self.revisions.remove(0);
+        }
+    }
+
+    /// One revision per entry: a header line, then the text it carries.
+    ///
+    /// ```text
+    /// full <timestamp> <length>
+    /// <content>
+    /// delta <timestamp> <prefix> <suffix> <length>
+    /// <middle>
+    /// ```
+    ///
+    /// A delta keeps the first `prefix` and last `suffix` bytes of the
+    /// revision before it around the new `middle`.
+    pub fn encode(&self) -> String {
+        let mut out = String::new();
+        let mut previous: Option<&str> = None;
+        for revision in &self.revisions {
+            let content = revision.content.as_str();
+            let timestamp = revision.timestamp_ms;
+            match previous {
+                None => out.push_str(&format!("full {timestamp} {}\n{content}\n", content.len())),
+                Some(before) => {
+                    let (prefix, suffix) = common_ends(before, content);
+                    let middle = &content[prefix..content.len() - suffix];
+                    out.push_str(&format!(
+                        "delta {timestamp} {prefix} {suffix} {}\n{middle}\n",
+                        middle.len()
+                    ));
+                }
+            }
+            previous = Some(content);
+        }
+        out
+    }
+
+    pub fn decode(text: &str) -> Result<Self, String> {
+        let mut revisions: Vec<Revision> = Vec::new();
+        let mut rest = text;
+        while !rest.is_empty() {
+            let (header, body) = rest
+                .split_once('\n')
+                .ok_or("Revision header without a body")?;
+            let fields: Vec<u64> = header
+                .split(' ')
+                .skip(1)
+                .map(|field| {
+                    field
+                        .parse()
+                        .map_err(|_| format!("Bad revision header: {header}"))
+                })
+                .collect::<Result<_, _>>()?;
+            let (timestamp_ms, content, length) = match (header.split(' ').next(), &fields[..]) {
+                (Some("full"), &[timestamp, length]) => {
+                    let content = slice(body, 0, length as usize)?;
+                    (timestamp, content.to_string(), length)
+                }
+                (Some("delta"), &[timestamp, prefix, suffix, length]) => {
+                    let before = &revisions
+                        .last()
+                        .ok_or("Revision delta without a base")?
+                        .content;
+                    if prefix + suffix > before.len() as u64 {
+                        return Err(format!("Bad revision header: {header}"));
+                    }
+                    let middle = slice(body, 0, length as usize)?;
+                    let head = slice(before, 0, prefix as usize)?;
+                    let tail = slice(before, before.len() - suffix as usize, before.len())?;
+                    (timestamp, format!("{head}{middle}{tail}"), length)
+                }
+                _ => return Err(format!("Bad revision header: {header}")),
+            };
+            revisions.push(Revision {
+                timestamp_ms,
+                content,
+            });
+            rest = body
+                .get(length as usize..)
+                .and_then(|rest| rest.strip_prefix('\n'))
+                .ok_or("Revision body without an end")?;
+        }
+        Ok(RevisionLog { revisions })
+    }
+}
+
+/// `text[start..end]`, or an error where that is out of bounds or splits a
+/// character.
+fn slice(text: &str, start: usize, end: usize) -> Result<&str, String> {
+    text.get(start..end)
+        .ok_or_else(|| "Revision length out of bounds".to_string())
+}
+
+/// The lengths in bytes of the longest common prefix of `a` and `b`, and of
+/// the longest common suffix of what follows it, on character boundaries.
+fn common_ends(a: &str, b: &str) -> (usize, usize) {
+    let prefix: usize = a
+        .chars()
+        .zip(b.chars())
+        .take_while(|(x, y)| x == y)
+        .map(|(c, _)| c.len_utf8())
+        .sum();
+    let suffix: usize = a[prefix..]
+        .chars()
+        .rev()
+        .zip(b[prefix..].chars().rev())
+        .take_while(|(x, y)| x == y)
+        .map(|(c, _)| c.len_utf8())
+        .sum();
+    (prefix, suffix)
+}
+
+/// The revision log of the graph stored under `dot_key`. A missing or
+/// unreadable log is empty.
+pub async fn load_revisions(storage: &StorageProvider, dot_key: &str) -> RevisionLog {
+    storage
+        .asynchronous()
+        .load(&revisions_key(dot_key))
+        .await
+        .ok()
+        .and_then(|data| RevisionLog::decode(&String::from_utf8_lossy(&data)).ok())
+        .unwrap_or_default()
+}
+
+/// Add `content` to the revision log of the graph stored under `dot_key`, e.g.
+/// after saving it.
+pub async fn record_revision(
+    storage: &StorageProvider,
+    dot_key: &str,
+    content: &str,
+    timestamp_ms: u64,
+    retention: &Retention,
+) -> Result<(), StorageError> {
+    let mut log = load_revisions(storage, dot_key).await;
+    if !log.record(timestamp_ms, content, retention) {
+        return Ok(());
+    }
+    storage
+        .asynchronous()
+        .save(&revisions_key(dot_key), log.encode().as_bytes())
+        .await
+}
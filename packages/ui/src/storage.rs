@@ -1,43 +1,165 @@
 //! This module defines the trait details for managing data.
 use std::sync::Arc;
 
+use thiserror::Error;
+
+// Kept apart: with both storage traits in scope, calling their methods on a
+// storage would be ambiguous
+pub(crate) mod asynchronous;
+
 pub const KITCHEN_SINK_STORAGE_KEY: &str = "kitchen_sink.dot";
 
+/// Keys under this prefix hold app settings rather than documents. Backends
+/// leave `.`-prefixed folders out of listings, and the desktop git storage
+/// out of its repository.
+pub const SETTINGS_PREFIX: &str = ".settings/";
+
 // static KITCHEN_SINK: &str = include_str!("../assets/dot/kitchen_sink.dot");
 
+/// Why a storage operation failed.
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum StorageError {
+    /// Nothing is stored under the key.
+    #[error("Nothing stored under {0}")]
+    NotFound(String),
+
+    /// Something is already stored under the key, e.g. the target of a rename.
+    #[error("{0} already exists")]
+    AlreadyExists(String),
+
+    /// The key can't name anything in the backend, e.g. it would leave the
+    /// storage folder.
+    #[error("{0} is not a valid key")]
+    InvalidKey(String),
+
+    /// The backend is out of space, e.g. the browser's storage quota.
+    #[error("Storage is full")]
+    QuotaExceeded,
+
+    /// What is stored under the key can't be read back.
+    #[error("Data under {key} is corrupt: {message}")]
+    Corrupt { key: String, message: String },
+
+    /// Any other failure of the backend.
+    #[error("Storage backend error: {0}")]
+    Backend(String),
+}
+
+/// What is known about the data stored under a key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Metadata {
+    /// Size in bytes.
+    pub size: u64,
+    /// When the data was last saved, in milliseconds since the Unix epoch.
+    /// `None` where the backend doesn't record it.
+    pub modified_ms: Option<u64>,
+    /// [content_hash] of the data.
+    pub hash: String,
+}
+
+/// A cheap, stable hash of `data`: 64-bit FNV-1a, as 16 hex digits. Used to
+/// tell whether stored content changed without comparing it.
+pub fn content_hash(data: &[u8]) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for &byte in data {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
 pub trait PlatformStorage: Send + Sync {
-    fn save(&self, key: &str, data: &[u8]) -> Result<(), String>;
-    fn load(&self, key: &str) -> Result<Vec<u8>, String>;
-    fn delete(&self, key: &str) -> Result<(), String>;
+    fn save(&self, key: &str, data: &[u8]) -> Result<(), StorageError>;
+    fn load(&self, key: &str) -> Result<Vec<u8>, StorageError>;
+    fn delete(&self, key: &str) -> Result<(), StorageError>;
     fn exists(&self, key: &str) -> bool;
+
+    /// The stored keys starting with `prefix`, sorted. Backends that can't
+    /// enumerate their contents return nothing.
+    fn list(&self, prefix: &str) -> Result<Vec<String>, StorageError> {
+        let _ = prefix;
+        Ok(Vec::new())
+    }
+
+    /// Move the data under `from` to `to`. Fails if `to` is taken.
+    ///
+    /// The default copies the data and then deletes `from`; backends that
+    /// can move in one step should.
+    fn rename(&self, from: &str, to: &str) -> Result<(), StorageError> {
+        if self.exists(to) {
+            return Err(StorageError::AlreadyExists(to.to_string()));
+        }
+        let data = self.load(from)?;
+        self.save(to, &data)?;
+        self.delete(from)
+    }
+
+    /// Size, modification time and hash of the data under `key`.
+    ///
+    /// The default loads the data to measure it and can't tell when it was
+    /// saved.
+    fn metadata(&self, key: &str) -> Result<Metadata, StorageError> {
+        let data = self.load(key)?;
+        Ok(Metadata {
+            size: data.len() as u64,
+            modified_ms: None,
+            hash: content_hash(&data),
+        })
+    }
+
+    /// All keys currently stored, e.g. to offer link targets in the editor.
+    fn keys(&self) -> Vec<String> {
+        self.list("").unwrap_or_default()
+    }
 }
 
 // A storage provider context that wraps any storage implementation
 #[derive(Clone)]
 pub struct StorageProvider {
     inner: Arc<dyn PlatformStorage>,
+    /// The same storage, answering asynchronously
+    asynchronous: Arc<dyn asynchronous::AsyncPlatformStorage>,
 }
 
 impl StorageProvider {
     /// Create a new [StorageProvider] with the given storage implementation
     pub fn new<S: PlatformStorage + 'static>(storage: S) -> Self {
+        let inner = Arc::new(storage);
         Self {
-            inner: Arc::new(storage),
+            asynchronous: inner.clone(),
+            inner,
         }
     }
 
+    /// Create a [StorageProvider] for a backend that can only answer
+    /// asynchronously, e.g. over the network. Its synchronous methods fail
+    /// with [StorageError::Backend].
+    pub fn new_async<S: asynchronous::AsyncPlatformStorage + 'static>(storage: S) -> Self {
+        Self {
+            inner: Arc::new(asynchronous::AsyncOnly),
+            asynchronous: Arc::new(storage),
+        }
+    }
+
+    /// The storage, answering asynchronously. Prefer it where waiting is
+    /// fine, e.g. loading a file in a task, so slow backends don't hold up
+    /// rendering.
+    pub fn asynchronous(&self) -> &dyn asynchronous::AsyncPlatformStorage {
+        self.asynchronous.as_ref()
+    }
+
     /// Save data with the given key
-    pub fn save(&self, key: &str, data: &[u8]) -> Result<(), String> {
+    pub fn save(&self, key: &str, data: &[u8]) -> Result<(), StorageError> {
         self.inner.save(key, data)
     }
 
     /// Load data for the given key
-    pub fn load(&self, key: &str) -> Result<Vec<u8>, String> {
+    pub fn load(&self, key: &str) -> Result<Vec<u8>, StorageError> {
         self.inner.load(key)
     }
 
     /// Delete data for the given ke
-    pub fn delete(&self, key: &str) -> Result<(), String> {
+    pub fn delete(&self, key: &str) -> Result<(), StorageError> {
         self.inner.delete(key)
     }
 
@@ -45,4 +167,24 @@ impl StorageProvider {
     pub fn exists(&self, key: &str) -> bool {
         self.inner.exists(key)
     }
+
+    /// List all stored keys
+    pub fn keys(&self) -> Vec<String> {
+        self.inner.keys()
+    }
+
+    /// List the stored keys starting with `prefix`
+    pub fn list(&self, prefix: &str) -> Result<Vec<String>, StorageError> {
+        self.inner.list(prefix)
+    }
+
+    /// Move the data under `from` to `to`
+    pub fn rename(&self, from: &str, to: &str) -> Result<(), StorageError> {
+        self.inner.rename(from, to)
+    }
+
+    /// Size, modification time and hash of the data under `key`
+    pub fn metadata(&self, key: &str) -> Result<Metadata, StorageError> {
+        self.inner.metadata(key)
+    }
 }
@@ -0,0 +1,108 @@
+//! Storage that answers asynchronously.
+use std::future::Future;
+use std::pin::Pin;
+
+use super::{Metadata, PlatformStorage, StorageError};
+
+/// A boxed future, so async traits can be used as trait objects. Not `Send`,
+/// as the futures of browser APIs hold JS values.
+pub type LocalBoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
+
+/// [PlatformStorage] for backends that can only answer asynchronously, such
+/// as IndexedDB, the network or a worker.
+///
+/// Every [PlatformStorage] is one as well, answering when first polled.
+pub trait AsyncPlatformStorage {
+    fn save<'a>(
+        &'a self,
+        key: &'a str,
+        data: &'a [u8],
+    ) -> LocalBoxFuture<'a, Result<(), StorageError>>;
+    fn load<'a>(&'a self, key: &'a str) -> LocalBoxFuture<'a, Result<Vec<u8>, StorageError>>;
+    fn delete<'a>(&'a self, key: &'a str) -> LocalBoxFuture<'a, Result<(), StorageError>>;
+    fn exists<'a>(&'a self, key: &'a str) -> LocalBoxFuture<'a, bool>;
+    /// See [PlatformStorage::list].
+    fn list<'a>(&'a self, prefix: &'a str)
+        -> LocalBoxFuture<'a, Result<Vec<String>, StorageError>>;
+    /// See [PlatformStorage::rename].
+    fn rename<'a>(
+        &'a self,
+        from: &'a str,
+        to: &'a str,
+    ) -> LocalBoxFuture<'a, Result<(), StorageError>>;
+    /// See [PlatformStorage::metadata].
+    fn metadata<'a>(&'a self, key: &'a str) -> LocalBoxFuture<'a, Result<Metadata, StorageError>>;
+}
+
+impl<S: PlatformStorage + ?Sized> AsyncPlatformStorage for S {
+    fn save<'a>(
+        &'a self,
+        key: &'a str,
+        data: &'a [u8],
+    ) -> LocalBoxFuture<'a, Result<(), StorageError>> {
+        Box::pin(async move { PlatformStorage::save(self, key, data) })
+    }
+
+    fn load<'a>(&'a self, key: &'a str) -> LocalBoxFuture<'a, Result<Vec<u8>, StorageError>> {
+        Box::pin(async move { PlatformStorage::load(self, key) })
+    }
+
+    fn delete<'a>(&'a self, key: &'a str) -> LocalBoxFuture<'a, Result<(), StorageError>> {
+        Box::pin(async move { PlatformStorage::delete(self, key) })
+    }
+
+    fn exists<'a>(&'a self, key: &'a str) -> LocalBoxFuture<'a, bool> {
+        Box::pin(async move { PlatformStorage::exists(self, key) })
+    }
+
+    fn list<'a>(
+        &'a self,
+        prefix: &'a str,
+    ) -> LocalBoxFuture<'a, Result<Vec<String>, StorageError>> {
+        Box::pin(async move { PlatformStorage::list(self, prefix) })
+    }
+
+    fn rename<'a>(
+        &'a self,
+        from: &'a str,
+        to: &'a str,
+    ) -> LocalBoxFuture<'a, Result<(), StorageError>> {
+        Box::pin(async move { PlatformStorage::rename(self, from, to) })
+    }
+
+    fn metadata<'a>(&'a self, key: &'a str) -> LocalBoxFuture<'a, Result<Metadata, StorageError>> {
+        Box::pin(async move { PlatformStorage::metadata(self, key) })
+    }
+}
+
+/// The synchronous side of a storage that only answers asynchronously:
+/// every call fails, so code that can't wait finds nothing.
+pub(crate) struct AsyncOnly;
+
+impl AsyncOnly {
+    fn error() -> StorageError {
+        StorageError::Backend("this storage only answers asynchronously".to_string())
+    }
+}
+
+impl PlatformStorage for AsyncOnly {
+    fn save(&self, _key: &str, _data: &[u8]) -> Result<(), StorageError> {
+        Err(Self::error())
+    }
+
+    fn load(&self, _key: &str) -> Result<Vec<u8>, StorageError> {
+        Err(Self::error())
+    }
+
+    fn delete(&self, _key: &str) -> Result<(), StorageError> {
+        Err(Self::error())
+    }
+
+    fn exists(&self, _key: &str) -> bool {
+        false
+    }
+
+    fn list(&self, _prefix: &str) -> Result<Vec<String>, StorageError> {
+        Err(Self::error())
+    }
+}
@@ -0,0 +1,121 @@
+//! Colour themes for rendered graphs.
+//!
+//! A theme recolours what Graphviz draws in its default colours (black lines
+//! and text on white) with CSS in the rendered `<svg>`, so the DOT source is
+//! left alone. Colours set in the source are kept.
+
+/// Where the chosen theme is remembered, among the other
+/// [settings](crate::SETTINGS_PREFIX).
+pub const THEME_STORAGE_KEY: &str = ".settings/theme";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub name: &'static str,
+    pub background: &'static str,
+    /// Replaces white node and cluster fills.
+    pub fill: &'static str,
+    /// Replaces black node and cluster outlines.
+    pub stroke: &'static str,
+    /// Replaces black edges and arrowheads.
+    pub edge: &'static str,
+    /// Replaces black text.
+    pub text: &'static str,
+    pub link_external: &'static str,
+    pub link_internal: &'static str,
+    pub link_fragment: &'static str,
+    /// Overrides the width of every line, e.g. `"2"`.
+    pub stroke_width: Option<&'static str>,
+}
+
+pub const LIGHT: Theme = Theme {
+    name: "light",
+    background: "#ffffff",
+    fill: "#ffffff",
+    stroke: "#000000",
+    edge: "#000000",
+    text: "#000000",
+    link_external: "#1e88e5",
+    link_internal: "#43a047",
+    link_fragment: "#fb8c00",
+    stroke_width: None,
+};
+
+pub const DARK: Theme = Theme {
+    name: "dark",
+    background: "#1e1e1e",
+    fill: "#2a2a2a",
+    stroke: "#e0e0e0",
+    edge: "#c8c8c8",
+    text: "#e8e8e8",
+    link_external: "#64b5f6",
+    link_internal: "#81c784",
+    link_fragment: "#ffb74d",
+    stroke_width: None,
+};
+
+pub const HIGH_CONTRAST: Theme = Theme {
+    name: "high-contrast",
+    background: "#000000",
+    fill: "#000000",
+    stroke: "#ffffff",
+    edge: "#ffff00",
+    text: "#ffffff",
+    link_external: "#00ffff",
+    link_internal: "#00ff00",
+    link_fragment: "#ff00ff",
+    stroke_width: Some("2"),
+};
+
+pub const THEMES: [Theme; 3] = [LIGHT, DARK, HIGH_CONTRAST];
+
+impl Default for Theme {
+    fn default() -> Self {
+        LIGHT
+    }
+}
+
+impl Theme {
+    pub fn by_name(name: &str) -> Option<Theme> {
+        THEMES.into_iter().find(|theme| theme.name == name)
+    }
+
+    /// CSS applying the theme to a Graphviz `<svg>`, rough or not. The rules
+    /// only match an `<svg>` with a `data-theme` attribute naming the theme.
+    pub fn css(&self) -> String {
+        let Theme {
+            name,
+            background,
+            fill,
+            stroke,
+            edge,
+            text,
+            link_external,
+            link_internal,
+            link_fragment,
+            ..
+        } = self;
+        let svg = format!(r#"svg[data-theme="{name}"]"#);
+        let mut css = format!(
+            r#"
+    {svg} {{ background: {background}; }}
+    {svg} g.graph > polygon[fill="white"] {{ fill: {background}; }}
+    {svg} g.node [fill="white"], {svg} g.cluster [fill="white"] {{ fill: {fill}; }}
+    {svg} g.node [stroke="black"], {svg} g.cluster [stroke="black"] {{ stroke: {stroke}; }}
+    {svg} g.node [fill="black"], {svg} g.cluster [fill="black"] {{ fill: {stroke}; }}
+    {svg} g.edge [stroke="black"] {{ stroke: {edge}; }}
+    {svg} g.edge [fill="black"] {{ fill: {edge}; }}
+    {svg} text:not([fill]), {svg} text[fill="black"] {{ fill: {text}; }}
+    {svg} g[data-link-type="external"] text {{ fill: {link_external} !important; }}
+    {svg} g[data-link-type="internal"] text {{ fill: {link_internal} !important; }}
+    {svg} g[data-link-type="fragment"] text {{ fill: {link_fragment} !important; }}
+    {svg} g[data-link-type] text {{ text-decoration: underline !important; }}
+    {svg} g[data-link-type]:hover text {{ opacity: 0.7; }}"#
+        );
+        if let Some(width) = self.stroke_width {
+            css.push_str(&format!(
+                "\n    {svg} g.node [stroke], {svg} g.cluster [stroke], {svg} g.edge [stroke] {{ stroke-width: {width}; }}"
+            ));
+        }
+        css
+    }
+}
@@ -2,11 +2,20 @@
 //! and displays the graph. This view is a reference implementation of how to
 //! use the components from this library with routing.
 use crate::{
-    components::{CodeEditor, DotDisplay, ErrorOverlay},
-    hooks::use_graph_editor_logic,
-    platform, PreloadComplete, StorageProvider,
+    components::{
+        CodeEditor, DotDisplay, ErrorOverlay, ExportMenu, GraphDiff, LinkKind, RevisionTimeline,
+        RoughSettings, SvgBuildConfig,
+    },
+    error::RenderError,
+    hooks::{
+        use_graph_editor_logic, use_graph_view_logic, use_rough_style, use_theme, GraphViewLogic,
+    },
+    revisions::Retention,
+    storage::KITCHEN_SINK_STORAGE_KEY,
+    StorageProvider,
 };
 use dioxus::prelude::*;
+use dot_lang::format::{format, FormatOptions};
 
 static KITCHEN_SINK: &str = include_str!("../../assets/dot/kitchen_sink.dot");
 const TAILWIND_CSS: Asset = asset!("../../assets/tailwind.css");
@@ -15,24 +24,19 @@ const TAILWIND_CSS: Asset = asset!("../../assets/tailwind.css");
 fn make_default(title: String) -> String {
     format!(
         r##"digraph "{}" {{
-  // Global graph attributes
+  // Global graph attributes. Colours come from the theme.
   label="{}";
   labelloc=top;
   fontname="Helvetica";
   fontsize=12;
-  color="#444444";
   charset="UTF-8";
 
   node [
     fontname="Helvetica";
     fontsize=11;
-    style=filled;
-    fillcolor="#e8f4ff";
-    color="#336699"
   ];
 
   edge [
-    color="#666666";
     arrowsize=0.9;
     fontname="Helvetica";
     fontsize=10;
@@ -58,82 +62,154 @@ fn make_default(title: String) -> String {
     )
 }
 
+/// The version of a graph the preview can show the changes against.
+#[derive(Debug, Clone, PartialEq)]
+enum Baseline {
+    /// The graph as it was when the file was opened.
+    Opened,
+    /// Another stored graph.
+    Key(String),
+}
+
+impl Baseline {
+    /// The `<option>` value for this baseline.
+    fn value(&self) -> String {
+        match self {
+            Baseline::Opened => "opened".to_string(),
+            Baseline::Key(key) => format!("key:{key}"),
+        }
+    }
+
+    fn from_value(value: &str) -> Option<Baseline> {
+        match value {
+            "opened" => Some(Baseline::Opened),
+            _ => value
+                .strip_prefix("key:")
+                .map(|key| Baseline::Key(key.to_string())),
+        }
+    }
+}
+
 #[component]
 pub fn GraphView<R>(
     route: R,
     key_path: String,
     rough_enabled: Signal<bool>,
     starter: Option<String>,
+    /// How much revision history to keep, instead of the [Retention] in
+    /// context or the default.
+    retention: Option<Retention>,
 ) -> Element
 where
     R: Routable + Clone + PartialEq,
 {
-    let mut dot_input = use_signal(String::new);
     let storage = use_context::<StorageProvider>();
     let mut editor = use_graph_editor_logic();
+    let retention = retention
+        .or(try_use_context::<Retention>())
+        .unwrap_or_default();
+    let view = use_graph_view_logic(
+        key_path,
+        move |key: String| {
+            if key == KITCHEN_SINK_STORAGE_KEY {
+                KITCHEN_SINK.to_string()
+            } else {
+                starter.clone().unwrap_or_else(|| make_default(key))
+            }
+        },
+        retention,
+    );
+    let GraphViewLogic {
+        dot_input, opened, ..
+    } = view;
 
-    // Subscribe to preload_complete so the effect re-runs once assets are in storage.
-    // try_use_context so this compiles for desktop too (no PreloadComplete provided there).
-    let preload_complete = use_context::<PreloadComplete>();
-
-    let decoded = url_escape::decode(&key_path).to_string();
+    let decoded = (view.key)();
+    let rough_options = use_rough_style(decoded.clone());
+    let theme = use_theme();
+    // Large graphs can switch transitions off
+    let mut animate = use_signal(|| true);
+    // What the preview shows the changes against, if anything
+    let mut compare = use_signal(|| None::<Baseline>);
 
-    let storage_clone = storage.clone();
-    let decoded_clone = decoded.clone();
+    // A newly opened file starts with no history
     use_effect(move || {
-        // Re-runs when key_path OR preload_complete changes.
-        let _ = use_route::<R>();
-        let preloaded = preload_complete();
-
-        let dot = storage_clone
-            .load(&decoded_clone)
-            .map(|data| String::from_utf8_lossy(&data).to_string())
-            .unwrap_or_else(|_| {
-                // Storage miss: don't fall back to make_default while preload is still
-                // in-flight — we'd clobber the real file once it arrives.
-                if !preloaded {
-                    return String::new();
-                }
-                let d = if decoded_clone == "kitchen_sink.dot" {
-                    KITCHEN_SINK.to_string()
-                } else {
-                    starter
-                        .clone()
-                        .unwrap_or_else(|| make_default(decoded_clone.clone()))
-                };
-                if let Err(e) = storage_clone.save(&decoded_clone, d.as_bytes()) {
-                    error!("Failed to save new file to storage: {}", e);
-                }
-                d
-            });
+        let _ = opened();
+        editor.clear_history();
+    });
 
-        // Don't overwrite existing content with an empty pending string.
-        if !dot.is_empty() {
-            dot_input.set(dot);
+    // Listing storage can walk a whole directory tree on desktop, so only list
+    // it again once another document is opened, which may just have been
+    // created. Opening also follows preloading.
+    let keys_storage = storage.clone();
+    let stored_keys = use_resource(move || {
+        let storage = keys_storage.clone();
+        async move {
+            let _ = opened();
+            storage.asynchronous().list("").await.unwrap_or_default()
         }
     });
 
-    // Add auto-save effect with debouncing
+    // Lint on every edit. Internal `.dot` links are checked against the
+    // stored keys, using the same `/:key_path` mapping the router uses; until
+    // they are listed every link passes.
     use_effect(move || {
-        let current_dot = dot_input();
+        let source = dot_input();
+        let keys = stored_keys();
+        let link_exists = |path: &str| {
+            let key = url_escape::decode(path.trim_start_matches('/')).to_string();
+            keys.as_ref().is_none_or(|keys| keys.contains(&key))
+        };
+        editor.lint(&source, &link_exists);
+    });
 
-        // Skip saving on initial load or empty content
-        if current_dot.is_empty() {
-            return;
+    let baseline = use_resource(move || {
+        let storage = storage.clone();
+        async move {
+            match compare() {
+                Some(Baseline::Opened) => Some(opened()),
+                Some(Baseline::Key(key)) => Some(
+                    storage
+                        .asynchronous()
+                        .load(&key)
+                        .await
+                        .map(|data| String::from_utf8_lossy(&data).to_string())
+                        .unwrap_or_default(),
+                ),
+                None => None,
+            }
         }
+    });
+    let link_targets = stored_keys().unwrap_or_default();
+    let compare_keys: Vec<String> = link_targets
+        .iter()
+        .filter(|key| key.ends_with(".dot"))
+        .cloned()
+        .collect();
+    let navigator = use_navigator();
+    let export_key = decoded.clone();
+    let history_key = decoded;
 
-        let storage_clone = storage.clone();
-        let decoded_clone = decoded.clone();
-        spawn(async move {
-            platform::sleep(std::time::Duration::from_millis(500)).await;
+    // Reformat the buffer in place. Formatting needs a clean parse, so on a
+    // syntax error we surface the parse errors in the overlay instead.
+    let mut format_source = move || {
+        let current = dot_input.peek().clone();
+        match format(&current, &FormatOptions::default()) {
+            Ok(formatted) => editor.apply(dot_input, "Format", formatted),
+            Err(errors) => editor.render_errors.set(Some(RenderError::from(errors))),
+        }
+    };
 
-            if let Err(e) = storage_clone.save(&decoded_clone, current_dot.as_bytes()) {
-                error!("Failed to auto-save changes: {}", e);
-            } else {
-                info!("Auto-saved changes to {}", decoded_clone);
-            }
-        });
-    });
+    let history = editor.history.read();
+    let (can_undo, can_redo) = (history.can_undo(), history.can_redo());
+    let undo_title = match history.undo_label() {
+        Some(label) => format!("Undo (Ctrl+Z): {label}"),
+        None => "Undo (Ctrl+Z)".to_string(),
+    };
+    let redo_title = match history.redo_label() {
+        Some(label) => format!("Redo (Ctrl+Shift+Z): {label}"),
+        None => "Redo (Ctrl+Shift+Z)".to_string(),
+    };
+    drop(history);
 
     rsx! {
         document::Link { rel: "stylesheet", href: TAILWIND_CSS }
@@ -144,19 +220,75 @@ where
             if !(editor.collapsed)() {
                 div {
                     class: "flex flex-col flex-1 bg-gray-50 border-r border-gray-200 overflow-none w-1/2 max-w-[800px]",
+                    // Shift+Alt+F formats, as in VS Code. Ctrl+Z undoes, and
+                    // Ctrl+Shift+Z or Ctrl+Y redo (Cmd on macOS).
+                    onkeydown: move |evt: KeyboardEvent| {
+                        let modifiers = evt.modifiers();
+                        let shortcut = modifiers.contains(Modifiers::CONTROL)
+                            || modifiers.contains(Modifiers::META);
+                        if modifiers.contains(Modifiers::SHIFT)
+                            && modifiers.contains(Modifiers::ALT)
+                            && evt.code() == Code::KeyF
+                        {
+                            evt.prevent_default();
+                            format_source();
+                        } else if shortcut && evt.code() == Code::KeyZ {
+                            evt.prevent_default();
+                            if modifiers.contains(Modifiers::SHIFT) {
+                                editor.redo(dot_input);
+                            } else {
+                                editor.undo(dot_input);
+                            }
+                        } else if shortcut && evt.code() == Code::KeyY {
+                            evt.prevent_default();
+                            editor.redo(dot_input);
+                        }
+                    },
                     h2 {
                         class: "text-xl font-bold text-gray-800 p-2 border-b border-gray-200 flex justify-between items-center",
                         "DOT Source"
-                        button {
-                            class: "ml-2 px-2 py-1 text-xs bg-gray-200 hover:bg-gray-300 rounded",
-                            onclick: move |_| editor.collapsed.set(true),
-                            "⟨⟨⟨⟨"
+                        div {
+                            class: "flex items-center",
+                            button {
+                                class: "ml-2 px-2 py-1 text-xs bg-gray-200 hover:bg-gray-300 rounded disabled:opacity-50",
+                                title: undo_title,
+                                disabled: !can_undo,
+                                onclick: move |_| editor.undo(dot_input),
+                                "Undo"
+                            }
+                            button {
+                                class: "ml-2 px-2 py-1 text-xs bg-gray-200 hover:bg-gray-300 rounded disabled:opacity-50",
+                                title: redo_title,
+                                disabled: !can_redo,
+                                onclick: move |_| editor.redo(dot_input),
+                                "Redo"
+                            }
+                            button {
+                                class: "ml-2 px-2 py-1 text-xs bg-gray-200 hover:bg-gray-300 rounded",
+                                title: "Format (Shift+Alt+F)",
+                                onclick: move |_| format_source(),
+                                "Format"
+                            }
+                            button {
+                                class: "ml-2 px-2 py-1 text-xs bg-gray-200 hover:bg-gray-300 rounded",
+                                onclick: move |_| editor.collapsed.set(true),
+                                "⟨⟨⟨⟨"
+                            }
                         }
                     }
                     CodeEditor {
                         value: dot_input(),
-                        oninput: move |new_value: String| dot_input.set(new_value),
+                        oninput: move |new_value: String| editor.type_text(dot_input, new_value),
                         error_lines: (editor.error_lines)(),
+                        diagnostics: (editor.diagnostics)().map(|d| d.errors).unwrap_or_default(),
+                        link_targets,
+                        onopenlink: move |target: String| {
+                            if let LinkKind::Internal(path) = (SvgBuildConfig::default().classify_link)(&target) {
+                                navigator.push(path.as_str());
+                            }
+                        },
+                        oncursor: move |offset: usize| editor.select_at(&dot_input.peek(), offset),
+                        highlight: (editor.highlighted)(),
                         placeholder: "Enter your DOT graph here...".to_string()
                     }
                 }
@@ -176,14 +308,70 @@ where
             div {
                 class: "flex flex-col bg-white overflow-auto flex-1 relative",
                 ErrorOverlay {
-                    errors: editor.render_errors
+                    errors: editor.diagnostics
                 }
                 div {
-                    class: "flex-1 bg-white overflow-auto",
-                    DotDisplay {
-                        dot: dot_input(),
-                        error_signal: editor.render_errors,
-                        rough: rough_enabled(),
+                    class: "absolute top-2 left-2 z-10 flex items-start gap-2",
+                    ExportMenu {
+                        target: "#graph-preview svg",
+                        file_key: export_key,
+                    }
+                    RevisionTimeline {
+                        file_key: history_key,
+                        onrestore: move |content| editor.apply(dot_input, "Restore revision", content),
+                    }
+                    if rough_enabled() {
+                        RoughSettings { options: rough_options }
+                    }
+                    button {
+                        class: "px-2 py-1 text-xs bg-gray-200 hover:bg-gray-300 rounded",
+                        aria_pressed: animate(),
+                        title: "Animate changes to the graph",
+                        onclick: move |_| animate.toggle(),
+                        if animate() { "Animation: on" } else { "Animation: off" }
+                    }
+                    select {
+                        class: "px-2 py-1 text-xs bg-gray-200 hover:bg-gray-300 rounded",
+                        title: "Show the changes against another version",
+                        onchange: move |e| compare.set(Baseline::from_value(&e.value())),
+                        option { value: "", selected: compare().is_none(), "Compare: off" }
+                        option {
+                            value: Baseline::Opened.value(),
+                            selected: compare() == Some(Baseline::Opened),
+                            "Compare: since opened"
+                        }
+                        for key in compare_keys {
+                            option {
+                                key: "{key}",
+                                value: Baseline::Key(key.clone()).value(),
+                                selected: compare() == Some(Baseline::Key(key.clone())),
+                                "Compare: {key}"
+                            }
+                        }
+                    }
+                }
+                div {
+                    id: "graph-preview",
+                    class: "flex-1 min-h-0 overflow-hidden",
+                    style: "background: {theme.background};",
+                    if let Some(old) = baseline().flatten() {
+                        GraphDiff {
+                            old,
+                            new: dot_input(),
+                            rough: rough_enabled(),
+                            theme,
+                        }
+                    } else {
+                        DotDisplay {
+                            dot: dot_input(),
+                            error_signal: editor.render_errors,
+                            rough: rough_enabled(),
+                            rough_options: rough_options(),
+                            theme,
+                            animate: animate(),
+                            selected: (editor.selected)(),
+                            onselect: move |element| editor.select_element(&dot_input.peek(), element),
+                        }
                     }
                 }
             }
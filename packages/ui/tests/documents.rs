@@ -0,0 +1,227 @@
+//! Checks how documents are listed, named and moved.
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Mutex;
+
+use dot_repl_ui::documents::{
+    delete_document, document_key, entries, rename_document, sidecar_keys, Entry,
+};
+use dot_repl_ui::{PlatformStorage, StorageError, StorageProvider};
+
+#[derive(Default)]
+struct MemoryStorage(Mutex<BTreeMap<String, Vec<u8>>>);
+
+impl PlatformStorage for MemoryStorage {
+    fn save(&self, key: &str, data: &[u8]) -> Result<(), StorageError> {
+        self.0
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), data.to_vec());
+        Ok(())
+    }
+
+    fn load(&self, key: &str) -> Result<Vec<u8>, StorageError> {
+        let files = self.0.lock().unwrap();
+        let data = files.get(key).cloned();
+        data.ok_or_else(|| StorageError::NotFound(key.to_string()))
+    }
+
+    fn delete(&self, key: &str) -> Result<(), StorageError> {
+        let removed = self.0.lock().unwrap().remove(key);
+        removed
+            .map(|_| ())
+            .ok_or_else(|| StorageError::NotFound(key.to_string()))
+    }
+
+    fn exists(&self, key: &str) -> bool {
+        self.0.lock().unwrap().contains_key(key)
+    }
+
+    fn list(&self, prefix: &str) -> Result<Vec<String>, StorageError> {
+        let files = self.0.lock().unwrap();
+        Ok(files
+            .keys()
+            .filter(|key| key.starts_with(prefix))
+            .cloned()
+            .collect())
+    }
+}
+
+fn storage_with(keys: &[&str]) -> StorageProvider {
+    let storage = MemoryStorage::default();
+    for key in keys {
+        storage.save(key, key.as_bytes()).unwrap();
+    }
+    StorageProvider::new(storage)
+}
+
+fn keys(list: &[&str]) -> Vec<String> {
+    list.iter().map(|key| key.to_string()).collect()
+}
+
+/// The rows of `entries`, as `"<indent><name>"` with folders ending in `/`.
+fn outline(rows: &[Entry]) -> Vec<String> {
+    rows.iter()
+        .map(|row| match row {
+            Entry::Folder { name, depth, .. } => format!("{}{name}/", "  ".repeat(*depth)),
+            Entry::Document { name, depth, .. } => format!("{}{name}", "  ".repeat(*depth)),
+        })
+        .collect()
+}
+
+#[test]
+fn documents_are_listed_in_folders() {
+    let keys = keys(&[
+        "zebra.dot",
+        "diagrams/flow.dot",
+        "notes.txt",
+        "diagrams/net/lan.dot",
+        "diagrams-old.dot",
+        "diagrams/auth.dot",
+    ]);
+    let rows = entries(&keys, "", &HashSet::new());
+    assert_eq!(
+        outline(&rows),
+        [
+            "diagrams/",
+            "  auth.dot",
+            "  flow.dot",
+            "  net/",
+            "    lan.dot",
+            "diagrams-old.dot",
+            "zebra.dot",
+        ]
+    );
+    assert_eq!(
+        rows[4],
+        Entry::Document {
+            key: "diagrams/net/lan.dot".to_string(),
+            name: "lan.dot".to_string(),
+            depth: 2,
+        }
+    );
+    assert_eq!(
+        rows[3],
+        Entry::Folder {
+            path: "diagrams/net".to_string(),
+            name: "net".to_string(),
+            depth: 1,
+        }
+    );
+}
+
+#[test]
+fn closed_folders_hide_their_contents() {
+    let keys = keys(&["a/b/c.dot", "a/d.dot", "e.dot"]);
+    let closed = HashSet::from(["a/b".to_string()]);
+    assert_eq!(
+        outline(&entries(&keys, "", &closed)),
+        ["a/", "  b/", "  d.dot", "e.dot"]
+    );
+
+    let closed = HashSet::from(["a".to_string()]);
+    assert_eq!(outline(&entries(&keys, "", &closed)), ["a/", "e.dot"]);
+}
+
+#[test]
+fn searching_matches_whole_keys_and_opens_folders() {
+    let keys = keys(&["Net/LAN.dot", "net/wan.dot", "flow.dot"]);
+    let closed = HashSet::from(["Net".to_string(), "net".to_string()]);
+    assert_eq!(
+        outline(&entries(&keys, " lan ", &closed)),
+        ["Net/", "  LAN.dot"]
+    );
+    assert_eq!(
+        outline(&entries(&keys, "NET/", &closed)),
+        ["Net/", "  LAN.dot", "net/", "  wan.dot"]
+    );
+    assert!(entries(&keys, "missing", &closed).is_empty());
+}
+
+#[test]
+fn names_become_dot_keys() {
+    assert_eq!(document_key("flow").as_deref(), Some("flow.dot"));
+    assert_eq!(document_key(" flow.dot ").as_deref(), Some("flow.dot"));
+    assert_eq!(
+        document_key("/diagrams/flow/").as_deref(),
+        Some("diagrams/flow.dot")
+    );
+    assert_eq!(
+        document_key("my notes/v2 draft").as_deref(),
+        Some("my notes/v2 draft.dot")
+    );
+}
+
+#[test]
+fn names_that_leave_or_hide_the_folder_are_rejected() {
+    for name in [
+        "",
+        "  ",
+        "/",
+        "a//b",
+        "a/ /b",
+        ".",
+        "..",
+        "../x",
+        "a/../../x",
+        "./a",
+        ".settings/theme",
+        "a/.hidden",
+    ] {
+        assert_eq!(document_key(name), None, "{name:?}");
+    }
+}
+
+#[test]
+fn sidecars_are_hidden_and_named_after_the_document() {
+    assert_eq!(
+        sidecar_keys("diagrams/flow.dot"),
+        [
+            ".settings/diagrams/flow.rough".to_string(),
+            ".history/diagrams/flow.history".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn renaming_moves_the_sidecars_that_exist() {
+    let [rough, history] = sidecar_keys("a.dot");
+    let storage = storage_with(&["a.dot", &history]);
+    rename_document(&storage, "a.dot", "b.dot").unwrap();
+
+    let [new_rough, new_history] = sidecar_keys("b.dot");
+    assert_eq!(
+        storage.list("").unwrap(),
+        [new_history.clone(), "b.dot".to_string()]
+    );
+    assert_eq!(storage.load(&new_history).unwrap(), history.as_bytes());
+    assert!(!storage.exists(&rough) && !storage.exists(&new_rough));
+}
+
+#[test]
+fn renaming_onto_a_taken_sidecar_moves_nothing() {
+    let [_, history] = sidecar_keys("a.dot");
+    let [_, taken] = sidecar_keys("b.dot");
+    let storage = storage_with(&["a.dot", &history, &taken]);
+    assert_eq!(
+        rename_document(&storage, "a.dot", "b.dot"),
+        Err(StorageError::AlreadyExists(taken.clone()))
+    );
+    assert_eq!(
+        storage.list("").unwrap(),
+        [history, taken, "a.dot".to_string()]
+    );
+
+    let storage = storage_with(&["a.dot", "b.dot"]);
+    assert_eq!(
+        rename_document(&storage, "a.dot", "b.dot"),
+        Err(StorageError::AlreadyExists("b.dot".to_string()))
+    );
+}
+
+#[test]
+fn deleting_removes_the_sidecars_too() {
+    let [rough, _] = sidecar_keys("a.dot");
+    let storage = storage_with(&["a.dot", &rough, "b.dot"]);
+    delete_document(&storage, "a.dot").unwrap();
+    assert_eq!(storage.list("").unwrap(), ["b.dot"]);
+}
@@ -0,0 +1,65 @@
+//! Checks for the PDF written around exported JPEGs.
+use dot_repl_ui::export::jpeg_pdf;
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+/// The text of the PDF after its last `startxref`, which is all ASCII.
+fn tail(pdf: &[u8]) -> &str {
+    let start = pdf
+        .windows(b"xref\n0 ".len())
+        .rposition(|w| w == b"xref\n0 ")
+        .expect("an xref table");
+    std::str::from_utf8(&pdf[start..]).unwrap()
+}
+
+#[test]
+fn xref_offsets_point_at_their_objects() {
+    // Not a real JPEG, but with bytes that aren't valid UTF-8 and that look
+    // like PDF syntax, which the offsets must step over
+    let jpeg = [
+        0xff, 0xd8, b'\n', b'1', b' ', b'0', b' ', b'o', b'b', b'j', 0xff, 0xd9,
+    ];
+    let pdf = jpeg_pdf(&jpeg, 640, 480, 240.0, 180.0);
+    assert!(pdf.starts_with(b"%PDF-1.4\n"));
+    assert!(pdf.ends_with(b"%%EOF\n"));
+
+    let tail = tail(&pdf);
+    let mut lines = tail.lines();
+    assert_eq!(lines.next(), Some("xref"));
+    assert_eq!(lines.next(), Some("0 6"));
+    assert_eq!(lines.next(), Some("0000000000 65535 f "));
+    for object in 1..=5 {
+        let entry = lines.next().unwrap();
+        assert_eq!(entry.len(), 19, "{entry:?}");
+        assert!(entry.ends_with(" 00000 n "), "{entry:?}");
+        let offset: usize = entry[..10].parse().unwrap();
+        let header = format!("{object} 0 obj\n");
+        assert_eq!(
+            &pdf[offset..offset + header.len()],
+            header.as_bytes(),
+            "object {object}"
+        );
+    }
+    assert_eq!(lines.next(), Some("trailer"));
+    assert_eq!(lines.next(), Some("<< /Size 6 /Root 1 0 R >>"));
+    assert_eq!(lines.next(), Some("startxref"));
+    let startxref: usize = lines.next().unwrap().parse().unwrap();
+    assert_eq!(&pdf[startxref..startxref + 5], b"xref\n");
+    assert_eq!(startxref, pdf.len() - tail.len());
+}
+
+#[test]
+fn the_image_fills_the_page() {
+    let jpeg = [0xff, 0xd8, 0xff, 0xd9];
+    let pdf = jpeg_pdf(&jpeg, 640, 480, 240.0, 180.0);
+    let text = String::from_utf8_lossy(&pdf);
+    assert!(text.contains("/MediaBox [0 0 240.00 180.00]"));
+    assert!(text.contains("/Width 640 /Height 480"));
+    assert!(text.contains("/Filter /DCTDecode /Length 4 >>\nstream\n"));
+    assert!(text.contains("q 240.00 0 0 180.00 0 0 cm /Im0 Do Q"));
+
+    let image = find(&pdf, b"stream\n").unwrap() + b"stream\n".len();
+    assert_eq!(&pdf[image..image + jpeg.len()], jpeg);
+}
@@ -0,0 +1,125 @@
+//! Checks how edits are grouped into undo steps.
+use dot_repl_ui::history::{History, HISTORY_LIMIT, TYPING_PAUSE_MS};
+
+/// Type `texts` one after the other into `source`, starting at `at` with
+/// `gap` milliseconds between keystrokes.
+fn type_into(history: &mut History, source: &mut String, texts: &[&str], at: u64, gap: u64) {
+    for (i, text) in texts.iter().enumerate() {
+        history.record_typing(source, at + i as u64 * gap);
+        *source = text.to_string();
+    }
+}
+
+#[test]
+fn a_typing_burst_is_one_step() {
+    let mut history = History::default();
+    let mut source = String::new();
+    type_into(&mut history, &mut source, &["d", "di", "dig"], 0, 100);
+
+    assert_eq!(history.undo_label(), Some("Typing"));
+    assert_eq!(history.undo(source).as_deref(), Some(""));
+    assert!(!history.can_undo());
+}
+
+#[test]
+fn a_pause_starts_a_new_step() {
+    let mut history = History::default();
+    let mut source = String::new();
+    type_into(&mut history, &mut source, &["a", "ab"], 0, 100);
+    // Just under the pause still joins; the pause itself does not
+    type_into(
+        &mut history,
+        &mut source,
+        &["abc"],
+        100 + TYPING_PAUSE_MS - 1,
+        0,
+    );
+    type_into(
+        &mut history,
+        &mut source,
+        &["abcd"],
+        99 + 2 * TYPING_PAUSE_MS,
+        0,
+    );
+
+    let source = history.undo(source).unwrap();
+    assert_eq!(source, "abc");
+    assert_eq!(history.undo(source).as_deref(), Some(""));
+}
+
+#[test]
+fn a_labelled_edit_breaks_a_burst() {
+    let mut history = History::default();
+    let mut source = String::new();
+    type_into(&mut history, &mut source, &["a", "ab"], 0, 100);
+    history.record(&source, "Format");
+    source = "ab;".to_string();
+    type_into(&mut history, &mut source, &["ab;c"], 300, 0);
+
+    assert_eq!(history.undo_label(), Some("Typing"));
+    let source = history.undo(source).unwrap();
+    assert_eq!(source, "ab;");
+    assert_eq!(history.undo_label(), Some("Format"));
+    let source = history.undo(source).unwrap();
+    assert_eq!(source, "ab");
+    assert_eq!(history.undo(source).as_deref(), Some(""));
+}
+
+#[test]
+fn undo_ends_a_burst() {
+    let mut history = History::default();
+    let mut source = String::new();
+    type_into(&mut history, &mut source, &["a"], 0, 0);
+    type_into(&mut history, &mut source, &["ab"], TYPING_PAUSE_MS, 0);
+    source = history.undo(source).unwrap();
+    type_into(&mut history, &mut source, &["ax"], TYPING_PAUSE_MS + 10, 0);
+
+    let source = history.undo(source).unwrap();
+    assert_eq!(source, "a");
+}
+
+#[test]
+fn undo_and_redo_swap_steps() {
+    let mut history = History::default();
+    history.record("a", "Format");
+
+    assert_eq!(history.undo("b".to_string()).as_deref(), Some("a"));
+    assert_eq!(history.redo_label(), Some("Format"));
+    assert_eq!(history.redo("a".to_string()).as_deref(), Some("b"));
+    assert!(!history.can_redo());
+    assert_eq!(history.undo_label(), Some("Format"));
+    assert_eq!(history.redo("b".to_string()), None);
+}
+
+#[test]
+fn a_new_edit_clears_redo() {
+    let mut history = History::default();
+    history.record("a", "Format");
+    history.undo("b".to_string());
+    assert!(history.can_redo());
+    history.record("a", "Delete node a");
+    assert!(!history.can_redo());
+
+    // So does typing
+    history.undo("b".to_string());
+    assert!(history.can_redo());
+    history.record_typing("a", 0);
+    assert!(!history.can_redo());
+}
+
+#[test]
+fn the_oldest_steps_are_dropped_past_the_limit() {
+    let mut history = History::default();
+    for i in 0..=HISTORY_LIMIT {
+        history.record(&i.to_string(), "Edit");
+    }
+
+    let mut undone = Vec::new();
+    let mut source = String::new();
+    while let Some(before) = history.undo(source) {
+        undone.push(before.clone());
+        source = before;
+    }
+    assert_eq!(undone.len(), HISTORY_LIMIT);
+    assert_eq!(undone.last().map(String::as_str), Some("1"));
+}
@@ -0,0 +1,147 @@
+//! Checks the revision log's encoding and what it keeps.
+use dot_repl_ui::revisions::{revisions_key, Retention, RevisionLog};
+
+/// Room for any number of revisions, each in its own interval.
+const KEEP_ALL: Retention = Retention {
+    max_revisions: usize::MAX,
+    max_bytes: usize::MAX,
+    interval_ms: 1,
+};
+
+fn log_of(contents: &[&str], retention: &Retention) -> RevisionLog {
+    let mut log = RevisionLog::default();
+    for (i, content) in contents.iter().enumerate() {
+        log.record(i as u64 * 1000, content, retention);
+    }
+    log
+}
+
+fn contents(log: &RevisionLog) -> Vec<&str> {
+    log.revisions().iter().map(|r| r.content.as_str()).collect()
+}
+
+#[test]
+fn logs_are_kept_under_the_history_prefix() {
+    assert_eq!(
+        revisions_key("diagrams/flow.dot"),
+        ".history/diagrams/flow.history"
+    );
+}
+
+#[test]
+fn later_revisions_are_stored_as_the_changed_middle() {
+    let log = log_of(
+        &["digraph { a -> b }", "digraph { a -> c -> b }", "graph { }"],
+        &KEEP_ALL,
+    );
+    assert_eq!(
+        log.encode(),
+        "full 0 18\ndigraph { a -> b }\n\
+         delta 1000 15 3 5\nc -> \n\
+         delta 2000 0 2 7\ngraph {\n"
+    );
+}
+
+#[test]
+fn decoding_rebuilds_every_revision() {
+    let log = log_of(
+        &[
+            "digraph {\n  a\n}",
+            "digraph {\n  a -> b\n}",
+            "digraph {\n  é -> b\n}",
+            "",
+            "digraph {\n  é -> ü\n}\n",
+        ],
+        &KEEP_ALL,
+    );
+    let decoded = RevisionLog::decode(&log.encode()).unwrap();
+    assert_eq!(decoded, log);
+    assert_eq!(contents(&decoded)[2], "digraph {\n  é -> b\n}");
+    assert_eq!(decoded.revisions()[4].timestamp_ms, 4000);
+}
+
+#[test]
+fn shared_ends_stay_on_character_boundaries() {
+    // The last bytes of 'é' and 'ü' differ, their first ones don't
+    let log = log_of(&["aéz", "aüz"], &KEEP_ALL);
+    assert_eq!(log.encode(), "full 0 4\naéz\ndelta 1000 1 1 2\nü\n");
+    assert_eq!(RevisionLog::decode(&log.encode()).unwrap(), log);
+}
+
+#[test]
+fn bad_logs_are_rejected() {
+    for text in [
+        "full 0 10\nshort\n",
+        "delta 0 0 0 1\nx\n",
+        "full 0 3\nabc\ndelta 1 2 2 0\n\n",
+        "full 0 3\nabc",
+        "full x 3\nabc\n",
+        "partial 0 3\nabc\n",
+        "full 0 1\né\n",
+    ] {
+        assert!(RevisionLog::decode(text).is_err(), "{text:?}");
+    }
+    assert_eq!(RevisionLog::decode("").unwrap(), RevisionLog::default());
+}
+
+#[test]
+fn unchanged_content_adds_nothing() {
+    let mut log = RevisionLog::default();
+    assert!(log.record(0, "a", &KEEP_ALL));
+    assert!(!log.record(1000, "a", &KEEP_ALL));
+    assert_eq!(log.revisions().len(), 1);
+}
+
+#[test]
+fn saves_in_one_interval_replace_the_newest_revision() {
+    let retention = Retention {
+        interval_ms: 60_000,
+        ..KEEP_ALL
+    };
+    let mut log = RevisionLog::default();
+    for (at, content) in [
+        (0, "first"),
+        (10_000, "second"),
+        (20_000, "third"),
+        (60_000, "fourth"),
+        (119_999, "fifth"),
+    ] {
+        log.record(at, content, &retention);
+    }
+    // The first revision is never replaced
+    let kept: Vec<_> = log
+        .revisions()
+        .iter()
+        .map(|r| (r.timestamp_ms, r.content.as_str()))
+        .collect();
+    assert_eq!(kept, [(0, "first"), (20_000, "third"), (119_999, "fifth")]);
+}
+
+#[test]
+fn the_oldest_revisions_go_past_the_count() {
+    let retention = Retention {
+        max_revisions: 3,
+        ..KEEP_ALL
+    };
+    let log = log_of(&["a", "b", "c", "d", "e"], &retention);
+    assert_eq!(contents(&log), ["c", "d", "e"]);
+}
+
+#[test]
+fn the_oldest_revisions_go_past_the_size() {
+    let mut log = log_of(&["aaaaaaaaaa", "bbbbbbbbbb", "cccccccccc"], &KEEP_ALL);
+    let full = log.encode().len();
+    log.apply(&Retention {
+        max_bytes: full - 1,
+        ..KEEP_ALL
+    });
+    assert_eq!(contents(&log), ["bbbbbbbbbb", "cccccccccc"]);
+    assert!(log.encode().len() < full);
+
+    // One revision is kept whatever its size
+    log.apply(&Retention {
+        max_bytes: 0,
+        ..KEEP_ALL
+    });
+    assert_eq!(contents(&log), ["cccccccccc"]);
+}
@@ -0,0 +1,82 @@
+//! Checks that rough output is deterministic, and stable under edits.
+#![cfg(feature = "rough")]
+use dot_repl_ui::components::{roughen_svg, RoughOptions};
+
+/// A Graphviz SVG for `digraph { a -> b }`, with node `a` drawn at
+/// `a_shape`.
+fn graph(a_shape: &str) -> String {
+    format!(
+        r##"<?xml version="1.0" encoding="UTF-8" standalone="no"?>
+<!DOCTYPE svg PUBLIC "-//W3C//DTD SVG 1.1//EN" "http://www.w3.org/Graphics/SVG/1.1/DTD/svg11.dtd">
+<svg width="62pt" height="116pt" viewBox="0.00 0.00 62.00 116.00" xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink">
+<g id="graph0" class="graph" transform="scale(1 1) rotate(0) translate(4 112)">
+<title>%3</title>
+<polygon fill="white" stroke="none" points="-4,4 -4,-112 58,-112 58,4 -4,4"/>
+<g id="node1" class="node">
+<title>a</title>
+{a_shape}
+<text text-anchor="middle" x="27" y="-86.3" font-family="Times,serif" font-size="14.00">a</text>
+</g>
+<g id="node2" class="node">
+<title>b</title>
+<ellipse fill="#e8f4ff" stroke="black" cx="27" cy="-18" rx="27" ry="18"/>
+<text text-anchor="middle" x="27" y="-14.3" font-family="Times,serif" font-size="14.00">b</text>
+</g>
+<g id="edge1" class="edge">
+<title>a&#45;&gt;b</title>
+<path fill="none" stroke="black" d="M27,-71.7C27,-63.98 27,-54.71 27,-46.11"/>
+<polygon fill="black" stroke="black" points="30.5,-46.1 27,-36.1 23.5,-46.1 30.5,-46.1"/>
+</g>
+</g>
+</svg>"##
+    )
+}
+
+const A: &str = r##"<ellipse fill="none" stroke="black" cx="27" cy="-90" rx="27" ry="18"/>"##;
+
+/// The `d` attributes drawn for the element titled `title`.
+fn paths_of(svg: &str, title: &str) -> Vec<String> {
+    let marker = format!("<title>{title}</title>");
+    let start = svg
+        .find(&marker)
+        .unwrap_or_else(|| panic!("no element titled {title}"));
+    let rest = &svg[start + marker.len()..];
+    let end = rest.find("<title>").unwrap_or(rest.len());
+    rest[..end]
+        .split(" d=\"")
+        .skip(1)
+        .map(|path| path.split('"').next().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn same_input_and_seed_give_the_same_output() {
+    let options = RoughOptions::default();
+    let first = roughen_svg(&graph(A), &options).unwrap();
+    let second = roughen_svg(&graph(A), &options).unwrap();
+    assert_eq!(first, second);
+    assert!(!paths_of(&first, "b").is_empty(), "{first}");
+
+    let reseeded = roughen_svg(&graph(A), &RoughOptions { seed: 2, ..options }).unwrap();
+    assert_ne!(paths_of(&first, "b"), paths_of(&reseeded, "b"));
+}
+
+#[test]
+fn editing_one_node_leaves_the_others_as_drawn() {
+    let options = RoughOptions::default();
+    let before = roughen_svg(&graph(A), &options).unwrap();
+    let wider = r##"<ellipse fill="#ffeedd" stroke="red" cx="27" cy="-90" rx="40" ry="20"/>"##;
+    let after = roughen_svg(&graph(wider), &options).unwrap();
+
+    assert_ne!(paths_of(&before, "a"), paths_of(&after, "a"));
+    assert_eq!(paths_of(&before, "b"), paths_of(&after, "b"));
+    assert_eq!(paths_of(&before, "a-&gt;b"), paths_of(&after, "a-&gt;b"));
+}
+
+#[test]
+fn the_doctype_is_dropped_and_text_is_kept() {
+    let out = roughen_svg(&graph(A), &RoughOptions::default()).unwrap();
+    assert!(!out.contains("<!DOCTYPE"));
+    assert!(out.starts_with("<svg"));
+    assert!(out.contains(">b</text>"));
+}
@@ -0,0 +1,131 @@
+//! Checks providers for storage that only answers asynchronously.
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::pin;
+use std::sync::Mutex;
+use std::task::{Context, Poll, Waker};
+
+use dot_repl_ui::revisions::{load_revisions, record_revision, Retention};
+use dot_repl_ui::{AsyncPlatformStorage, LocalBoxFuture, Metadata, StorageError, StorageProvider};
+
+/// Storage whose futures are ready when first polled.
+#[derive(Default)]
+struct MemoryStorage(Mutex<BTreeMap<String, Vec<u8>>>);
+
+impl AsyncPlatformStorage for MemoryStorage {
+    fn save<'a>(
+        &'a self,
+        key: &'a str,
+        data: &'a [u8],
+    ) -> LocalBoxFuture<'a, Result<(), StorageError>> {
+        let mut files = self.0.lock().unwrap();
+        files.insert(key.to_string(), data.to_vec());
+        Box::pin(async { Ok(()) })
+    }
+
+    fn load<'a>(&'a self, key: &'a str) -> LocalBoxFuture<'a, Result<Vec<u8>, StorageError>> {
+        let data = self.0.lock().unwrap().get(key).cloned();
+        Box::pin(async move { data.ok_or_else(|| StorageError::NotFound(key.to_string())) })
+    }
+
+    fn delete<'a>(&'a self, key: &'a str) -> LocalBoxFuture<'a, Result<(), StorageError>> {
+        let removed = self.0.lock().unwrap().remove(key);
+        Box::pin(async move {
+            removed
+                .map(|_| ())
+                .ok_or_else(|| StorageError::NotFound(key.to_string()))
+        })
+    }
+
+    fn exists<'a>(&'a self, key: &'a str) -> LocalBoxFuture<'a, bool> {
+        let exists = self.0.lock().unwrap().contains_key(key);
+        Box::pin(async move { exists })
+    }
+
+    fn list<'a>(
+        &'a self,
+        prefix: &'a str,
+    ) -> LocalBoxFuture<'a, Result<Vec<String>, StorageError>> {
+        let files = self.0.lock().unwrap();
+        let keys = files.keys().filter(|key| key.starts_with(prefix));
+        let keys: Vec<String> = keys.cloned().collect();
+        Box::pin(async move { Ok(keys) })
+    }
+
+    fn rename<'a>(
+        &'a self,
+        from: &'a str,
+        to: &'a str,
+    ) -> LocalBoxFuture<'a, Result<(), StorageError>> {
+        let mut files = self.0.lock().unwrap();
+        let result = match files.remove(from) {
+            Some(data) => {
+                files.insert(to.to_string(), data);
+                Ok(())
+            }
+            None => Err(StorageError::NotFound(from.to_string())),
+        };
+        Box::pin(async move { result })
+    }
+
+    fn metadata<'a>(&'a self, key: &'a str) -> LocalBoxFuture<'a, Result<Metadata, StorageError>> {
+        Box::pin(async move { Err(StorageError::NotFound(key.to_string())) })
+    }
+}
+
+/// Poll `future` once: every future here is ready straight away.
+fn ready<T>(future: impl Future<Output = T>) -> T {
+    match pin!(future).poll(&mut Context::from_waker(Waker::noop())) {
+        Poll::Ready(value) => value,
+        Poll::Pending => panic!("the future is still pending"),
+    }
+}
+
+#[test]
+fn async_only_storage_answers_asynchronously() {
+    let storage = StorageProvider::new_async(MemoryStorage::default());
+    ready(storage.asynchronous().save("a.dot", b"graph {}")).unwrap();
+
+    assert_eq!(
+        ready(storage.asynchronous().load("a.dot")).unwrap(),
+        b"graph {}"
+    );
+    assert_eq!(
+        ready(storage.asynchronous().list("")).unwrap(),
+        ["a.dot".to_string()]
+    );
+    assert!(matches!(
+        storage.load("a.dot"),
+        Err(StorageError::Backend(_))
+    ));
+    assert!(matches!(
+        storage.save("b.dot", b""),
+        Err(StorageError::Backend(_))
+    ));
+    assert!(!storage.exists("a.dot"));
+}
+
+#[test]
+fn revisions_are_kept_in_async_only_storage() {
+    let storage = StorageProvider::new_async(MemoryStorage::default());
+    let retention = Retention {
+        interval_ms: 1,
+        ..Retention::default()
+    };
+    ready(record_revision(
+        &storage, "a.dot", "graph {}", 0, &retention,
+    ))
+    .unwrap();
+    ready(record_revision(
+        &storage,
+        "a.dot",
+        "graph { a }",
+        1000,
+        &retention,
+    ))
+    .unwrap();
+
+    let log = ready(load_revisions(&storage, "a.dot"));
+    let contents: Vec<&str> = log.revisions().iter().map(|r| r.content.as_str()).collect();
+    assert_eq!(contents, ["graph {}", "graph { a }"]);
+}
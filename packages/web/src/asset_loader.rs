@@ -1,12 +1,12 @@
-use crate::storage::{fnv1a_hex, WebStorage};
+use crate::storage::WebStorage;
 use dioxus::logger::tracing;
-use dot_repl_ui::PlatformStorage as _;
+use dot_repl_ui::{content_hash, PlatformStorage as _};
 use std::collections::HashSet;
 use wasm_bindgen::JsCast;
 use wasm_bindgen_futures::JsFuture;
 use web_sys::{window, Request, RequestInit, RequestMode, Response};
 
-/// Pre-populate LocalStorage with DOT files from static assets.
+/// Pre-populate storage with DOT files from static assets.
 ///
 /// ## Freshness strategy
 ///
@@ -16,7 +16,7 @@ use web_sys::{window, Request, RequestInit, RequestMode, Response};
 ///    running `dot_input` signal holds the user's work in memory; we never
 ///    touch that here.
 /// 2. **Server content is the source of truth on reload** – when the page
-///    reloads (or on first visit), we sync LocalStorage with the server's
+///    reloads (or on first visit), we sync storage with the server's
 ///    manifest. Any file on the server overwrites the local copy if it
 ///    differs (resetting any persistent edits from a previous session).
 ///    Files no longer in the manifest are removed from storage.
@@ -54,17 +54,17 @@ pub async fn preload_dot_files(storage: &WebStorage, dots_folder: &str) -> Resul
             }
         };
 
-        let server_hash = fnv1a_hex(content.as_bytes());
+        let server_hash = content_hash(content.as_bytes());
         let current_local_content = storage.load(filename).ok();
-        
+
         let needs_update = current_local_content
             .as_ref()
             .map(|c| c != content.as_bytes())
             .unwrap_or(true);
 
         if needs_update {
-            // The server content is different from what's in LocalStorage.
-            // Overwrite LocalStorage so the user sees fresh content from the server.
+            // The server content is different from what's in storage.
+            // Overwrite it so the user sees fresh content from the server.
             match storage.save(filename, content.as_bytes()) {
                 Ok(_) => {
                     storage.save_server_hash(filename, &server_hash);
@@ -86,7 +86,7 @@ pub async fn preload_dot_files(storage: &WebStorage, dots_folder: &str) -> Resul
         }
     }
 
-    // Cleanup: Remove any files that were previously tracked as "from server" 
+    // Cleanup: Remove any files that were previously tracked as "from server"
     // but are no longer in the current manifest.
     let tracked_keys = storage.get_all_server_tracked_keys();
     for key in tracked_keys {
@@ -97,7 +97,7 @@ pub async fn preload_dot_files(storage: &WebStorage, dots_folder: &str) -> Resul
         }
     }
 
-    tracing::info!("Preloaded {} DOT files into LocalStorage", loaded_count);
+    tracing::info!("Preloaded {} DOT files into storage", loaded_count);
     Ok(loaded_count)
 }
 
@@ -79,7 +79,7 @@ impl GViz {
 }
 
 /// Extract line number from error message
-fn extract_line_number(msg: &str) -> Option<u32> {
+pub(crate) fn extract_line_number(msg: &str) -> Option<u32> {
     // Look for "line" followed by digits
     msg.find("line").and_then(|pos| {
         let after = &msg[pos + 4..];
@@ -0,0 +1,265 @@
+//! IndexedDB database behind [WebStorage](crate::WebStorage).
+//!
+//! IndexedDB is asynchronous while [PlatformStorage](dot_repl_ui::PlatformStorage)
+//! is not, so the whole store is read into memory when the database opens and
+//! reads are served from there. Writes update memory at once and are queued to
+//! IndexedDB; transactions on the store run in the order they are started.
+//! Each write returns a [Write] resolving once it is committed, and a write
+//! that fails is undone in memory, so memory always ends up matching the
+//! database.
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::rc::Rc;
+
+use dioxus::logger::tracing;
+use dot_repl_ui::{LocalBoxFuture, StorageError};
+use js_sys::{Array, Promise, Uint8Array};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    DomException, Event, IdbDatabase, IdbObjectStore, IdbOpenDbRequest, IdbRequest,
+    IdbRequestReadyState, IdbTransaction, IdbTransactionMode,
+};
+
+const DATABASE_NAME: &str = "dot-repl";
+const DATABASE_VERSION: u32 = 1;
+/// Raw bytes, keyed by storage key.
+const STORE: &str = "files";
+
+pub(crate) struct Database {
+    db: IdbDatabase,
+    /// Shared with the [Write]s in flight, which undo themselves if they fail.
+    files: Rc<RefCell<BTreeMap<String, Vec<u8>>>>,
+}
+
+/// A write that memory already shows, resolving once IndexedDB has committed
+/// it. If that fails, e.g. over quota, memory goes back to how it was, unless
+/// the key has been written again since.
+///
+/// The write happens whether or not this is awaited; awaiting it only tells
+/// how it went.
+pub(crate) type Write = LocalBoxFuture<'static, Result<(), StorageError>>;
+
+impl Database {
+    /// Open the database, creating it on first use, and read all of it.
+    pub(crate) async fn open() -> Result<Self, StorageError> {
+        let factory = web_sys::window()
+            .and_then(|w| w.indexed_db().ok().flatten())
+            .ok_or_else(|| StorageError::Backend("IndexedDB is not available".to_string()))?;
+        let request = factory
+            .open_with_u32(DATABASE_NAME, DATABASE_VERSION)
+            .map_err(|err| js_error("open", err))?;
+
+        let upgrading = request.clone();
+        let onupgradeneeded = Closure::once(move |_: Event| {
+            let Ok(db) = upgrading
+                .result()
+                .and_then(|db| db.dyn_into::<IdbDatabase>())
+            else {
+                return;
+            };
+            if !db.object_store_names().contains(STORE) {
+                if let Err(err) = db.create_object_store(STORE) {
+                    tracing::error!("Failed to create the IndexedDB store: {:?}", err);
+                }
+            }
+        });
+        request.set_onupgradeneeded(Some(onupgradeneeded.as_ref().unchecked_ref()));
+        let opened = settle(&request).await;
+        request.set_onupgradeneeded(None);
+        let db: IdbDatabase = opened?.dyn_into().map_err(|err| js_error("open", err))?;
+        // Let another tab upgrade the database rather than block it
+        let onversionchange = Closure::<dyn Fn(Event)>::new({
+            let db = db.clone();
+            move |_: Event| db.close()
+        });
+        db.set_onversionchange(Some(onversionchange.as_ref().unchecked_ref()));
+        onversionchange.forget();
+
+        // Both requests are made and listened to before waiting, or the
+        // transaction may finish in between
+        let transaction = db
+            .transaction_with_str(STORE)
+            .map_err(|err| js_error("read", err))?;
+        let store = transaction
+            .object_store(STORE)
+            .map_err(|err| js_error("read", err))?;
+        let keys = store.get_all_keys().map_err(|err| js_error("read", err))?;
+        let values = store.get_all().map_err(|err| js_error("read", err))?;
+        let (keys, values) = (settle(&keys), settle(&values));
+        let keys = Array::from(&keys.await?);
+        let values = Array::from(&values.await?);
+
+        let files = keys
+            .iter()
+            .zip(values.iter())
+            .filter_map(|(key, value)| Some((key.as_string()?, Uint8Array::new(&value).to_vec())))
+            .collect();
+        Ok(Database {
+            db,
+            files: Rc::new(RefCell::new(files)),
+        })
+    }
+
+    pub(crate) fn get(&self, key: &str) -> Option<Vec<u8>> {
+        self.files.borrow().get(key).cloned()
+    }
+
+    pub(crate) fn contains(&self, key: &str) -> bool {
+        self.files.borrow().contains_key(key)
+    }
+
+    /// Every stored key, sorted.
+    pub(crate) fn keys(&self) -> Vec<String> {
+        self.files.borrow().keys().cloned().collect()
+    }
+
+    /// Store `data` under `key`.
+    pub(crate) fn put(&mut self, key: &str, data: &[u8]) -> Result<Write, StorageError> {
+        let (transaction, store) = self.write()?;
+        put(&store, key, data)?;
+        let previous = self
+            .files
+            .borrow_mut()
+            .insert(key.to_string(), data.to_vec());
+        Ok(self.settle_write(&transaction, key, Some(data.to_vec()), previous))
+    }
+
+    pub(crate) fn delete(&mut self, key: &str) -> Result<Write, StorageError> {
+        let (transaction, store) = self.write()?;
+        store
+            .delete(&JsValue::from_str(key))
+            .map_err(|err| js_error(key, err))?;
+        let previous = self.files.borrow_mut().remove(key);
+        Ok(self.settle_write(&transaction, key, None, previous))
+    }
+
+    /// The [Write] of `transaction`, which left `written` under `key` in
+    /// memory in place of `previous`.
+    fn settle_write(
+        &self,
+        transaction: &IdbTransaction,
+        key: &str,
+        written: Option<Vec<u8>>,
+        previous: Option<Vec<u8>>,
+    ) -> Write {
+        let done = committed(transaction);
+        let files = self.files.clone();
+        let key = key.to_string();
+        Box::pin(async move {
+            let result = done.await;
+            if let Err(err) = &result {
+                tracing::error!("Failed to write {} to IndexedDB: {}", key, err);
+                let mut files = files.borrow_mut();
+                if files.get(&key) == written.as_ref() {
+                    match previous {
+                        Some(data) => files.insert(key, data),
+                        None => files.remove(&key),
+                    };
+                }
+            }
+            result
+        })
+    }
+
+    /// Store all of `entries` in one transaction, and wait until it is
+    /// written.
+    pub(crate) async fn put_all(
+        &mut self,
+        entries: &[(String, Vec<u8>)],
+    ) -> Result<(), StorageError> {
+        let (transaction, store) = self.write()?;
+        for (key, data) in entries {
+            put(&store, key, data)?;
+        }
+        committed(&transaction).await?;
+        let mut files = self.files.borrow_mut();
+        for (key, data) in entries {
+            files.insert(key.clone(), data.clone());
+        }
+        Ok(())
+    }
+
+    fn write(&self) -> Result<(IdbTransaction, IdbObjectStore), StorageError> {
+        let transaction = self
+            .db
+            .transaction_with_str_and_mode(STORE, IdbTransactionMode::Readwrite)
+            .map_err(|err| js_error("write", err))?;
+        let store = transaction
+            .object_store(STORE)
+            .map_err(|err| js_error("write", err))?;
+        Ok((transaction, store))
+    }
+}
+
+fn put(store: &IdbObjectStore, key: &str, data: &[u8]) -> Result<(), StorageError> {
+    store
+        .put_with_key(&Uint8Array::from(data), &JsValue::from_str(key))
+        .map(|_| ())
+        .map_err(|err| js_error(key, err))
+}
+
+/// The result of `request`, once it has one. Listens from when it is called
+/// rather than when first polled, so no event is missed.
+fn settle(request: &IdbRequest) -> impl Future<Output = Result<JsValue, StorageError>> {
+    let open = request.dyn_ref::<IdbOpenDbRequest>();
+    let promise = Promise::new(&mut |resolve, reject| {
+        request.set_onsuccess(Some(&resolve));
+        request.set_onerror(Some(&reject));
+        // Opening waits for as long as another tab keeps an older version
+        // open, so give up instead
+        if let Some(open) = open {
+            open.set_onblocked(Some(&reject));
+        }
+    });
+    let request = request.clone();
+    async move {
+        let outcome = JsFuture::from(promise).await;
+        request.set_onsuccess(None);
+        request.set_onerror(None);
+        if let Some(open) = request.dyn_ref::<IdbOpenDbRequest>() {
+            open.set_onblocked(None);
+        }
+        match outcome {
+            Ok(_) => request.result().map_err(|err| js_error("request", err)),
+            Err(_) if request.ready_state() == IdbRequestReadyState::Pending => Err(
+                StorageError::Backend("open: blocked by another tab".to_string()),
+            ),
+            Err(_) => Err(dom_error("request", request.error().ok().flatten())),
+        }
+    }
+}
+
+/// Resolves once `transaction` is written, or fails if it aborts. Listens from
+/// when it is called, like [settle].
+fn committed(transaction: &IdbTransaction) -> impl Future<Output = Result<(), StorageError>> {
+    let promise = Promise::new(&mut |resolve, reject| {
+        transaction.set_oncomplete(Some(&resolve));
+        transaction.set_onerror(Some(&reject));
+        transaction.set_onabort(Some(&reject));
+    });
+    let transaction = transaction.clone();
+    async move {
+        JsFuture::from(promise)
+            .await
+            .map(|_| ())
+            .map_err(|_| dom_error("transaction", transaction.error()))
+    }
+}
+
+fn dom_error(context: &str, err: Option<DomException>) -> StorageError {
+    match err {
+        Some(err) if err.name() == "QuotaExceededError" => StorageError::QuotaExceeded,
+        Some(err) => StorageError::Backend(format!("{context}: {}: {}", err.name(), err.message())),
+        None => StorageError::Backend(format!("{context}: IndexedDB request failed")),
+    }
+}
+
+fn js_error(context: &str, err: JsValue) -> StorageError {
+    match err.dyn_into::<DomException>() {
+        Ok(err) => dom_error(context, Some(err)),
+        Err(err) => StorageError::Backend(format!("{context}: {:?}", err)),
+    }
+}
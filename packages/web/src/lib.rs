@@ -5,6 +5,10 @@ pub use error::Error;
 mod bindgen;
 use bindgen::GViz;
 
+mod worker;
+pub use worker::{WorkerGViz, RENDER_TIMEOUT, START_TIMEOUT};
+
+mod indexed_db;
 mod storage;
 pub use storage::WebStorage;
 pub mod asset_loader;
@@ -12,7 +16,9 @@ pub mod asset_loader;
 use dioxus::logger::tracing;
 use dioxus::prelude::*;
 
-use dot_repl_ui::{GVizProvider, PreloadComplete, StorageProvider};
+use dot_repl_ui::components::fonts;
+use dot_repl_ui::hooks::use_theme_provider;
+use dot_repl_ui::{GVizProvider, PreloadComplete, StorageError, StorageProvider};
 
 use crate::asset_loader::preload_dot_files;
 use gloo_timers::future::sleep;
@@ -22,13 +28,33 @@ use web_sys::js_sys::Reflect;
 
 #[component]
 pub fn WebApp(children: Element) -> Element {
+    // IndexedDB opens asynchronously, and nothing may read storage before it
+    // has, or it would find LocalStorage's contents
+    let storage = use_resource(WebStorage::open);
+    match storage() {
+        Some(storage) => rsx! {
+            OpenedWebApp { storage, children }
+        },
+        None => rsx! {},
+    }
+}
+
+/// [WebApp] once its storage is open.
+#[component]
+fn OpenedWebApp(storage: WebStorage, children: Element) -> Element {
     // Build cool things ✌️
-    let storage = storage::WebStorage::new();
     let storage_provider = StorageProvider::new(storage.clone());
 
+    // The graph theme, remembered in storage across sessions
+    use_theme_provider(storage_provider.clone());
+
     // provide storgae in context for all child elements
     use_context_provider(|| storage_provider);
 
+    // Writes IndexedDB turns down after they returned, e.g. over quota
+    let mut write_failure = use_signal(|| None::<StorageError>);
+    use_hook(|| storage.on_write_failure(move |err| write_failure.set(Some(err))));
+
     // signal that will be saved to the context as None, until GViz is loaded
     let gviz_signal = use_signal::<Option<GVizProvider>>(|| None);
     let mut gviz_signal = use_context_provider(|| gviz_signal);
@@ -43,55 +69,64 @@ pub fn WebApp(children: Element) -> Element {
     use_context_provider(|| preload_complete);
 
     // Preload DOT assets from the server on every cold page load.
-    // Uses a server-hash sentinel in LocalStorage to decide whether to
+    // Uses a server-hash sentinel in storage to decide whether to
     // overwrite existing data: if the server has a new version of a file it
     // gets written; if not, the user's local copy (possibly hand-edited) is
     // kept intact.  In-session edits are always safe — they live in signals.
     let preload_storage = storage.clone();
-    spawn(async move {
-        match preload_dot_files(&preload_storage, "/assets/dots").await {
-            Ok(n) => tracing::info!("Preloaded {} DOT file(s) from server", n),
-            Err(e) => tracing::warn!("DOT preload failed: {}", e),
-        }
-        // Signal to GraphView (and any other subscriber) that storage is ready.
-        preload_complete.set(true);
+    use_hook(move || {
+        spawn(async move {
+            match preload_dot_files(&preload_storage, "/assets/dots").await {
+                Ok(n) => tracing::info!("Preloaded {} DOT file(s) from server", n),
+                Err(e) => tracing::warn!("DOT preload failed: {}", e),
+            }
+            // Signal to GraphView (and any other subscriber) that storage is ready.
+            preload_complete.set(true);
+        })
     });
 
-    spawn(async move {
-        // Wait for the viz_instance_promise to be loaded
-        loop {
-            if let Ok(val) = Reflect::get(
-                &web_sys::window().unwrap(),
-                &JsValue::from_str("viz_instance"),
-            ) {
-                if !val.is_undefined() {
-                    break;
+    // Started once: a worker per render would load viz.js again each time
+    use_hook(move || {
+        spawn(async move {
+            // Render in a worker where possible, so typing never waits on
+            // Graphviz. A worker that fails or hangs while starting leaves
+            // rendering to the main thread.
+            match WorkerGViz::new().await {
+                Ok(worker) => {
+                    gviz_signal.set(Some(GVizProvider::new(worker)));
+                    return;
                 }
+                Err(e) => tracing::warn!("Rendering on the main thread: {}", e),
+            }
+
+            // Wait for the viz_instance_promise to be loaded
+            loop {
+                if let Ok(val) = Reflect::get(
+                    &web_sys::window().unwrap(),
+                    &JsValue::from_str("viz_instance"),
+                ) {
+                    if !val.is_undefined() {
+                        break;
+                    }
+                }
+                sleep(Duration::from_millis(50)).await;
             }
-            sleep(Duration::from_millis(50)).await;
-        }
 
-        let gviz = GViz::new()
-            .await
-            .map_err(|e| {
-                panic!("Failed to create GViz instance: {:?}", e);
-            })
-            .unwrap();
+            let gviz = GViz::new()
+                .await
+                .map_err(|e| {
+                    panic!("Failed to create GViz instance: {:?}", e);
+                })
+                .unwrap();
 
-        let gviz_provider = GVizProvider::new(gviz);
-        gviz_signal.set(Some(gviz_provider));
+            let gviz_provider = GVizProvider::new(gviz);
+            gviz_signal.set(Some(gviz_provider));
+        })
     });
 
     rsx! {
         // Global app resources
-        document::Link {
-            rel: "stylesheet",
-            href: "https://fonts.googleapis.com/css2?family=Architects+Daughter&display=swap"
-        }
-        document::Link {
-            rel: "stylesheet",
-            href: "https://fonts.googleapis.com/css2?family=Noto+Sans+Symbols+2&display=swap"
-        }
+        document::Style { {fonts::page_font_css()} }
         document::Script {
             r#type: "module",
             r#"
@@ -100,5 +135,14 @@ pub fn WebApp(children: Element) -> Element {
             "#
         }
         {children}
+        if let Some(err) = write_failure() {
+            div {
+                class: "fixed bottom-2 right-2 px-3 py-2 text-sm bg-red-50 text-red-700 border border-red-200 rounded shadow cursor-pointer",
+                role: "alert",
+                title: "Dismiss",
+                onclick: move |_| write_failure.set(None),
+                "Not saved: {err}"
+            }
+        }
     }
 }
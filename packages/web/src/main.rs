@@ -2,6 +2,7 @@
 
 use dioxus::prelude::*;
 use dot_repl_ui::components::fonts::ARCHITECTS_DAUGHTER_FAMILY;
+use dot_repl_ui::components::{FileBrowser, ThemePicker};
 use dot_repl_ui::Navbar;
 use dot_repl_web::WebApp;
 
@@ -49,6 +50,7 @@ fn App() -> Element {
 /// which allows us to use the web-specific `Route` enum.
 #[component]
 fn WebNavbar() -> Element {
+    let route = use_route::<Route>();
     let mut rough_enabled = use_context::<Signal<bool>>();
     rsx! {
         div {
@@ -74,9 +76,17 @@ fn WebNavbar() -> Element {
                     }
                     "Rough Style"
                 }
+                ThemePicker {}
             }
         }
 
-        Outlet::<Route> {}
+        div {
+            class: "flex flex-1 min-h-0",
+            FileBrowser { route }
+            div {
+                class: "flex-1 min-w-0 overflow-auto",
+                Outlet::<Route> {}
+            }
+        }
     }
 }
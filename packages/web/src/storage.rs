@@ -1,85 +1,326 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::rc::Rc;
+
 use base64::{engine::general_purpose::STANDARD_NO_PAD, Engine as _};
-use dot_repl_ui::PlatformStorage;
-use gloo_storage::{LocalStorage, Storage};
+use dioxus::logger::tracing;
+use dot_repl_ui::{content_hash, Metadata, PlatformStorage, StorageError};
+use gloo_storage::{errors::StorageError as GlooError, LocalStorage, Storage};
+use wasm_bindgen_futures::spawn_local;
+
+use crate::indexed_db::{Database, Write};
 
-#[derive(Clone)]
+/// Storage for the web build. Files live in IndexedDB once [WebStorage::open]
+/// has opened it, and in LocalStorage (base64-encoded) where it can't.
+#[derive(Clone, PartialEq)]
 pub struct WebStorage;
 
+thread_local! {
+    /// The database [WebStorage::open] opened, if any. The browser runs us on
+    /// one thread, and `IdbDatabase` can't be shared between threads.
+    static DATABASE: RefCell<Option<Database>> = const { RefCell::new(None) };
+
+    /// Told about writes that fail after they returned, see
+    /// [WebStorage::on_write_failure].
+    static ON_WRITE_FAILURE: RefCell<Option<Rc<dyn Fn(StorageError)>>> =
+        const { RefCell::new(None) };
+}
+
+/// `f` applied to the open database, or `None` when LocalStorage is in use.
+fn with_database<T>(f: impl FnOnce(&mut Database) -> T) -> Option<T> {
+    DATABASE.with_borrow_mut(|database| database.as_mut().map(f))
+}
+
 impl PlatformStorage for WebStorage {
-    fn save(&self, key: &str, data: &[u8]) -> Result<(), String> {
-        // Convert binary data to Base64 string for storage
-        let encoded = STANDARD_NO_PAD.encode(data);
-        LocalStorage::set(key, encoded).map_err(|err| format!("Failed to save data: {:?}", err))
+    fn save(&self, key: &str, data: &[u8]) -> Result<(), StorageError> {
+        match with_database(|db| db.put(key, data)) {
+            Some(write) => report(write?),
+            None => {
+                // Convert binary data to Base64 string for storage
+                let encoded = STANDARD_NO_PAD.encode(data);
+                LocalStorage::set(key, encoded).map_err(|err| storage_error(key, err))?;
+            }
+        }
+        // Best-effort, like the server hash
+        let now = js_sys::Date::now() as u64;
+        set_text(&modified_key(key), &now.to_string());
+        Ok(())
     }
 
-    fn load(&self, key: &str) -> Result<Vec<u8>, String> {
+    fn load(&self, key: &str) -> Result<Vec<u8>, StorageError> {
+        if let Some(data) = with_database(|db| db.get(key)) {
+            return data.ok_or_else(|| StorageError::NotFound(key.to_string()));
+        }
         // Retrieve Base64 string and convert back to binary
-        let encoded: String =
-            LocalStorage::get(key).map_err(|err| format!("Failed to load data: {:?}", err))?;
+        let encoded: String = LocalStorage::get(key).map_err(|err| storage_error(key, err))?;
 
         STANDARD_NO_PAD
             .decode(&encoded)
-            .map_err(|err| format!("Failed to decode data: {:?}", err))
+            .map_err(|err| StorageError::Corrupt {
+                key: key.to_string(),
+                message: err.to_string(),
+            })
     }
 
-    fn delete(&self, key: &str) -> Result<(), String> {
-        // Remove the key from local storage
-        LocalStorage::delete(key);
+    fn delete(&self, key: &str) -> Result<(), StorageError> {
+        if let Some(write) = with_database(|db| db.delete(key)) {
+            report(write?);
+        } else {
+            // Remove the key from local storage
+            LocalStorage::delete(key);
+        }
+        remove_text(&modified_key(key));
         Ok(())
     }
 
     fn exists(&self, key: &str) -> bool {
-        // Check if key exists in local storage
-        LocalStorage::get::<String>(key).is_ok()
+        with_database(|db| db.contains(key))
+            // Check if key exists in local storage
+            .unwrap_or_else(|| LocalStorage::get::<String>(key).is_ok())
+    }
+
+    fn list(&self, prefix: &str) -> Result<Vec<String>, StorageError> {
+        let all = all_keys()?;
+        let mut keys: Vec<String> = owned_keys(&all)
+            .filter(|key| key.starts_with(prefix) && !in_hidden_folder(key))
+            .cloned()
+            .collect();
+        keys.sort();
+        Ok(keys)
+    }
+
+    fn rename(&self, from: &str, to: &str) -> Result<(), StorageError> {
+        if self.exists(to) {
+            return Err(StorageError::AlreadyExists(to.to_string()));
+        }
+        let data = self.load(from)?;
+        self.save(to, &data)?;
+        // The content is unchanged, so it keeps its modification time
+        if let Some(modified) = get_text(&modified_key(from)) {
+            set_text(&modified_key(to), &modified);
+        }
+        // The asset loader deletes server-tracked keys missing from its
+        // manifest, so the renamed file stays the user's and `from` is fetched
+        // again on the next preload
+        remove_text(&server_hash_key(from));
+        self.delete(from)
+    }
+
+    fn metadata(&self, key: &str) -> Result<Metadata, StorageError> {
+        let data = self.load(key)?;
+        let modified_ms = get_text(&modified_key(key)).and_then(|ms| ms.parse().ok());
+        Ok(Metadata {
+            size: data.len() as u64,
+            modified_ms,
+            hash: content_hash(&data),
+        })
+    }
+}
+
+fn storage_error(key: &str, err: GlooError) -> StorageError {
+    match err {
+        GlooError::KeyNotFound(_) => StorageError::NotFound(key.to_string()),
+        GlooError::JsError(err) if err.name == "QuotaExceededError" => StorageError::QuotaExceeded,
+        GlooError::SerdeError(err) => StorageError::Corrupt {
+            key: key.to_string(),
+            message: err.to_string(),
+        },
+        err => StorageError::Backend(format!("{key}: {err}")),
+    }
+}
+
+/// Every key in LocalStorage, sentinels included.
+fn local_storage_keys() -> Result<Vec<String>, StorageError> {
+    let local_storage = web_sys::window()
+        .and_then(|w| w.local_storage().ok().flatten())
+        .ok_or_else(|| StorageError::Backend("LocalStorage is not available".to_string()))?;
+    let length = local_storage.length().unwrap_or(0);
+    Ok((0..length)
+        .filter_map(|i| local_storage.key(i).ok().flatten())
+        .collect())
+}
+
+/// The keys of `all` that [WebStorage] stored, without sentinels.
+/// LocalStorage is shared with everything else on the origin, so these are
+/// the documents and the keys with a sentinel, see [server_hash_key] and
+/// [modified_key].
+fn owned_keys(all: &[String]) -> impl Iterator<Item = &String> {
+    let tracked: HashSet<&str> = all
+        .iter()
+        .filter_map(|key| {
+            key.strip_suffix(MODIFIED_SUFFIX)
+                .or_else(|| key.strip_suffix(SERVER_HASH_SUFFIX))
+        })
+        .collect();
+    all.iter()
+        .filter(move |key| key.ends_with(".dot") || tracked.contains(key.as_str()))
+}
+
+/// Every stored key, sentinels included.
+fn all_keys() -> Result<Vec<String>, StorageError> {
+    with_database(|db| db.keys())
+        .map(Ok)
+        .unwrap_or_else(local_storage_keys)
+}
+
+/// The text stored under a sentinel key. IndexedDB keeps it as UTF-8 bytes.
+fn get_text(key: &str) -> Option<String> {
+    with_database(|db| {
+        db.get(key)
+            .map(|data| String::from_utf8_lossy(&data).to_string())
+    })
+    .unwrap_or_else(|| LocalStorage::get::<String>(key).ok())
+}
+
+/// Store text under a sentinel key. Best-effort, so errors are ignored.
+fn set_text(key: &str, value: &str) {
+    match with_database(|db| db.put(key, value.as_bytes())) {
+        Some(write) => settle(write),
+        None => {
+            let _ = LocalStorage::set(key, value);
+        }
     }
 }
 
+fn remove_text(key: &str) {
+    match with_database(|db| db.delete(key)) {
+        Some(write) => settle(write),
+        None => LocalStorage::delete(key),
+    }
+}
+
+/// Let a best-effort `write` finish, so it is undone in memory if it fails.
+fn settle(write: Result<Write, StorageError>) {
+    if let Ok(write) = write {
+        spawn_local(async move {
+            let _ = write.await;
+        });
+    }
+}
+
+/// Let `write` finish, and tell [WebStorage::on_write_failure] if it fails.
+fn report(write: Write) {
+    spawn_local(async move {
+        if let Err(err) = write.await {
+            if let Some(on_failure) = ON_WRITE_FAILURE.with_borrow(Clone::clone) {
+                on_failure(err);
+            }
+        }
+    });
+}
+
+/// Move what [WebStorage] keeps in LocalStorage into `db`, returning how many
+/// keys were moved. Only its own keys and their sentinels move, see
+/// [owned_keys]; whatever else the origin keeps there stays. Keys are removed
+/// from LocalStorage once IndexedDB holds them, so this only does something
+/// the first time, or after a session that had to fall back to LocalStorage;
+/// what that session saved replaces what IndexedDB holds.
+async fn migrate_local_storage(db: &mut Database) -> Result<usize, StorageError> {
+    let all = local_storage_keys()?;
+    let sentinels = all
+        .iter()
+        .filter(|key| key.ends_with(MODIFIED_SUFFIX) || key.ends_with(SERVER_HASH_SUFFIX));
+    let mut entries = Vec::new();
+    for key in owned_keys(&all).chain(sentinels) {
+        let Ok(value) = LocalStorage::get::<String>(key) else {
+            continue;
+        };
+        // Sentinels hold plain text, files base64
+        let data = if key.contains('\0') {
+            value.into_bytes()
+        } else {
+            match STANDARD_NO_PAD.decode(&value) {
+                Ok(data) => data,
+                Err(_) => {
+                    tracing::warn!("Leaving {} in LocalStorage: it isn't a stored file", key);
+                    continue;
+                }
+            }
+        };
+        entries.push((key.clone(), data));
+    }
+    if entries.is_empty() {
+        return Ok(0);
+    }
+    db.put_all(&entries).await?;
+    for (key, _) in &entries {
+        LocalStorage::delete(key);
+    }
+    Ok(entries.len())
+}
+
 // You might also want to add a constructor
 impl WebStorage {
+    /// Storage in LocalStorage, or in IndexedDB once [WebStorage::open] has
+    /// opened it.
     pub fn new() -> Self {
         WebStorage
     }
 
+    /// Open IndexedDB and move anything left in LocalStorage into it. Falls
+    /// back to LocalStorage, logging why, where IndexedDB is unavailable
+    /// (e.g. some private browsing modes) or the move fails.
+    pub async fn open() -> Self {
+        if with_database(|_| ()).is_some() {
+            return WebStorage;
+        }
+        let mut db = match Database::open().await {
+            Ok(db) => db,
+            Err(err) => {
+                tracing::warn!("Using LocalStorage, IndexedDB failed to open: {}", err);
+                return WebStorage;
+            }
+        };
+        match migrate_local_storage(&mut db).await {
+            Ok(0) => {}
+            Ok(n) => tracing::info!("Moved {} key(s) from LocalStorage to IndexedDB", n),
+            Err(err) => {
+                tracing::warn!("Using LocalStorage, moving it to IndexedDB failed: {}", err);
+                return WebStorage;
+            }
+        }
+        DATABASE.with_borrow_mut(|database| *database = Some(db));
+        WebStorage
+    }
+
+    /// Call `on_failure` with each write to IndexedDB that fails after
+    /// [PlatformStorage::save] or [PlatformStorage::delete] returned, e.g.
+    /// over quota. The change is undone by then, so reads no longer show it.
+    pub fn on_write_failure(&self, on_failure: impl Fn(StorageError) + 'static) {
+        ON_WRITE_FAILURE.with_borrow_mut(|callback| *callback = Some(Rc::new(on_failure)));
+    }
+
     /// Save the hash of the last server-provided content for `key`.
     /// This is stored under a separate sentinel key so it never collides with
     /// user content.
     pub fn save_server_hash(&self, key: &str, hash: &str) {
         let sentinel = server_hash_key(key);
         // Ignore errors — this is best-effort bookkeeping.
-        let _ = LocalStorage::set(&sentinel, hash);
+        set_text(&sentinel, hash);
     }
 
     /// Return the previously-stored server hash for `key`, if any.
     pub fn load_server_hash(&self, key: &str) -> Option<String> {
         let sentinel = server_hash_key(key);
-        LocalStorage::get::<String>(&sentinel).ok()
+        get_text(&sentinel)
     }
 
     /// Delete the server-hash sentinel for `key`.
     pub fn delete_server_hash(&self, key: &str) {
         let sentinel = server_hash_key(key);
-        LocalStorage::delete(&sentinel);
+        remove_text(&sentinel);
     }
 
     /// Return all keys that currently have a server-hash sentinel in storage.
     /// This allows the asset loader to identify and remove files that are no
     /// longer present in the server's manifest.
     pub fn get_all_server_tracked_keys(&self) -> Vec<String> {
-        let window = web_sys::window().expect("no window");
-        let local_storage = window.local_storage().expect("no local storage").expect("no local storage");
-        let length = local_storage.length().expect("no length");
-        let mut keys = Vec::new();
-
-        for i in 0..length {
-            if let Ok(Some(key)) = local_storage.key(i) {
-                if key.ends_with("\0__server_hash") {
-                    // Strip the sentinel suffix to get the original key
-                    let original_key = &key[..key.len() - "\0__server_hash".len()];
-                    keys.push(original_key.to_string());
-                }
-            }
-        }
-        keys
+        all_keys()
+            .unwrap_or_default()
+            .into_iter()
+            // Strip the sentinel suffix to get the original key
+            .filter_map(|key| key.strip_suffix(SERVER_HASH_SUFFIX).map(str::to_string))
+            .collect()
     }
 }
 
@@ -89,21 +330,25 @@ impl Default for WebStorage {
     }
 }
 
-/// The `LocalStorage` key under which we store the server-side content hash
+const SERVER_HASH_SUFFIX: &str = "\0__server_hash";
+const MODIFIED_SUFFIX: &str = "\0__modified";
+
+/// The key under which we store the server-side content hash
 /// for a given user-facing key.  The `\0` separator makes it impossible for a
 /// legitimate filename to collide with this key.
 fn server_hash_key(key: &str) -> String {
-    format!("{}\0__server_hash", key)
+    format!("{key}{SERVER_HASH_SUFFIX}")
 }
 
-/// Compute a cheap, stable hash of a byte slice.
-/// We use FNV-1a (64-bit) — it's tiny, deterministic, and has no external
-/// dependencies beyond what we already pull in.
-pub fn fnv1a_hex(data: &[u8]) -> String {
-    let mut hash: u64 = 0xcbf29ce484222325;
-    for &byte in data {
-        hash ^= u64::from(byte);
-        hash = hash.wrapping_mul(0x100000001b3);
-    }
-    format!("{:016x}", hash)
+/// The key recording when `key` was last saved, in milliseconds since the
+/// Unix epoch. Neither backend keeps timestamps itself.
+fn modified_key(key: &str) -> String {
+    format!("{key}{MODIFIED_SUFFIX}")
+}
+
+/// Whether `key` is in a `.`-prefixed folder, like the settings and revision
+/// logs. Listings leave those out, as the desktop backends do.
+fn in_hidden_folder(key: &str) -> bool {
+    key.rsplit_once('/')
+        .is_some_and(|(folders, _)| folders.split('/').any(|folder| folder.starts_with('.')))
 }
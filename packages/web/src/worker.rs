@@ -0,0 +1,424 @@
+//! Rendering DOT in a Web Worker, so large graphs don't freeze the page.
+//!
+//! The worker loads viz.js itself and talks to [WorkerGViz] in messages:
+//!
+//! - worker → page `{ ready: true }` once viz.js has loaded
+//! - page → worker `{ id, dot }` to render `dot`
+//! - worker → page `{ id, svg }` with the rendered `<svg>`, or
+//!   `{ id, errors: [{ level, message }] }` where Graphviz failed
+//!
+//! Only one request is with the worker at a time; the rest wait in a queue.
+//! Requests whose future is dropped before they are sent are skipped, and
+//! replaced by the next one queued, so while someone types only their latest
+//! source is rendered. A render that takes longer than [RENDER_TIMEOUT] fails,
+//! and the worker is replaced, as there is no other way to stop Graphviz. A
+//! worker that hasn't loaded viz.js within [START_TIMEOUT] is given up on.
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::rc::{Rc, Weak};
+use std::time::Duration;
+
+use dioxus::logger::tracing;
+use dot_repl_ui as ui;
+use dot_repl_ui::error::{ErrorInfo, ErrorLevel, RenderError};
+use dot_repl_ui::LocalBoxFuture;
+use gloo_timers::future::sleep;
+use js_sys::{Array, Function, Promise, Reflect};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::{
+    Blob, BlobPropertyBag, ErrorEvent, Event, MessageEvent, Url, Worker, WorkerOptions, WorkerType,
+};
+
+use crate::bindgen::extract_line_number;
+
+/// How long a render may take before it fails.
+pub const RENDER_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long a worker may take to load viz.js before it fails.
+pub const START_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// The worker. viz.js is the same build the `viz_instance` script in
+/// [WebApp](crate::WebApp) loads.
+const WORKER_SCRIPT: &str = r#"
+import { instance } from 'https://cdn.jsdelivr.net/npm/@viz-js/viz@3.21.0/dist/viz.js';
+
+const viz = await instance();
+self.onmessage = (event) => {
+    const { id, dot } = event.data;
+    try {
+        const result = viz.render(dot, { format: "svg" });
+        if (result.status === "success") {
+            // Without the XML prolog, like the `<svg>` element's outerHTML
+            self.postMessage({ id, svg: result.output.slice(result.output.indexOf("<svg")) });
+        } else {
+            self.postMessage({ id, errors: result.errors });
+        }
+    } catch (error) {
+        self.postMessage({ id, errors: [{ level: "error", message: String(error) }] });
+    }
+};
+self.postMessage({ ready: true });
+"#;
+
+/// A render waiting for, or with, the worker. `resolve` is called with the
+/// worker's response, or with an error message.
+struct Request {
+    id: u64,
+    dot: String,
+    resolve: Function,
+    /// Set once nothing waits for the response any more
+    abandoned: Rc<Cell<bool>>,
+}
+
+struct State {
+    /// Object URL of [WORKER_SCRIPT]
+    script_url: String,
+    worker: Worker,
+    /// Whether the worker has loaded viz.js
+    ready: bool,
+    /// Counts the workers started, so a timer for one leaves its replacement
+    /// alone
+    generation: u64,
+    /// Resolves the future of [WorkerGViz::new] once the first worker is
+    /// ready, or rejects it if it fails to start
+    starting: Option<(Function, Function)>,
+    next_id: u64,
+    in_flight: Option<Request>,
+    queue: VecDeque<Request>,
+    // Kept alive for as long as the worker uses them
+    _onmessage: Closure<dyn FnMut(MessageEvent)>,
+    _onerror: Closure<dyn FnMut(Event)>,
+}
+
+impl Drop for State {
+    fn drop(&mut self) {
+        // Its handlers go with us
+        self.worker.terminate();
+    }
+}
+
+/// A [GraphVizable](ui::GraphVizable) rendering in a Web Worker.
+#[derive(Clone)]
+pub struct WorkerGViz {
+    state: Rc<RefCell<State>>,
+}
+
+impl WorkerGViz {
+    /// Start the worker, and wait until it has loaded viz.js.
+    pub async fn new() -> Result<Self, String> {
+        let script = Array::of1(&JsValue::from_str(WORKER_SCRIPT));
+        let options = BlobPropertyBag::new();
+        options.set_type("text/javascript");
+        let blob = Blob::new_with_str_sequence_and_options(&script, &options)
+            .map_err(|e| format!("Failed to create the worker script: {:?}", e))?;
+        let script_url = Url::create_object_url_with_blob(&blob)
+            .map_err(|e| format!("Failed to create the worker script: {:?}", e))?;
+
+        let worker = start_worker(&script_url)
+            .map_err(|e| format!("Failed to start the render worker: {:?}", e))?;
+        let state = Rc::new_cyclic(|weak: &Weak<RefCell<State>>| {
+            let (onmessage, onerror) = listen(&worker, weak);
+            RefCell::new(State {
+                script_url,
+                worker,
+                ready: false,
+                generation: 0,
+                starting: None,
+                next_id: 0,
+                in_flight: None,
+                queue: VecDeque::new(),
+                _onmessage: onmessage,
+                _onerror: onerror,
+            })
+        });
+        let started = Promise::new(&mut |resolve, reject| {
+            state.borrow_mut().starting = Some((resolve, reject));
+        });
+        watch_start(&state);
+        JsFuture::from(started)
+            .await
+            .map_err(|e| format!("The render worker failed to start: {:?}", e))?;
+        Ok(WorkerGViz { state })
+    }
+
+    async fn render(&self, dot: &str) -> Result<String, ui::Error> {
+        let abandoned = Rc::new(Cell::new(false));
+        let response = Promise::new(&mut |resolve, _| {
+            let mut state = self.state.borrow_mut();
+            state.next_id += 1;
+            // The newest request takes the place of those nobody waits for
+            state.queue.retain(|request| !request.abandoned.get());
+            state.queue.push_back(Request {
+                id: state.next_id,
+                dot: dot.to_string(),
+                resolve,
+                abandoned: abandoned.clone(),
+            });
+        });
+        let _abandon = Abandon(abandoned);
+        dispatch(&self.state);
+        let response = JsFuture::from(response)
+            .await
+            .unwrap_or_else(|_| JsValue::from_str("The render was rejected"));
+        parse_response(&response)
+    }
+}
+
+impl ui::AsyncGraphVizable for WorkerGViz {
+    type Error = ui::Error;
+    fn render_dot<'a>(&'a self, dot: &'a str) -> LocalBoxFuture<'a, Result<String, Self::Error>> {
+        Box::pin(self.render(dot))
+    }
+}
+
+/// Marks a request abandoned when its future is dropped, so it is not sent to
+/// the worker. The queue may be borrowed at that point, so the request is
+/// taken out of it later.
+struct Abandon(Rc<Cell<bool>>);
+
+impl Drop for Abandon {
+    fn drop(&mut self) {
+        self.0.set(true);
+    }
+}
+
+fn start_worker(script_url: &str) -> Result<Worker, JsValue> {
+    let options = WorkerOptions::new();
+    options.set_type(WorkerType::Module);
+    Worker::new_with_options(script_url, &options)
+}
+
+/// Have `worker` answer to `state`. The handlers must be kept while it runs.
+fn listen(
+    worker: &Worker,
+    state: &Weak<RefCell<State>>,
+) -> (Closure<dyn FnMut(MessageEvent)>, Closure<dyn FnMut(Event)>) {
+    let messages = state.clone();
+    let onmessage = Closure::<dyn FnMut(MessageEvent)>::new(move |event: MessageEvent| {
+        if let Some(state) = messages.upgrade() {
+            on_message(&state, event.data());
+        }
+    });
+    let errors = state.clone();
+    let onerror = Closure::<dyn FnMut(Event)>::new(move |event: Event| {
+        // A module that fails to load gets a plain `Event`
+        let message = event
+            .dyn_ref::<ErrorEvent>()
+            .map_or_else(|| "it failed to load".to_string(), ErrorEvent::message);
+        if let Some(state) = errors.upgrade() {
+            on_error(&state, message);
+        }
+    });
+    worker.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
+    worker.set_onerror(Some(onerror.as_ref().unchecked_ref()));
+    (onmessage, onerror)
+}
+
+fn on_message(state: &Rc<RefCell<State>>, data: JsValue) {
+    {
+        let mut state = state.borrow_mut();
+        if Reflect::get(&data, &"ready".into()).is_ok_and(|ready| ready.is_truthy()) {
+            state.ready = true;
+            if let Some((resolve, _)) = state.starting.take() {
+                let _ = resolve.call0(&JsValue::NULL);
+            }
+        } else {
+            let id = Reflect::get(&data, &"id".into())
+                .ok()
+                .and_then(|id| id.as_f64());
+            match state.in_flight.take() {
+                Some(request) if Some(request.id as f64) == id => {
+                    let _ = request.resolve.call1(&JsValue::NULL, &data);
+                }
+                // The answer to a request that timed out
+                other => state.in_flight = other,
+            }
+        }
+    }
+    dispatch(state);
+}
+
+/// The worker failed outside of a render, most likely while loading viz.js.
+fn on_error(state: &Rc<RefCell<State>>, message: String) {
+    tracing::error!("Render worker error: {}", message);
+    let mut state = state.borrow_mut();
+    if state.ready {
+        return;
+    }
+    if let Some((_, reject)) = state.starting.take() {
+        let _ = reject.call1(&JsValue::NULL, &JsValue::from_str(&message));
+    }
+    // A replacement that won't start: fail what is waiting for it
+    fail_all(&mut state, &format!("The render worker failed: {message}"));
+}
+
+/// Answer every request with `message`.
+fn fail_all(state: &mut State, message: &str) {
+    let failure = JsValue::from_str(message);
+    for request in state
+        .in_flight
+        .take()
+        .into_iter()
+        .chain(state.queue.drain(..))
+    {
+        let _ = request.resolve.call1(&JsValue::NULL, &failure);
+    }
+}
+
+/// Send the next request to the worker, if it is free.
+fn dispatch(state: &Rc<RefCell<State>>) {
+    let id = {
+        let mut guard = state.borrow_mut();
+        let state = &mut *guard;
+        if !state.ready || state.in_flight.is_some() {
+            return;
+        }
+        state.queue.retain(|request| !request.abandoned.get());
+        let Some(request) = state.queue.pop_front() else {
+            return;
+        };
+        let message = js_sys::Object::new();
+        let _ = Reflect::set(
+            &message,
+            &"id".into(),
+            &JsValue::from_f64(request.id as f64),
+        );
+        let _ = Reflect::set(&message, &"dot".into(), &JsValue::from_str(&request.dot));
+        if let Err(e) = state.worker.post_message(&message) {
+            let failure = JsValue::from_str(&format!("Failed to send the render: {:?}", e));
+            let _ = request.resolve.call1(&JsValue::NULL, &failure);
+            return;
+        }
+        let id = request.id;
+        state.in_flight = Some(request);
+        id
+    };
+
+    let state = Rc::downgrade(state);
+    spawn_local(async move {
+        sleep(RENDER_TIMEOUT).await;
+        if let Some(state) = state.upgrade() {
+            time_out(&state, id);
+        }
+    });
+}
+
+/// Fail request `id` if the worker is still on it, and replace the worker.
+fn time_out(state: &Rc<RefCell<State>>, id: u64) {
+    {
+        let mut state = state.borrow_mut();
+        if state.in_flight.as_ref().map(|request| request.id) != Some(id) {
+            return;
+        }
+        let request = state.in_flight.take().expect("checked above");
+        let failure = JsValue::from_str(&format!(
+            "Rendering took longer than {}s",
+            RENDER_TIMEOUT.as_secs()
+        ));
+        let _ = request.resolve.call1(&JsValue::NULL, &failure);
+
+        tracing::warn!("Render timed out, restarting the render worker");
+        state.worker.terminate();
+    }
+    let weak = Rc::downgrade(state);
+    {
+        let mut state = state.borrow_mut();
+        state.ready = false;
+        state.generation += 1;
+        match start_worker(&state.script_url) {
+            Ok(worker) => {
+                let (onmessage, onerror) = listen(&worker, &weak);
+                state.worker = worker;
+                state._onmessage = onmessage;
+                state._onerror = onerror;
+            }
+            Err(e) => {
+                fail_all(
+                    &mut state,
+                    &format!("Failed to restart the render worker: {:?}", e),
+                );
+                return;
+            }
+        }
+    }
+    watch_start(state);
+}
+
+/// Give up on the current worker if it hasn't loaded viz.js within
+/// [START_TIMEOUT], e.g. because the CDN hangs.
+fn watch_start(state: &Rc<RefCell<State>>) {
+    let generation = state.borrow().generation;
+    let state = Rc::downgrade(state);
+    spawn_local(async move {
+        sleep(START_TIMEOUT).await;
+        let Some(state) = state.upgrade() else {
+            return;
+        };
+        let mut state = state.borrow_mut();
+        if state.ready || state.generation != generation {
+            return;
+        }
+        let message = format!("viz.js didn't load within {}s", START_TIMEOUT.as_secs());
+        tracing::error!("Render worker error: {}", message);
+        if let Some((_, reject)) = state.starting.take() {
+            let _ = reject.call1(&JsValue::NULL, &JsValue::from_str(&message));
+        }
+        fail_all(&mut state, &format!("The render worker failed: {message}"));
+    });
+}
+
+/// The SVG in a worker response, or what went wrong.
+fn parse_response(response: &JsValue) -> Result<String, ui::Error> {
+    if let Some(message) = response.as_string() {
+        return Err(render_error(vec![ErrorInfo {
+            level: ErrorLevel::Error,
+            message,
+            line: None,
+        }]));
+    }
+    if let Some(svg) = Reflect::get(response, &"svg".into())
+        .ok()
+        .and_then(|svg| svg.as_string())
+    {
+        return Ok(svg);
+    }
+    let errors = Reflect::get(response, &"errors".into())
+        .ok()
+        .and_then(|errors| errors.dyn_into::<Array>().ok())
+        .unwrap_or_default();
+    let mut errors: Vec<ErrorInfo> = errors
+        .iter()
+        .map(|error| {
+            let field = |name: &str| {
+                Reflect::get(&error, &name.into())
+                    .ok()
+                    .and_then(|value| value.as_string())
+                    .unwrap_or_default()
+            };
+            let message = field("message");
+            ErrorInfo {
+                level: match field("level").as_str() {
+                    "error" => ErrorLevel::Error,
+                    "warning" => ErrorLevel::Warning,
+                    _ => ErrorLevel::Info,
+                },
+                line: extract_line_number(&message),
+                message,
+            }
+        })
+        .collect();
+    if errors.is_empty() {
+        errors.push(ErrorInfo {
+            level: ErrorLevel::Error,
+            message: "Graphviz failed without saying why".to_string(),
+            line: None,
+        });
+    }
+    Err(render_error(errors))
+}
+
+fn render_error(errors: Vec<ErrorInfo>) -> ui::Error {
+    ui::Error::DotRenderError(RenderError { errors })
+}